@@ -1,19 +1,242 @@
-// there will be more here when i add support for more games/mods
-// for now just p1 wakeup and glados death :)
+// timing rules decide where the time of a demo starts and ends
+// every rule has a trigger, when the trigger fires on some tick the start or end of the time gets moved there
+// the portal 1 ones (wakeup and glados death) are built in, other categories can load their own rules from a file:
+//
+//   [[rule]]
+//   name = "wakeup"            # shows up next to the time, defaults to the trigger
+//   type = "start"             # start or end
+//   trigger = "fix_angle"      # see Trigger below for every trigger and the keys it needs
+//   angle = [0, 189.99756, 0]
+//   offset = 1                 # ticks added to the tick the trigger fired on, default 0
+//   map = "testchmb_a_00"      # optional, the rule only works on this map (the map trigger needs it)
+//
+// if rules fire more than once the latest tick wins, for both the start and the end
 
-use crate::structs::{net_svc_message::NetSvcMessage, data_manager::DataManager, netsvc_types::SvcFixAngle,
-    net_svc_message::NetSvcMessageTypes::SvcFixAngle as fix_angle_type
+use std::path::Path;
+use crate::config::{load_config, parse_toml, ConfigError};
+use crate::json::JsonValue;
+use crate::structs::{net_svc_message::NetSvcMessage, data_manager::DataManager,
+    net_svc_message::NetSvcMessageDataTypes, entities::EntityPropValue, utils::Vec3
 };
 
-pub fn try_adjust_for_wakeup(messages: &Vec<NetSvcMessage>, tick: i32, data_mgr: &mut DataManager) {
-    let data: SvcFixAngle = messages.iter().find(|m| {m.msg_type == fix_angle_type}).unwrap().data.clone().into();
-    if data.angle == vec![0f32, 189.99756f32, 0f32] {
-        data_mgr.adj_start_tick = tick + 1; // plus one because yes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleType {
+    Start,
+    End,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trigger {
+    ConsoleCommand(String), // command = "...", the whole ConsoleCmd packet has to match
+    FixAngle(Vec<f32>), // angle = [pitch, yaw, roll], an SvcFixAngle that sets the view to exactly this
+    Position(Vec3, Vec3), // min = [x, y, z], max = [x, y, z], the view origin going into the box
+    // class = "CPropPortal", prop = "m_bActivated", value = "1"
+    // demos dont have entity outputs so this is a prop of an entity of that class getting set to the value instead
+    // only works when entities are parsed, so not for info and time unless theres a rule like this
+    Entity(String, String, String),
+    Map, // the first tick (start) or last tick (end) of a demo on the map of the rule
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingRule {
+    pub name: String,
+    pub rule_type: RuleType,
+    pub trigger: Trigger,
+    pub map: Option<String>,
+    pub offset: i32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimingRules {
+    pub rules: Vec<TimingRule>,
+}
+
+impl TimingRules {
+    // the rules iipdp always had, portal 1 any%
+    pub fn portal() -> Self {
+        return Self { rules: vec![
+            TimingRule {
+                name: "wakeup".to_string(),
+                rule_type: RuleType::Start,
+                trigger: Trigger::FixAngle(vec![0f32, 189.99756f32, 0f32]),
+                map: None,
+                offset: 1, // plus one because yes
+            },
+            TimingRule {
+                name: "glados death".to_string(),
+                rule_type: RuleType::End,
+                trigger: Trigger::ConsoleCommand("startneurotoxins 99999".to_string()),
+                map: None,
+                offset: 1, // again plus one because yes
+            },
+        ] };
+    }
+
+    // toml, or json if the file ends in .json
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        return Self::from_config(&load_config(path)?);
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        return Self::from_config(&parse_toml(text)?);
+    }
+
+    // takes the parsed config file, the rules are in a list called rule
+    pub fn from_config(config: &JsonValue) -> Result<Self, ConfigError> {
+        let Some(rules) = config.get("rule").and_then(|r| r.as_array()) else {
+            return Err(ConfigError::new(0, "no [[rule]] in the rules file".to_string()));
+        };
+        let rules = rules.iter().enumerate()
+            .map(|(i, rule)| TimingRule::from_config(rule).map_err(|err| ConfigError::new(0, format!("rule {}: {}", i + 1, err))))
+            .collect::<Result<Vec<TimingRule>, ConfigError>>()?;
+        return Ok(Self { rules: rules });
+    }
+
+    // entity rules need the slow full parse
+    pub fn needs_entities(&self) -> bool {
+        return self.rules.iter().any(|r| matches!(r.trigger, Trigger::Entity(..)));
     }
 }
 
-pub fn try_adjust_for_glados_death(command: &String, tick: i32, data_mgr: &mut DataManager) {
-    if command == "startneurotoxins 99999" {
-        data_mgr.adj_end_tick = tick + 1 // again plus one because yes
+impl TimingRule {
+    fn from_config(rule: &JsonValue) -> Result<Self, String> {
+        let get_str = |key: &str| -> Result<String, String> {
+            return rule.get(key).and_then(|v| v.as_str()).map(|v| v.to_string()).ok_or(format!("needs {} = \"...\"", key));
+        };
+        let get_floats = |key: &str| -> Result<Vec<f32>, String> {
+            let values = rule.get(key).and_then(|v| v.as_array()).ok_or(format!("needs {} = [x, y, z]", key))?;
+            let floats: Vec<f32> = values.iter().filter_map(|v| v.as_f64()).map(|v| v as f32).collect();
+            if floats.len() != 3 || values.len() != 3 {
+                return Err(format!("{} has to be a list of 3 numbers", key));
+            }
+            return Ok(floats);
+        };
+
+        let rule_type = match get_str("type")?.as_str() {
+            "start" => RuleType::Start,
+            "end" => RuleType::End,
+            other => return Err(format!("type has to be start or end, not \"{}\"", other)),
+        };
+        let trigger_name = get_str("trigger")?;
+        let trigger = match trigger_name.as_str() {
+            "console_command" => Trigger::ConsoleCommand(get_str("command")?),
+            "fix_angle" => Trigger::FixAngle(get_floats("angle")?),
+            "position" => {
+                let (min, max) = (get_floats("min")?, get_floats("max")?);
+                Trigger::Position(Vec3 { x: min[0], y: min[1], z: min[2] }, Vec3 { x: max[0], y: max[1], z: max[2] })
+            },
+            "entity" => Trigger::Entity(get_str("class")?, get_str("prop")?, get_str("value")?),
+            "map" => Trigger::Map,
+            other => return Err(format!("unknown trigger \"{}\", it has to be console_command, fix_angle, position, entity or map", other)),
+        };
+        let map = rule.get("map").map(|v| v.as_str().map(|v| v.to_string()).ok_or("map has to be a string".to_string())).transpose()?;
+        if trigger == Trigger::Map && map.is_none() {
+            return Err("the map trigger needs map = \"...\"".to_string());
+        }
+        let offset = match rule.get("offset") {
+            Some(value) => value.as_i64().ok_or("offset has to be a whole number of ticks".to_string())? as i32,
+            None => 0,
+        };
+        let name = match rule.get("name") {
+            Some(value) => value.as_str().ok_or("name has to be a string".to_string())?.to_string(),
+            None => trigger_name,
+        };
+
+        return Ok(Self { name: name, rule_type: rule_type, trigger: trigger, map: map, offset: offset });
+    }
+
+    fn on_map(&self, map_name: &str) -> bool {
+        return self.map.as_ref().map(|m| m == map_name).unwrap_or(true);
     }
 }
+
+// moves the start or end of the time if this is later than what we had
+fn fire(data_mgr: &mut DataManager, rule_index: usize, tick: i32) {
+    let rule = &data_mgr.timing_rules.rules[rule_index];
+    let tick = tick + rule.offset;
+    let (adj_tick, adj_rule) = match rule.rule_type {
+        RuleType::Start => (&mut data_mgr.adj_start_tick, &mut data_mgr.adj_start_rule),
+        RuleType::End => (&mut data_mgr.adj_end_tick, &mut data_mgr.adj_end_rule),
+    };
+    if adj_tick.map(|t| tick >= t).unwrap_or(true) {
+        *adj_tick = Some(tick);
+        *adj_rule = Some(rule.name.clone());
+    }
+}
+
+// every rule with a trigger that matches
+fn matching_rules(data_mgr: &DataManager, matches: impl Fn(&Trigger) -> bool) -> Vec<usize> {
+    return data_mgr.timing_rules.rules.iter().enumerate()
+        .filter(|(_, rule)| rule.on_map(&data_mgr.map_name) && matches(&rule.trigger))
+        .map(|(i, _)| i)
+        .collect();
+}
+
+pub fn try_adjust_for_fix_angle(messages: &[NetSvcMessage], tick: i32, data_mgr: &mut DataManager) {
+    let Some(data) = messages.iter().find_map(|m| match &m.data { NetSvcMessageDataTypes::SvcFixAngle(data) => Some(data), _ => None }) else {
+        return;
+    };
+    for rule_index in matching_rules(data_mgr, |t| matches!(t, Trigger::FixAngle(angle) if *angle == data.angle)) {
+        fire(data_mgr, rule_index, tick);
+    }
+}
+
+pub fn try_adjust_for_console_command(command: &str, tick: i32, data_mgr: &mut DataManager) {
+    for rule_index in matching_rules(data_mgr, |t| matches!(t, Trigger::ConsoleCommand(c) if c == command)) {
+        fire(data_mgr, rule_index, tick);
+    }
+}
+
+// only fires when going into the box, not on every tick inside of it
+pub fn try_adjust_for_position(origin: &Vec3, tick: i32, data_mgr: &mut DataManager) {
+    for rule_index in matching_rules(data_mgr, |t| matches!(t, Trigger::Position(..))) {
+        let Trigger::Position(min, max) = &data_mgr.timing_rules.rules[rule_index].trigger else { continue; };
+        let inside = (min.x..=max.x).contains(&origin.x) && (min.y..=max.y).contains(&origin.y) && (min.z..=max.z).contains(&origin.z);
+
+        if data_mgr.inside_box.len() <= rule_index {
+            data_mgr.inside_box.resize(rule_index + 1, false);
+        }
+        if inside && !data_mgr.inside_box[rule_index] {
+            fire(data_mgr, rule_index, tick);
+        }
+        data_mgr.inside_box[rule_index] = inside;
+    }
+}
+
+// map rules need the last tick and entity rules need the entities from the signon packets, which get read late
+// so these are checked once the whole demo is parsed
+pub fn adjust_after_parsing(data_mgr: &mut DataManager) {
+    for rule_index in matching_rules(data_mgr, |t| *t == Trigger::Map) {
+        let tick = match data_mgr.timing_rules.rules[rule_index].rule_type {
+            RuleType::Start => 0,
+            RuleType::End => data_mgr.last_packet_tick,
+        };
+        fire(data_mgr, rule_index, tick);
+    }
+
+    for rule_index in matching_rules(data_mgr, |t| matches!(t, Trigger::Entity(..))) {
+        let Trigger::Entity(class_name, prop_name, value) = &data_mgr.timing_rules.rules[rule_index].trigger else { continue; };
+        let Some(class_id) = data_mgr.dt_mgr.prop_lookup.iter().position(|(class, _)| class.class_name == *class_name) else { continue; };
+        let Some(prop_index) = data_mgr.dt_mgr.get_prop_index(class_id as i32, prop_name) else { continue; };
+
+        // every tick the prop changed to the value on any entity of the class
+        let ticks: Vec<i32> = data_mgr.entity_mgr.history.iter().flatten()
+            .filter(|lifetime| lifetime.class_id == class_id as i32)
+            .filter_map(|lifetime| lifetime.prop_history.get(&prop_index))
+            .flat_map(|history| history.windows(2).filter(|w| !prop_matches(&w[0].1, value) && prop_matches(&w[1].1, value)).map(|w| w[1].0)
+                .chain(history.first().filter(|(_, v)| prop_matches(v, value)).map(|(t, _)| *t)))
+            .collect();
+        for tick in ticks {
+            fire(data_mgr, rule_index, tick);
+        }
+    }
+}
+
+fn prop_matches(prop: &EntityPropValue, value: &str) -> bool {
+    return match prop {
+        EntityPropValue::Int(i) => value.parse::<i32>().map(|v| v == *i).unwrap_or(false),
+        EntityPropValue::Float(f) => value.parse::<f32>().map(|v| v == *f).unwrap_or(false),
+        EntityPropValue::String(s) => s == value,
+        other => other.to_string() == value,
+    };
+}
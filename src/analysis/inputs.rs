@@ -0,0 +1,184 @@
+use std::io::{self, Write};
+
+use crate::json::{JsonValue, ToJson};
+use crate::structs::demo::Demo;
+use crate::structs::packet::PacketDataType;
+use crate::structs::user_cmd_info::Buttons;
+use crate::structs::utils::Vec3;
+
+// what the player was doing with their keyboard and mouse on every usercmd, usually one per tick
+// a usercmd in a demo only has what changed since the one before it so this carries the rest over
+// usercmds are only read with the full parse
+
+#[derive(Debug, Clone)]
+pub struct TickInput {
+    pub tick: i32,
+    pub command_number: i32,
+    pub view_angles: Vec3,
+    pub forward_move: f32, // positive is forward
+    pub side_move: f32, // positive is right
+    pub up_move: f32,
+    pub buttons: Buttons,
+    pub mouse_dx: i32, // positive is right
+    pub mouse_dy: i32, // positive is down
+}
+
+impl TickInput {
+    pub fn new() -> Self {
+        Self {
+            tick: 0,
+            command_number: 0,
+            view_angles: Vec3::new(),
+            forward_move: 0.0,
+            side_move: 0.0,
+            up_move: 0.0,
+            buttons: Buttons::None,
+            mouse_dx: 0,
+            mouse_dy: 0,
+        }
+    }
+}
+
+// in order, for portal 2 only the first player
+pub fn get_input_track(demo: &Demo) -> Vec<TickInput> {
+    let mut track: Vec<TickInput> = Vec::new();
+    let mut cur = TickInput::new();
+
+    for packet in &demo.packets {
+        let PacketDataType::UserCmd(user_cmd) = &packet.data else { continue; };
+        if packet.slot.unwrap_or(0) != 0 {
+            continue;
+        }
+        let info = &user_cmd.data;
+
+        cur.tick = packet.tick;
+        cur.command_number = info.command_number.unwrap_or(cur.command_number + 1);
+        cur.view_angles = Vec3 {
+            x: info.view_angles_x.unwrap_or(cur.view_angles.x),
+            y: info.view_angles_y.unwrap_or(cur.view_angles.y),
+            z: info.view_angles_z.unwrap_or(cur.view_angles.z),
+        };
+        cur.forward_move = info.forward_move.unwrap_or(cur.forward_move);
+        cur.side_move = info.side_move.unwrap_or(cur.side_move);
+        cur.up_move = info.up_move.unwrap_or(cur.up_move);
+        cur.buttons = info.buttons.unwrap_or(cur.buttons);
+        cur.mouse_dx = info.mouse_dx.unwrap_or(cur.mouse_dx);
+        cur.mouse_dy = info.mouse_dy.unwrap_or(cur.mouse_dy);
+
+        track.push(cur.clone());
+    }
+
+    return track;
+}
+
+// the buttons that get a row in the timeline and a field in the json, movement keys go by the action and not the key so rebinds dont matter
+const SHOWN_BUTTONS: [(&str, Buttons, &str); 9] = [
+    ("forward", Buttons::Forward, "#4caf50"),
+    ("back", Buttons::Back, "#4caf50"),
+    ("left", Buttons::MoveLeft, "#2196f3"),
+    ("right", Buttons::MoveRight, "#2196f3"),
+    ("jump", Buttons::Jump, "#ff9800"),
+    ("duck", Buttons::Duck, "#9c27b0"),
+    ("use", Buttons::Use, "#795548"),
+    ("attack", Buttons::Attack, "#1e88e5"), // blue portal
+    ("attack2", Buttons::Attack2, "#fb8c00"), // orange portal
+];
+
+impl ToJson for TickInput {
+    fn to_json(&self) -> JsonValue {
+        let mut fields = vec![
+            ("tick", self.tick.to_json()),
+            ("command_number", self.command_number.to_json()),
+        ];
+        for (name, button, _) in SHOWN_BUTTONS {
+            fields.push((name, self.buttons.contains(button).to_json()));
+        }
+        fields.extend([
+            ("forward_move", self.forward_move.to_json()),
+            ("side_move", self.side_move.to_json()),
+            ("mouse_dx", self.mouse_dx.to_json()),
+            ("mouse_dy", self.mouse_dy.to_json()),
+            ("pitch", self.view_angles.x.to_json()),
+            ("yaw", self.view_angles.y.to_json()),
+        ]);
+        return JsonValue::object(fields);
+    }
+}
+
+// one usercmd per line so its easy to read in as a stream for input overlays
+pub fn write_input_track_json<W: Write>(writer: &mut W, track: &[TickInput], tick_interval: f32) -> io::Result<()> {
+    writeln!(writer, "{{")?;
+    writeln!(writer, "  \"tick_interval\": {},", tick_interval.to_json())?;
+    writeln!(writer, "  \"inputs\": [")?;
+    for (i, input) in track.iter().enumerate() {
+        writeln!(writer, "    {}{}", input.to_json(), if i + 1 < track.len() { "," } else { "" })?;
+    }
+    writeln!(writer, "  ]")?;
+    writeln!(writer, "}}")?;
+    return Ok(());
+}
+
+const SVG_TICK_WIDTH: i32 = 2;
+const SVG_LABEL_WIDTH: i32 = 70;
+const SVG_ROW_HEIGHT: i32 = 18;
+const SVG_MOUSE_HEIGHT: i32 = 80;
+const SVG_AXIS_HEIGHT: i32 = 20;
+
+// a row per button with a bar wherever its held, then the mouse movement and the time in seconds at the bottom
+pub fn write_input_timeline_svg<W: Write>(writer: &mut W, track: &[TickInput], tick_interval: f32) -> io::Result<()> {
+    let first_tick = track.first().map(|t| t.tick).unwrap_or(0);
+    let last_tick = track.last().map(|t| t.tick).unwrap_or(0);
+    let x_of = |tick: i32| SVG_LABEL_WIDTH + (tick - first_tick) * SVG_TICK_WIDTH;
+    let buttons_height = SHOWN_BUTTONS.len() as i32 * SVG_ROW_HEIGHT;
+    let width = x_of(last_tick + 1);
+    let height = buttons_height + SVG_MOUSE_HEIGHT + SVG_AXIS_HEIGHT;
+
+    writeln!(writer, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="12">"#, width, height)?;
+    writeln!(writer, r#"<rect width="{}" height="{}" fill="white"/>"#, width, height)?;
+
+    for (row, (name, button, color)) in SHOWN_BUTTONS.iter().enumerate() {
+        let y = row as i32 * SVG_ROW_HEIGHT;
+        writeln!(writer, r#"<text x="4" y="{}">{}</text>"#, y + 13, name)?;
+        // a usercmd lasts until the next one, held ticks in a row are one bar
+        let mut start: Option<i32> = None;
+        for (i, input) in track.iter().enumerate() {
+            let end = track.get(i + 1).map(|next| next.tick).unwrap_or(input.tick + 1);
+            if input.buttons.contains(*button) {
+                start.get_or_insert(input.tick);
+            }
+            if let Some(bar_start) = start.filter(|_| !input.buttons.contains(*button) || i + 1 == track.len()) {
+                let bar_end = if input.buttons.contains(*button) { end } else { input.tick };
+                writeln!(writer, r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                    x_of(bar_start), y + 2, (bar_end - bar_start) * SVG_TICK_WIDTH, SVG_ROW_HEIGHT - 4, color)?;
+                start = None;
+            }
+        }
+    }
+
+    // dx and dy as lines around the middle of the row, scaled to the biggest movement in the demo
+    let mouse_middle = buttons_height + SVG_MOUSE_HEIGHT / 2;
+    let max_delta = track.iter().map(|t| t.mouse_dx.abs().max(t.mouse_dy.abs())).max().unwrap_or(0).max(1);
+    let scale = (SVG_MOUSE_HEIGHT / 2 - 2) as f32 / max_delta as f32;
+    writeln!(writer, r#"<text x="4" y="{}">mouse</text>"#, mouse_middle - 2)?;
+    writeln!(writer, r##"<text x="4" y="{}" fill="#e53935">dx</text><text x="28" y="{}" fill="#8e24aa">dy</text>"##, mouse_middle + 12, mouse_middle + 12)?;
+    writeln!(writer, r##"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="#cccccc"/>"##, SVG_LABEL_WIDTH, mouse_middle, width, mouse_middle)?;
+    for (color, get_delta) in [("#e53935", (|t: &TickInput| t.mouse_dx) as fn(&TickInput) -> i32), ("#8e24aa", |t: &TickInput| t.mouse_dy)] {
+        let points: Vec<String> = track.iter()
+            .map(|t| format!("{},{:.1}", x_of(t.tick) + SVG_TICK_WIDTH / 2, mouse_middle as f32 - get_delta(t) as f32 * scale))
+            .collect();
+        writeln!(writer, r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="1"/>"#, points.join(" "), color)?;
+    }
+
+    // a mark every second
+    let axis_y = buttons_height + SVG_MOUSE_HEIGHT;
+    let ticks_per_second = 1.0 / tick_interval;
+    let mut second = 0;
+    while (second as f32 * ticks_per_second) as i32 <= last_tick - first_tick {
+        let x = x_of(first_tick + (second as f32 * ticks_per_second).round() as i32);
+        writeln!(writer, r##"<line x1="{}" y1="0" x2="{}" y2="{}" stroke="#eeeeee"/><text x="{}" y="{}">{}s</text>"##, x, x, axis_y, x + 2, axis_y + 14, second)?;
+        second += 1;
+    }
+
+    writeln!(writer, "</svg>")?;
+    return Ok(());
+}
@@ -0,0 +1,8 @@
+// things we figure out from the parsed demo, as opposed to the structs folder which is what the demo actually contains
+
+pub mod view_track;
+pub mod run;
+pub mod player_state;
+pub mod portals;
+pub mod inputs;
+pub mod movement;
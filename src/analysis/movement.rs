@@ -0,0 +1,157 @@
+use crate::analysis::inputs::{get_input_track, TickInput};
+use crate::analysis::player_state::{get_player_states, PlayerState};
+use crate::structs::demo::Demo;
+use crate::structs::user_cmd_info::Buttons;
+
+// how the player jumped and strafed, for reviewing runs
+// the inputs come from the usercmds and when the player left and hit the ground from the player entity (see player_state.rs)
+// both need the full parse, without the player entity only the jump presses and the sync over every tick are there
+
+// a jump at most this many ticks after landing counts as a bhop attempt
+pub const BHOP_WINDOW: i32 = 10;
+
+#[derive(Debug, Clone)]
+pub struct MovementStats {
+    pub jump_presses: i32,
+    pub jumps: i32, // times the player actually jumped off the ground
+    pub crouch_jumps: i32, // jumps where duck was held at some point before landing again
+    pub bhop_attempts: i32, // jumps that came at most BHOP_WINDOW ticks after landing
+    pub perfect_bhops: i32, // jumps on the first tick on the ground, those dont lose any speed to friction
+    pub bhop_ground_ticks: i32, // ticks on the ground before every bhop attempt added up, for the average
+    pub strafe_ticks: i32, // ticks in the air where the mouse moved and a strafe key was held
+    pub synced_ticks: i32, // the ones where the mouse and the strafe key went the same way
+    pub has_player_states: bool,
+}
+
+impl MovementStats {
+    pub fn new() -> Self {
+        Self {
+            jump_presses: 0,
+            jumps: 0,
+            crouch_jumps: 0,
+            bhop_attempts: 0,
+            perfect_bhops: 0,
+            bhop_ground_ticks: 0,
+            strafe_ticks: 0,
+            synced_ticks: 0,
+            has_player_states: false,
+        }
+    }
+
+    // for a folder of demos
+    pub fn add(&mut self, other: &MovementStats) {
+        self.jump_presses += other.jump_presses;
+        self.jumps += other.jumps;
+        self.crouch_jumps += other.crouch_jumps;
+        self.bhop_attempts += other.bhop_attempts;
+        self.perfect_bhops += other.perfect_bhops;
+        self.bhop_ground_ticks += other.bhop_ground_ticks;
+        self.strafe_ticks += other.strafe_ticks;
+        self.synced_ticks += other.synced_ticks;
+        self.has_player_states |= other.has_player_states;
+    }
+
+    // 0 to 1, None if there werent any bhop attempts
+    pub fn perfect_bhop_ratio(&self) -> Option<f32> {
+        if self.bhop_attempts == 0 {
+            return None;
+        }
+        return Some(self.perfect_bhops as f32 / self.bhop_attempts as f32);
+    }
+
+    // how long after landing the player jumped again on average, 1 is perfect
+    pub fn average_bhop_ground_ticks(&self) -> Option<f32> {
+        if self.bhop_attempts == 0 {
+            return None;
+        }
+        return Some(self.bhop_ground_ticks as f32 / self.bhop_attempts as f32);
+    }
+
+    // 0 to 1, None if the player never strafed
+    pub fn strafe_sync(&self) -> Option<f32> {
+        if self.strafe_ticks == 0 {
+            return None;
+        }
+        return Some(self.synced_ticks as f32 / self.strafe_ticks as f32);
+    }
+}
+
+pub fn get_movement_stats(demo: &Demo) -> MovementStats {
+    let inputs = get_input_track(demo);
+    let states = get_player_states(demo);
+    let mut stats = MovementStats::new();
+    stats.has_player_states = !states.is_empty();
+
+    let mut last_buttons = Buttons::None;
+    for input in &inputs {
+        if input.buttons.contains(Buttons::Jump) && !last_buttons.contains(Buttons::Jump) {
+            stats.jump_presses += 1;
+        }
+        last_buttons = input.buttons;
+
+        // without the player entity every tick counts as in the air
+        let in_air = !stats.has_player_states || state_at_tick(&states, input.tick).map(|s| !s.on_ground).unwrap_or(false);
+        if in_air {
+            add_strafe(&mut stats, input);
+        }
+    }
+
+    let mut was_on_ground: Option<bool> = None;
+    let mut landing_tick: Option<i32> = None;
+    let mut jumping = false; // in the air because of a jump
+    let mut ducked_in_jump = false;
+    let mut input_index = 0;
+    for state in &states {
+        // the last usercmd on or before this tick
+        while input_index + 1 < inputs.len() && inputs[input_index + 1].tick <= state.tick {
+            input_index += 1;
+        }
+        let buttons = inputs.get(input_index).filter(|i| i.tick <= state.tick).map(|i| i.buttons).unwrap_or(Buttons::None);
+
+        if was_on_ground == Some(true) && !state.on_ground {
+            // walking off a ledge isnt a jump
+            jumping = buttons.contains(Buttons::Jump) && state.velocity.z > 0.0;
+            ducked_in_jump = false;
+            if jumping {
+                stats.jumps += 1;
+                if let Some(ground_ticks) = landing_tick.map(|tick| state.tick - tick).filter(|t| *t <= BHOP_WINDOW) {
+                    stats.bhop_attempts += 1;
+                    stats.bhop_ground_ticks += ground_ticks;
+                    if ground_ticks <= 1 {
+                        stats.perfect_bhops += 1;
+                    }
+                }
+            }
+        } else if was_on_ground == Some(false) && state.on_ground {
+            landing_tick = Some(state.tick);
+            jumping = false;
+        }
+
+        if jumping && !ducked_in_jump && buttons.contains(Buttons::Duck) {
+            stats.crouch_jumps += 1;
+            ducked_in_jump = true;
+        }
+        was_on_ground = Some(state.on_ground);
+    }
+
+    return stats;
+}
+
+// turning right while holding the right strafe key (or left and left) is synced
+fn add_strafe(stats: &mut MovementStats, input: &TickInput) {
+    if input.mouse_dx == 0 || input.side_move == 0.0 {
+        return;
+    }
+    stats.strafe_ticks += 1;
+    if (input.mouse_dx > 0) == (input.side_move > 0.0) {
+        stats.synced_ticks += 1;
+    }
+}
+
+fn state_at_tick(states: &[PlayerState], tick: i32) -> Option<&PlayerState> {
+    let count = states.partition_point(|s| s.tick <= tick);
+    if count == 0 {
+        return None;
+    }
+    return Some(&states[count - 1]);
+}
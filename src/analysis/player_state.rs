@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::structs::demo::Demo;
+use crate::structs::entities::EntityPropValue;
+use crate::structs::entity_manager::EntityLifetime;
+use crate::structs::net_svc_message::NetSvcMessageDataTypes;
+use crate::structs::packet::{PacketDataType, PacketType};
+use crate::structs::utils::Vec3;
+
+// where the player actually is and how fast theyre moving on every tick, from the props of the player entity
+// unlike the view track this is the origin of the player (their feet) and the velocity the game uses,
+// so it doesnt move when you duck and it has vertical speed for jumps
+// needs the entities from the full parse, without them theres nothing here
+
+// bits of m_fFlags
+pub const FL_ONGROUND: i32 = 1;
+pub const FL_DUCKING: i32 = 1 << 1; // fully ducked
+
+#[derive(Debug, Clone)]
+pub struct PlayerState {
+    pub tick: i32,
+    pub origin: Vec3,
+    pub velocity: Vec3,
+    pub horizontal_speed: f32, // units per second
+    pub flags: i32, // m_fFlags
+    pub on_ground: bool,
+    pub ducked: bool,
+    pub ducking: bool, // in the middle of ducking or standing back up
+}
+
+// the entity index of the player whose demo it is, the player slot from SvcServerInfo + 1
+pub fn get_local_player_index(demo: &Demo) -> i32 {
+    for packet in &demo.packets {
+        let PacketDataType::Packet(data) = &packet.data else { continue; };
+        for message in &data.messages {
+            if let NetSvcMessageDataTypes::SvcServerInfo(info) = &message.data {
+                return info.player_slot + 1;
+            }
+        }
+    }
+    return 1;
+}
+
+// one entry per tick the local player entity exists on, same ticks as the view track
+pub fn get_player_states(demo: &Demo) -> Vec<PlayerState> {
+    let mut states: Vec<PlayerState> = Vec::new();
+    let player_index = get_local_player_index(demo);
+    let mut prop_indices: HashMap<(i32, &str), Vec<usize>> = HashMap::new(); // (class id, prop name) -> indices, so they only get looked up once
+
+    for packet in &demo.packets {
+        if packet.packet_type != PacketType::Packet || packet.tick < 0 {
+            continue;
+        }
+        let Some(lifetime) = demo.data_manager.entity_mgr.get_lifetime_at_tick(player_index, packet.tick) else { continue; };
+
+        let mut get_prop = |name: &'static str| -> Option<&EntityPropValue> {
+            let indices = prop_indices.entry((lifetime.class_id, name))
+                .or_insert_with(|| demo.data_manager.dt_mgr.get_prop_indices(lifetime.class_id, name));
+            return latest_prop(lifetime, indices, packet.tick);
+        };
+
+        // newer engines send x and y together and z on its own
+        let origin = match get_prop("m_vecOrigin") {
+            Some(EntityPropValue::Vector3(origin)) => origin.clone(),
+            Some(EntityPropValue::Vector2(xy)) => Vec3 { x: xy.x, y: xy.y, z: get_prop("m_vecOrigin[2]").map(prop_to_f32).unwrap_or(0.0) },
+            _ => continue,
+        };
+        let velocity = Vec3 {
+            x: get_prop("m_vecVelocity[0]").map(prop_to_f32).unwrap_or(0.0),
+            y: get_prop("m_vecVelocity[1]").map(prop_to_f32).unwrap_or(0.0),
+            z: get_prop("m_vecVelocity[2]").map(prop_to_f32).unwrap_or(0.0),
+        };
+        let flags = get_prop("m_fFlags").map(prop_to_f32).unwrap_or(0.0) as i32;
+        let ducked = flags & FL_DUCKING != 0 || get_prop("m_bDucked").map(prop_to_f32).unwrap_or(0.0) != 0.0;
+        let ducking = get_prop("m_bDucking").map(prop_to_f32).unwrap_or(0.0) != 0.0;
+
+        if states.last().map(|s| s.tick == packet.tick).unwrap_or(false) {
+            states.pop();
+        }
+        states.push(PlayerState {
+            tick: packet.tick,
+            horizontal_speed: (velocity.x * velocity.x + velocity.y * velocity.y).sqrt(),
+            origin: origin,
+            velocity: velocity,
+            flags: flags,
+            on_ground: flags & FL_ONGROUND != 0,
+            ducked: ducked,
+            ducking: ducking,
+        });
+    }
+
+    return states;
+}
+
+// the player has some props in both the local and the non local table but only gets sent one of them
+// so out of all the props with the name the one that changed last wins
+pub fn latest_prop<'a>(lifetime: &'a EntityLifetime, indices: &[usize], tick: i32) -> Option<&'a EntityPropValue> {
+    return indices.iter()
+        .filter_map(|index| lifetime.get_prop_change_at_tick(*index, tick))
+        .max_by_key(|(changed_tick, _)| *changed_tick)
+        .map(|(_, value)| value);
+}
+
+pub fn prop_to_f32(prop: &EntityPropValue) -> f32 {
+    return match prop {
+        EntityPropValue::Int(value) => *value as f32,
+        EntityPropValue::Float(value) => *value,
+        _ => 0.0,
+    };
+}
+
+pub fn write_player_states_csv<W: Write>(writer: &mut W, states: &[PlayerState]) -> io::Result<()> {
+    writeln!(writer, "tick,origin_x,origin_y,origin_z,velocity_x,velocity_y,velocity_z,horizontal_speed,on_ground,ducked,ducking,flags")?;
+    for s in states {
+        writeln!(writer, "{},{},{},{},{},{},{},{:.3},{},{},{},{}",
+            s.tick,
+            s.origin.x, s.origin.y, s.origin.z,
+            s.velocity.x, s.velocity.y, s.velocity.z,
+            s.horizontal_speed,
+            s.on_ground as i32, s.ducked as i32, s.ducking as i32,
+            s.flags,
+        )?;
+    }
+    return Ok(());
+}
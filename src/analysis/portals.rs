@@ -0,0 +1,233 @@
+use std::io::{self, Write};
+use std::mem::discriminant;
+
+use crate::analysis::player_state::{get_local_player_index, latest_prop, prop_to_f32};
+use crate::structs::demo::Demo;
+use crate::structs::entities::EntityPropValue;
+use crate::structs::entity_manager::EntityLifetime;
+use crate::structs::net_svc_message::NetSvcMessageDataTypes;
+use crate::structs::packet::PacketDataType;
+use crate::structs::user_message::{UserMessageDataType, PortalFizzleType};
+use crate::structs::utils::{EHandle, Vec3};
+
+// every portal that got shot, placed, fizzled and gone through, in tick order
+// shots and placements come from the portalgun and prop_portal entities so they need the full parse,
+// teleports are the EntityPortalled user message and portal 2 also says why a portal fizzled with PortalFXSurface
+// the portalgun only networks which portal it fired last, so shooting the same portal twice in a row is one shot
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PortalColor {
+    Blue, // the first portal, in co-op the colors are different but its still the first and second one
+    Orange,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PortalEventType {
+    Shot,
+    Placed,
+    Fizzled(Option<PortalFizzleType>), // only portal 2 says why
+    Teleported(String), // what went through, "player" or its class name
+}
+
+#[derive(Debug, Clone)]
+pub struct PortalEvent {
+    pub tick: i32,
+    pub event_type: PortalEventType,
+    pub color: Option<PortalColor>,
+    pub portal_index: Option<i32>, // entity index of the prop_portal (the one it went into for teleports), not there for shots
+    pub position: Option<Vec3>, // where the portal is, for teleports where the entity came out
+    pub angles: Option<Vec3>,
+    pub linked: Option<bool>, // if the other portal was already there when this one got placed
+}
+
+impl PortalEvent {
+    fn new(tick: i32, event_type: PortalEventType) -> Self {
+        Self { tick: tick, event_type: event_type, color: None, portal_index: None, position: None, angles: None, linked: None }
+    }
+}
+
+pub fn get_portal_events(demo: &Demo) -> Vec<PortalEvent> {
+    let mut entity_events: Vec<PortalEvent> = Vec::new();
+    for (ent_index, lifetimes) in demo.data_manager.entity_mgr.history.iter().enumerate() {
+        for lifetime in lifetimes {
+            match class_name(demo, lifetime.class_id) {
+                Some("CProp_Portal") => add_portal_events(demo, ent_index as i32, lifetime, &mut entity_events),
+                Some("CWeaponPortalgun") => add_shots(demo, lifetime, &mut entity_events),
+                _ => (),
+            }
+        }
+    }
+
+    let message_events = get_message_events(demo);
+    // PortalFXSurface is about the same placement or fizzle as the entity, but it knows why it fizzled
+    entity_events.retain(|e| !message_events.iter().any(|m| m.tick == e.tick && m.portal_index == e.portal_index && discriminant(&m.event_type) == discriminant(&e.event_type)));
+
+    let mut events = [entity_events, message_events].concat();
+    events.sort_by_key(|e| e.tick);
+    return events;
+}
+
+// placed when it gets activated or moves while its active, fizzled when it stops being active
+fn add_portal_events(demo: &Demo, ent_index: i32, lifetime: &EntityLifetime, events: &mut Vec<PortalEvent>) {
+    let dt_mgr = &demo.data_manager.dt_mgr;
+    let activated = dt_mgr.get_prop_indices(lifetime.class_id, "m_bActivated");
+    let is_portal_2 = dt_mgr.get_prop_indices(lifetime.class_id, "m_bIsPortal2");
+    let origin = dt_mgr.get_prop_indices(lifetime.class_id, "m_vecOrigin");
+    let angles = dt_mgr.get_prop_indices(lifetime.class_id, "m_angRotation");
+    let linked_portal = dt_mgr.get_prop_indices(lifetime.class_id, "m_hLinkedPortal");
+
+    let mut ticks: Vec<i32> = [&activated, &origin, &angles].into_iter().flatten()
+        .filter_map(|index| lifetime.prop_history.get(index))
+        .flat_map(|history| history.iter().map(|(tick, _)| *tick))
+        .collect();
+    ticks.sort();
+    ticks.dedup();
+
+    let mut was_active = false;
+    let mut last_position: Option<Vec3> = None;
+    let mut last_event = PortalEvent::new(0, PortalEventType::Placed);
+    for tick in ticks {
+        let active = latest_prop(lifetime, &activated, tick).map(prop_to_f32).unwrap_or(0.0) != 0.0;
+        let mut event = PortalEvent::new(tick, PortalEventType::Placed);
+        event.portal_index = Some(ent_index);
+        event.color = latest_prop(lifetime, &is_portal_2, tick).map(|v| if prop_to_f32(v) != 0.0 { PortalColor::Orange } else { PortalColor::Blue });
+        event.position = prop_to_vec3(latest_prop(lifetime, &origin, tick));
+        event.angles = prop_to_vec3(latest_prop(lifetime, &angles, tick));
+
+        if active && (!was_active || event.position != last_position) {
+            event.linked = latest_prop(lifetime, &linked_portal, tick)
+                .map(|handle| demo.data_manager.entity_mgr.resolve_ehandle_at_tick(&EHandle { val: prop_to_f32(handle) as i32 }, tick).is_some());
+            events.push(event.clone());
+        } else if !active && was_active {
+            // where it was and not where it goes back to
+            events.push(PortalEvent { tick: tick, event_type: PortalEventType::Fizzled(None), ..last_event.clone() });
+        }
+        was_active = active;
+        last_position = event.position.clone();
+        last_event = event;
+    }
+
+    if let Some(deleted_tick) = lifetime.deleted_tick.filter(|_| was_active) {
+        events.push(PortalEvent { tick: deleted_tick, event_type: PortalEventType::Fizzled(None), ..last_event });
+    }
+}
+
+// m_iLastFiredPortal is 1 for blue and 2 for orange
+fn add_shots(demo: &Demo, lifetime: &EntityLifetime, events: &mut Vec<PortalEvent>) {
+    for index in demo.data_manager.dt_mgr.get_prop_indices(lifetime.class_id, "m_iLastFiredPortal") {
+        for (tick, value) in lifetime.prop_history.get(&index).into_iter().flatten() {
+            let color = match prop_to_f32(value) as i32 {
+                1 => PortalColor::Blue,
+                2 => PortalColor::Orange,
+                _ => continue,
+            };
+            events.push(PortalEvent { color: Some(color), ..PortalEvent::new(*tick, PortalEventType::Shot) });
+        }
+    }
+}
+
+fn get_message_events(demo: &Demo) -> Vec<PortalEvent> {
+    let mut events: Vec<PortalEvent> = Vec::new();
+    let player_index = get_local_player_index(demo);
+
+    for packet in &demo.packets {
+        let PacketDataType::Packet(data) = &packet.data else { continue; };
+        for message in &data.messages {
+            let NetSvcMessageDataTypes::SvcUserMessage(user_message) = &message.data else { continue; };
+            match &user_message.data.data {
+                UserMessageDataType::EntityPortalled(portalled) => {
+                    let ent_index = portalled.portalled.ent_index();
+                    let what = if ent_index == player_index {
+                        "player".to_string()
+                    } else {
+                        demo.data_manager.entity_mgr.get_lifetime_at_tick(ent_index, packet.tick)
+                            .and_then(|l| class_name(demo, l.class_id))
+                            .map(String::from)
+                            .unwrap_or_else(|| format!("entity {}", ent_index))
+                    };
+                    let portal_index = portalled.portal.ent_index();
+                    events.push(PortalEvent {
+                        color: portal_color(demo, portal_index, packet.tick),
+                        portal_index: Some(portal_index),
+                        position: Some(portalled.new_position.clone()),
+                        angles: Some(portalled.new_angles.clone()),
+                        ..PortalEvent::new(packet.tick, PortalEventType::Teleported(what))
+                    });
+                },
+                UserMessageDataType::PortalFXSurface(fx) => {
+                    let event_type = match fx.effect {
+                        PortalFizzleType::PortalFizzleSuccess => PortalEventType::Placed,
+                        PortalFizzleType::PortalFizzleNone => continue,
+                        _ => PortalEventType::Fizzled(Some(fx.effect.clone())),
+                    };
+                    let coords = |c: &[Option<f32>]| Vec3 { x: c[0].unwrap_or(0.0), y: c[1].unwrap_or(0.0), z: c[2].unwrap_or(0.0) };
+                    events.push(PortalEvent {
+                        color: portal_color(demo, fx.portal_ent, packet.tick),
+                        portal_index: Some(fx.portal_ent),
+                        position: Some(coords(&fx.origin)),
+                        angles: Some(coords(&fx.angles)),
+                        ..PortalEvent::new(packet.tick, event_type)
+                    });
+                },
+                _ => (),
+            }
+        }
+    }
+
+    return events;
+}
+
+fn class_name(demo: &Demo, class_id: i32) -> Option<&str> {
+    return demo.data_manager.dt_mgr.prop_lookup.get(class_id as usize).map(|(class, _)| class.class_name.as_str());
+}
+
+fn portal_color(demo: &Demo, portal_index: i32, tick: i32) -> Option<PortalColor> {
+    let lifetime = demo.data_manager.entity_mgr.get_lifetime_at_tick(portal_index, tick)?;
+    let indices = demo.data_manager.dt_mgr.get_prop_indices(lifetime.class_id, "m_bIsPortal2");
+    let is_portal_2 = prop_to_f32(latest_prop(lifetime, &indices, tick)?) != 0.0;
+    return Some(if is_portal_2 { PortalColor::Orange } else { PortalColor::Blue });
+}
+
+fn prop_to_vec3(prop: Option<&EntityPropValue>) -> Option<Vec3> {
+    return match prop {
+        Some(EntityPropValue::Vector3(value)) => Some(value.clone()),
+        _ => None,
+    };
+}
+
+// "what portals did they place where", one line per event
+pub fn write_portal_report<W: Write>(writer: &mut W, events: &[PortalEvent]) -> io::Result<()> {
+    writeln!(writer, "{:>7}  {:<10} {:<7} details", "tick", "event", "portal")?;
+    for event in events {
+        let color = match event.color {
+            Some(PortalColor::Blue) => "blue",
+            Some(PortalColor::Orange) => "orange",
+            None => "?",
+        };
+        let name = match &event.event_type {
+            PortalEventType::Shot => "shot",
+            PortalEventType::Placed => "placed",
+            PortalEventType::Fizzled(_) => "fizzled",
+            PortalEventType::Teleported(_) => "teleport",
+        };
+        let mut details = String::new();
+        if let PortalEventType::Teleported(what) = &event.event_type {
+            details.push_str(&format!("{} came out at ", what));
+        }
+        if let Some(pos) = &event.position {
+            details.push_str(&format!("({:.2}, {:.2}, {:.2})", pos.x, pos.y, pos.z));
+        }
+        if let Some(ang) = &event.angles {
+            details.push_str(&format!(" facing ({:.2}, {:.2}, {:.2})", ang.x, ang.y, ang.z));
+        }
+        match (&event.event_type, event.linked) {
+            (PortalEventType::Fizzled(Some(reason)), _) => details.push_str(&format!(" ({:?})", reason)),
+            (PortalEventType::Placed, Some(true)) => details.push_str(", linked"),
+            (PortalEventType::Placed, Some(false)) => details.push_str(", not linked"),
+            _ => (),
+        }
+        let line = format!("{:>7}  {:<10} {:<7} {}", event.tick, name, color, details.trim_start());
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+    return Ok(());
+}
@@ -0,0 +1,167 @@
+use std::cmp::Ordering;
+use std::io::{self, Write};
+
+use crate::structs::demo::Demo;
+
+// timing a whole run that was recorded as a bunch of demos
+// every save/load and level change starts a new demo, so a map can have any number of demos in a row
+// demos in a row on the same map are one segment, the run goes to the next segment when the map changes
+
+#[derive(Debug, Clone)]
+pub struct RunDemo {
+    pub file_name: String,
+    pub map_name: String,
+    pub ticks: i32, // adjusted, so only the part between the start and end rules if they fired
+    pub time: f32,
+    pub start_rule: Option<String>,
+    pub end_rule: Option<String>,
+}
+
+impl RunDemo {
+    pub fn new(file_name: String, demo: &Demo) -> Self {
+        let (ticks, time) = demo.data_manager.get_adjusted_ticks_and_time();
+        Self {
+            file_name: file_name,
+            map_name: demo.header.map_name.clone(),
+            ticks: ticks,
+            time: time,
+            start_rule: demo.data_manager.adj_start_rule.clone(),
+            end_rule: demo.data_manager.adj_end_rule.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub map_name: String,
+    pub demos: Vec<RunDemo>,
+    pub ticks: i32,
+    pub time: f32,
+    pub cumulative_time: f32, // time of the run at the end of this segment
+}
+
+impl Segment {
+    // every rule that fired in this segment, in the order they fired
+    pub fn rules(&self) -> Vec<String> {
+        let mut rules: Vec<String> = Vec::new();
+        for demo in &self.demos {
+            if let Some(rule) = &demo.start_rule {
+                rules.push(format!("start: {}", rule));
+            }
+            if let Some(rule) = &demo.end_rule {
+                rules.push(format!("end: {}", rule));
+            }
+        }
+        return rules;
+    }
+}
+
+// the demos have to be in the order they were recorded, see natural_cmp for sorting file names
+pub fn get_segments(demos: Vec<RunDemo>) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut cumulative_time = 0.0;
+
+    for demo in demos {
+        cumulative_time += demo.time;
+        match segments.last_mut() {
+            Some(segment) if segment.map_name == demo.map_name => {
+                segment.ticks += demo.ticks;
+                segment.time += demo.time;
+                segment.cumulative_time = cumulative_time;
+                segment.demos.push(demo);
+            },
+            _ => segments.push(Segment {
+                map_name: demo.map_name.clone(),
+                ticks: demo.ticks,
+                time: demo.time,
+                cumulative_time: cumulative_time,
+                demos: vec![demo],
+            }),
+        }
+    }
+
+    return segments;
+}
+
+// compares numbers in file names by value so run_2.dem comes before run_10.dem
+// thats how demo recording names them, plain string sorting would mix up the order of the run
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let mut x_num = String::new();
+                let mut y_num = String::new();
+                while let Some(c) = a.next_if(|c| c.is_ascii_digit()) {
+                    x_num.push(c);
+                }
+                while let Some(c) = b.next_if(|c| c.is_ascii_digit()) {
+                    y_num.push(c);
+                }
+                let (x_num, y_num) = (x_num.trim_start_matches('0'), y_num.trim_start_matches('0'));
+                let ordering = x_num.len().cmp(&y_num.len()).then_with(|| x_num.cmp(y_num));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            },
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a.next();
+                b.next();
+            },
+        }
+    }
+}
+
+// livesplit wants hh:mm:ss.fffffff
+fn livesplit_time(time: f32) -> String {
+    let total_millis = (time as f64 * 1000.0).round() as u64;
+    let hours = total_millis / 3_600_000;
+    let minutes = total_millis / 60_000 % 60;
+    let seconds = total_millis / 1000 % 60;
+    return format!("{:02}:{:02}:{:02}.{:03}0000", hours, minutes, seconds, total_millis % 1000);
+}
+
+fn escape_xml(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
+
+// a livesplit .lss file with one split per segment, the times of the run are the personal best
+// game time and real time are the same since the demo time is all we have
+pub fn write_livesplit_splits<W: Write>(writer: &mut W, game_name: &str, segments: &[Segment]) -> io::Result<()> {
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<Run version="1.7.0">"#)?;
+    writeln!(writer, "  <GameIcon />")?;
+    writeln!(writer, "  <GameName>{}</GameName>", escape_xml(game_name))?;
+    writeln!(writer, "  <CategoryName />")?;
+    writeln!(writer, "  <Offset>00:00:00</Offset>")?;
+    writeln!(writer, "  <AttemptCount>1</AttemptCount>")?;
+    writeln!(writer, "  <AttemptHistory />")?;
+    writeln!(writer, "  <Segments>")?;
+    for segment in segments {
+        writeln!(writer, "    <Segment>")?;
+        writeln!(writer, "      <Name>{}</Name>", escape_xml(&segment.map_name))?;
+        writeln!(writer, "      <Icon />")?;
+        writeln!(writer, "      <SplitTimes>")?;
+        writeln!(writer, r#"        <SplitTime name="Personal Best">"#)?;
+        writeln!(writer, "          <RealTime>{}</RealTime>", livesplit_time(segment.cumulative_time))?;
+        writeln!(writer, "          <GameTime>{}</GameTime>", livesplit_time(segment.cumulative_time))?;
+        writeln!(writer, "        </SplitTime>")?;
+        writeln!(writer, "      </SplitTimes>")?;
+        writeln!(writer, "      <BestSegmentTime>")?;
+        writeln!(writer, "        <RealTime>{}</RealTime>", livesplit_time(segment.time))?;
+        writeln!(writer, "        <GameTime>{}</GameTime>", livesplit_time(segment.time))?;
+        writeln!(writer, "      </BestSegmentTime>")?;
+        writeln!(writer, "      <SegmentHistory />")?;
+        writeln!(writer, "    </Segment>")?;
+    }
+    writeln!(writer, "  </Segments>")?;
+    writeln!(writer, "  <AutoSplitterSettings />")?;
+    writeln!(writer, "</Run>")?;
+    return Ok(());
+}
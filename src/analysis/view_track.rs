@@ -0,0 +1,68 @@
+use std::io::{self, Write};
+
+use crate::structs::demo::Demo;
+use crate::structs::packet::{PacketDataType, PacketType};
+use crate::structs::utils::Vec3;
+
+// where the players camera is and where its looking on every tick, taken from the CmdInfo of every Packet packet
+// view_origin is the camera and not the players actual position, so the speed includes the view offset moving when you duck
+
+#[derive(Debug, Clone)]
+pub struct TickView {
+    pub tick: i32,
+    pub view_origin: Vec3,
+    pub view_angles: Vec3,
+    pub local_view_angles: Vec3,
+    pub horizontal_speed: f32, // units per second, from how far view_origin moved since the last tick
+}
+
+// one entry per tick, if a tick has more than one packet the last one wins
+pub fn get_view_track(demo: &Demo) -> Vec<TickView> {
+    let mut track: Vec<TickView> = Vec::new();
+
+    for packet in &demo.packets {
+        if packet.packet_type != PacketType::Packet || packet.tick < 0 {
+            continue;
+        }
+        let PacketDataType::Packet(data) = &packet.data else { continue; };
+        let cmd_info = &data.cmd_info;
+
+        if track.last().map(|t| t.tick == packet.tick).unwrap_or(false) {
+            track.pop();
+        }
+
+        let horizontal_speed = match track.last() {
+            Some(prev) => {
+                let time = (packet.tick - prev.tick) as f32 * demo.tick_interval();
+                let dx = cmd_info.view_origin.x - prev.view_origin.x;
+                let dy = cmd_info.view_origin.y - prev.view_origin.y;
+                (dx * dx + dy * dy).sqrt() / time
+            },
+            None => 0.0,
+        };
+
+        track.push(TickView {
+            tick: packet.tick,
+            view_origin: cmd_info.view_origin.clone(),
+            view_angles: cmd_info.view_angles.clone(),
+            local_view_angles: cmd_info.local_view_angles.clone(),
+            horizontal_speed: horizontal_speed,
+        });
+    }
+
+    return track;
+}
+
+pub fn write_view_track_csv<W: Write>(writer: &mut W, track: &[TickView]) -> io::Result<()> {
+    writeln!(writer, "tick,origin_x,origin_y,origin_z,pitch,yaw,roll,local_pitch,local_yaw,local_roll,horizontal_speed")?;
+    for t in track {
+        writeln!(writer, "{},{},{},{},{},{},{},{},{},{},{:.3}",
+            t.tick,
+            t.view_origin.x, t.view_origin.y, t.view_origin.z,
+            t.view_angles.x, t.view_angles.y, t.view_angles.z,
+            t.local_view_angles.x, t.local_view_angles.y, t.local_view_angles.z,
+            t.horizontal_speed,
+        )?;
+    }
+    return Ok(());
+}
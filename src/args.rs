@@ -1,36 +1,173 @@
 const USAGE_TEXT: &str = "
 iipdp v0.3.0 made by shroom
 Usage:
-\tiipdp.exe <DEMO_NAME> [OPTIONS]
+\tiipdp <COMMAND> <DEMO_OR_FOLDER> [OPTIONS]
+\tiipdp <DEMO_OR_FOLDER> (same as info, this is what happens when you drag a demo onto iipdp.exe)
+
+Commands:
+\tinfo   : Print the header and the time of the demo (or every demo in the folder)
+\ttime   : Print only the time, for folders the total time of all the demos
+\trun    : Time a folder of demos as one run, with a split every time the map changes
+\t\t--format text: Print a table of the splits (default)
+\t\t--format lss : Write the splits to a LiveSplit .lss file
+\tstats  : Print jump, bhop and strafe stats, for folders also for all the demos together
+\tdump   : Dump data from the demo into a file
+\t\t--format text: Everything in a human readable .txt file (default)
+\t\t--format fc  : Only flattened server class data
+\tverify : Dump data needed for verification (best used with a folder of demos)
+\texport : Export data from the demo for other tools
+\t\t--format json: Everything as a .json file (default)
+\t\t--format csv : View origin, angles and speed on every tick as a .csv file
+\t\t--format player: Origin, velocity, on ground and ducking of the player entity on every tick as a .csv file
+\t\t--format portals: Every portal shot, placement, fizzle and teleport with its tick as a .txt file
+\t\t--format inputs: Movement keys, jump, duck, use, attack and mouse movement of every usercmd as a .json file
+\t\t--format inputs-svg: The same inputs drawn as a timeline in a .svg file
+\thelp   : Print this message
 
 Options:
-\t-dump: Dump data from demo into a .txt file in the demo's directory
-\t\t-fc: Dump only flattened sever class data
-\t\t-v : Dump data needed for verification (best used with a folder of demos)
-\t-help: Print this message";
+\t-o, --output <PATH>  : Where to write the output file, for folders this is a folder (verify writes one file)
+\t                       by default the output goes next to the demo
+\t-f, --format <FORMAT>: Output format, see the commands above
+\t-r, --rules <FILE>   : Load the rules for where the time starts and ends from a file (see rules/portal.toml)
+\t                       by default its portal 1 any% (wakeup to glados death)
+\t-g, --games <FILE>   : Load game profiles for mods from a file (see games/example.toml)
+\t                       rules and game profile files can also be .json
+\t-q, --quiet          : Only print results and errors
+\t--no-pause           : Exit right away instead of waiting for enter to be pressed
+\t                       iipdp never waits when its input isnt a terminal (scripts, pipes, ci)
+\t-h, --help           : Print this message
+
+Exit codes:
+\t0: Everything worked
+\t1: The demo is invalid or couldnt be read, or the output couldnt be written
+\t2: The arguments, the rules file or the game profiles file are wrong
+\t3: The demo stopped parsing early, everything before the error is still used
+\tfor folders its the highest exit code of all the demos";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Info,
+    Time,
+    Run,
+    Stats,
+    Dump,
+    Verify,
+    Export,
+    Help,
+}
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    FlattenedClasses,
+    Json,
+    Csv,
+    PlayerCsv,
+    Portals,
+    InputsJson,
+    InputsSvg,
+    Lss,
+}
 
 pub struct Args {
+    pub command: Command,
     pub demo_name: String,
-    pub dump: bool,
-    pub fc: bool,
-	pub v: bool,
+    pub output: Option<String>,
+    pub rules: Option<String>,
+    pub games: Option<String>,
+    pub format: Format,
+    pub quiet: bool,
+    pub no_pause: bool,
 }
 
 impl Args {
-    pub fn parse(args: Vec<String>) -> Self {
-        let options: Vec<String> = vec!["-help".to_string(), "-dump".to_string()];
-        if args.len() == 1 || args.contains(&"-help".to_string()) || options.contains(&args[1]){
-            println!("{}", USAGE_TEXT);
-            std::io::stdin().read_line(&mut String::new()).unwrap();
-            std::process::exit(0);
+    // returns a message saying whats wrong if the arguments dont make sense
+    pub fn parse(args: Vec<String>) -> Result<Self, String> {
+        let mut args = args.into_iter().skip(1).peekable();
+
+        let command = match args.peek().map(|a| a.as_str()) {
+            None | Some("help") | Some("-h") | Some("--help") => Command::Help,
+            Some("info") => Command::Info,
+            Some("time") => Command::Time,
+            Some("run") => Command::Run,
+            Some("stats") => Command::Stats,
+            Some("dump") => Command::Dump,
+            Some("verify") => Command::Verify,
+            Some("export") => Command::Export,
+            // no command but a path to something, probably a demo that got dragged onto the exe
+            Some(arg) if std::path::Path::new(arg).exists() => Command::Info,
+            Some(arg) if arg.starts_with('-') => return Err(format!("Unknown option \"{}\"", arg)),
+            Some(arg) => return Err(format!("Unknown command \"{}\"", arg)),
+        };
+        if command == Command::Help {
+            return Ok(Self::new(command));
         }
-        
-        Self {
-            demo_name: args[1].clone(),
-            dump: args.contains(&"-dump".to_string()),
-            fc: args.contains(&"-fc".to_string()) && args.contains(&"-dump".to_string()),
-			v: args.contains(&"-v".to_string()) && args.contains(&"-dump".to_string()),
-		}
+        // the path is left in for drag and drop
+        if args.peek().map(|a| Self::is_command(a)).unwrap_or(false) {
+            args.next();
+        }
+
+        let mut parsed = Self::new(command);
+        let mut format: Option<String> = None;
+        let mut demo_name: Option<String> = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-o" | "--output" => parsed.output = Some(args.next().ok_or(format!("{} needs a path after it", arg))?),
+                "-f" | "--format" => format = Some(args.next().ok_or(format!("{} needs a format after it", arg))?),
+                "-r" | "--rules" => parsed.rules = Some(args.next().ok_or(format!("{} needs a file after it", arg))?),
+                "-g" | "--games" => parsed.games = Some(args.next().ok_or(format!("{} needs a file after it", arg))?),
+                "-q" | "--quiet" => parsed.quiet = true,
+                "--no-pause" => parsed.no_pause = true,
+                "-h" | "--help" => return Ok(Self::new(Command::Help)),
+                _ if arg.starts_with('-') => return Err(format!("Unknown option \"{}\"", arg)),
+                _ if demo_name.is_some() => return Err(format!("Unexpected argument \"{}\", only one demo or folder can be given", arg)),
+                _ => demo_name = Some(arg),
+            }
+        }
+
+        parsed.demo_name = demo_name.ok_or("No demo or folder given".to_string())?;
+        parsed.format = match (command, format.as_deref()) {
+            (Command::Dump, None | Some("text")) => Format::Text,
+            (Command::Dump, Some("fc")) => Format::FlattenedClasses,
+            (Command::Export, None | Some("json")) => Format::Json,
+            (Command::Export, Some("csv")) => Format::Csv,
+            (Command::Export, Some("player")) => Format::PlayerCsv,
+            (Command::Export, Some("portals")) => Format::Portals,
+            (Command::Export, Some("inputs")) => Format::InputsJson,
+            (Command::Export, Some("inputs-svg")) => Format::InputsSvg,
+            (Command::Run, None | Some("text")) => Format::Text,
+            (Command::Run, Some("lss")) => Format::Lss,
+            (Command::Dump | Command::Export | Command::Run, Some(f)) => return Err(format!("Unknown format \"{}\" for {}", f, Self::command_name(command))),
+            (_, Some(_)) => return Err(format!("{} doesn't take a format", Self::command_name(command))),
+            (_, None) => Format::Text,
+        };
+
+        return Ok(parsed);
+    }
+
+    fn new(command: Command) -> Self {
+        Self { command: command, demo_name: String::new(), output: None, rules: None, games: None, format: Format::Text, quiet: false, no_pause: false }
+    }
+
+    fn is_command(arg: &str) -> bool {
+        return ["info", "time", "run", "stats", "dump", "verify", "export"].contains(&arg);
     }
-}
\ No newline at end of file
+
+    fn command_name(command: Command) -> &'static str {
+        return match command {
+            Command::Info => "info",
+            Command::Time => "time",
+            Command::Run => "run",
+            Command::Stats => "stats",
+            Command::Dump => "dump",
+            Command::Verify => "verify",
+            Command::Export => "export",
+            Command::Help => "help",
+        };
+    }
+
+    pub fn print_usage() {
+        println!("{}", USAGE_TEXT);
+    }
+}
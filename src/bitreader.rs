@@ -11,6 +11,7 @@ pub struct BitReader {
     pub byte_size: usize,
     pub fetch: u64,
     pub current: usize,
+    pub overflowed: Option<usize>, // the bit where we first tried to read past the end, like bf_read in the source sdk
 }
 
 impl BitReader {
@@ -22,25 +23,32 @@ impl BitReader {
             byte_size: 0,
             fetch: 0,
             current: 0,
+            overflowed: None,
         };
         new_reader.bits = bits;
         new_reader.bit_size = new_reader.bits.len() * 8;
         new_reader.byte_size = new_reader.bit_size / 8;
+        // fetch always grabs 8 bytes so pad anything smaller than that, bit_size stays the real size
+        if new_reader.bits.len() < 8 {
+            new_reader.bits.resize(8, 0);
+        }
         new_reader.fetch();
         
         return new_reader;
     }
     
     pub fn fetch(&mut self) {
-        let block = if self.current / 8 + 8 > self.byte_size { self.byte_size - 8 } else { self.current / 8 };
+        let block = if self.current / 8 + 8 > self.bits.len() { self.bits.len() - 8 } else { self.current / 8 };
         self.fetch = u64::from_le_bytes(self.bits[block..block + 8].try_into().unwrap());
         self.offset = (self.current - (block * 8)) as u8;
     }
         
     // reads bits
+    // reading past the end doesnt panic, it returns 0 and sets overflowed so the parser can stop and tell you where
     pub fn read_bits(&mut self, amount: i32) -> u64 {        
-        if self.current + amount as usize > self.bit_size {
-            panic!("overflowed somehow lmao");
+        if !self.has_bits(amount) {
+            self.set_overflowed();
+            return 0;
         }
         let mut new_amount = amount.to_owned() as usize; // this is so that i can modify the amount value
         let mut res: u64 = 0;
@@ -73,7 +81,8 @@ impl BitReader {
             then we right shift it by "shift" to put the bits into their correct place in the number if we fetched above
         */
 
-        res |= ((self.fetch >> self.offset) & ((1u64 << new_amount) - 1)) << shift;
+        let mask = if new_amount == 64 { u64::MAX } else { (1u64 << new_amount) - 1 };
+        res |= ((self.fetch >> self.offset) & mask) << shift;
         
         self.current += new_amount;
         self.offset += new_amount as u8;
@@ -82,10 +91,24 @@ impl BitReader {
     
     // do i have to explain this one
     pub fn skip(&mut self, amount: i32) {
+        if !self.has_bits(amount) {
+            self.set_overflowed();
+            return;
+        }
         self.current += amount as usize;
-        if self.current > self.bit_size {
-            panic!("overflow while skipping");
+        self.fetch();
+    }
+
+    pub fn has_bits(&self, amount: i32) -> bool {
+        return amount >= 0 && self.current + amount as usize <= self.bit_size;
+    }
+
+    // only remembers the first overflow, everything after it is garbage anyway
+    fn set_overflowed(&mut self) {
+        if self.overflowed.is_none() {
+            self.overflowed = Some(self.current);
         }
+        self.current = self.bit_size;
         self.fetch();
     }
 
@@ -98,7 +121,7 @@ impl BitReader {
             cur_char = self.read_bits(8);
         }
 
-        return String::from_utf8(char_vec).unwrap().trim_end_matches("\0").to_string();
+        return String::from_utf8_lossy(&char_vec).trim_end_matches("\0").to_string();
     }
 
     // clones the bitreader and skips amount bits in the parent one
@@ -117,12 +140,24 @@ impl BitReader {
 
     pub fn read_signed_int(&mut self, amount: i32) -> i32 {
         let mut res = self.read_bits(amount) as i32;
-        if (res & (1 << (amount - 1))) != 0 {
+        // no sign extension needed if we read a full 32 bit int
+        if amount < 32 && (res & (1 << (amount - 1))) != 0 {
             res |= i32::MAX << amount;
         }
         return res;
     }
 
+    // source's ReadUBitVar, the first 2 bits tell you how many bits the actual value has
+    // used for entity indices and prop indices in packetentities
+    pub fn read_ubit_var(&mut self) -> i32 {
+        return match self.read_bits(2) {
+            0 => self.read_int(4),
+            1 => self.read_int(8),
+            2 => self.read_int(12),
+            _ => self.read_int(32),
+        }
+    }
+
     // i dislike steampipe
     // thanks jukspa :)
     pub fn read_var_int32(&mut self) -> i32 {
@@ -133,7 +168,7 @@ impl BitReader {
             res |= (b & 0x7F) << (7 * i);
             if (b & 0x80) == 0 { break; }
         }
-        return res as i32;
+        return res;
     }
 
     // used once, dont remember where
@@ -149,17 +184,25 @@ impl BitReader {
     // read ascii string that has a determined length
     // used mostly in the header
     pub fn read_ascii_string(&mut self, amount: i32) -> String {
+        if !self.has_bits(amount) {
+            self.set_overflowed();
+            return String::new();
+        }
         let mut char_vec: Vec<u8> = Vec::new();
         for _ in 0..amount / 8 {
             let cur_char = self.read_bits(8);
             char_vec.push(cur_char as u8);
         }
 
-        return String::from_utf8(char_vec).unwrap().trim_end_matches("\0").to_string();
+        return String::from_utf8_lossy(&char_vec).trim_end_matches("\0").to_string();
     }
 
     // also used once
     pub fn read_bytes(&mut self, amount: i32) -> Vec<u8> {
+        if !self.has_bits(amount.saturating_mul(8)) {
+            self.set_overflowed();
+            return Vec::new();
+        }
         let mut res: Vec<u8> = Vec::new();
         for _ in 0..amount {
             let cur_byte = self.read_bits(8) as u8;
@@ -177,7 +220,7 @@ impl BitReader {
     }
 
     // weird function that i just took from nekz.me
-    // seems to work properly so ¯\_(ツ)_/¯
+    // (it was slightly wrong, this is what source's ReadBitCoord actually does)
     pub fn read_vector_coord(&mut self) -> f32 {
         const COORD_INTEGER_BITS: i32 = 14;
         const COORD_FRACTIONAL_BITS: i32 = 5;
@@ -192,11 +235,11 @@ impl BitReader {
             let sign = self.read_bool();
 
             if integer {
-                value += self.read_int(COORD_INTEGER_BITS) as f32;
+                value += (self.read_int(COORD_INTEGER_BITS) + 1) as f32; // the integer part is never 0 so its stored minus one
             }
 
             if fraction {
-                value += self.read_float(COORD_FRACTIONAL_BITS) * COORD_RESOLUTION;
+                value += self.read_int(COORD_FRACTIONAL_BITS) as f32 * COORD_RESOLUTION;
             }
 
             if sign {
@@ -207,6 +250,66 @@ impl BitReader {
         return value
     }
 
+    // multiplayer version of the coord reader above, used by props with the CoordMp flags
+    pub fn read_vector_coord_mp(&mut self, integral: bool, low_precision: bool) -> f32 {
+        const COORD_INTEGER_BITS: i32 = 14;
+        const COORD_INTEGER_BITS_MP: i32 = 11;
+        const COORD_FRACTIONAL_BITS: i32 = 5;
+        const COORD_FRACTIONAL_BITS_LOW_PRECISION: i32 = 3;
+
+        let mut value: f32 = 0f32;
+        let in_bounds = self.read_bool();
+        let mut sign = false;
+
+        if integral {
+            if self.read_bool() {
+                sign = self.read_bool();
+                value = (self.read_int(if in_bounds { COORD_INTEGER_BITS_MP } else { COORD_INTEGER_BITS }) + 1) as f32;
+            }
+        } else {
+            let integer = self.read_bool();
+            sign = self.read_bool();
+
+            if integer {
+                value = (self.read_int(if in_bounds { COORD_INTEGER_BITS_MP } else { COORD_INTEGER_BITS }) + 1) as f32;
+            }
+
+            let fraction_bits = if low_precision { COORD_FRACTIONAL_BITS_LOW_PRECISION } else { COORD_FRACTIONAL_BITS };
+            value += self.read_int(fraction_bits) as f32 * (1f32 / (1 << fraction_bits) as f32);
+        }
+
+        if sign {
+            value = -value;
+        }
+
+        return value;
+    }
+
+    // demo protocol 4 only, a coord inside of a cell so theres no sign and the prop says how many bits the integer part has
+    pub fn read_bit_cell_coord(&mut self, bits: i32, integral: bool, low_precision: bool) -> f32 {
+        const COORD_FRACTIONAL_BITS: i32 = 5;
+        const COORD_FRACTIONAL_BITS_LOW_PRECISION: i32 = 3;
+
+        if integral {
+            return self.read_int(bits) as f32;
+        }
+
+        let integer = self.read_int(bits) as f32;
+        let fraction_bits = if low_precision { COORD_FRACTIONAL_BITS_LOW_PRECISION } else { COORD_FRACTIONAL_BITS };
+        return integer + self.read_int(fraction_bits) as f32 * (1f32 / (1 << fraction_bits) as f32);
+    }
+
+    // reads a float in the range [-1, 1], used for normal vectors
+    pub fn read_bit_normal(&mut self) -> f32 {
+        const NORMAL_FRACTIONAL_BITS: i32 = 11;
+        const NORMAL_RESOLUTION: f32 = 1f32 / ((1 << NORMAL_FRACTIONAL_BITS) - 1) as f32;
+
+        let sign = self.read_bool();
+        let value = self.read_int(NORMAL_FRACTIONAL_BITS) as f32 * NORMAL_RESOLUTION;
+
+        return if sign { -value } else { value };
+    }
+
     // calls the previous function for x, y, and z coords
     pub fn read_vector_coords(&mut self) -> Vec<Option<f32>> {
         let (x, y, z) = (self.read_bool(), self.read_bool(), self.read_bool());
@@ -0,0 +1,409 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::json::JsonValue;
+
+// config files are a small part of toml, just enough for lists of things like timing rules:
+//   # comments
+//   [table] and [[list of tables]], only one level so no dotted names
+//   key = "string", 'literal string', 123, -1.5, true, [1, 2, 3]
+// or json if the file ends in .json
+// everything gets read into a JsonValue so the code using the config doesnt care what format it was written in
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub line: usize, // 0 if its not about a specific line
+    pub message: String,
+}
+
+impl ConfigError {
+    pub fn new(line: usize, message: String) -> Self {
+        Self { line: line, message: message }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            return write!(f, "{}", self.message);
+        }
+        return write!(f, "line {}: {}", self.line, self.message);
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// picks the format from the file extension
+pub fn load_config(path: &Path) -> Result<JsonValue, ConfigError> {
+    let text = fs::read_to_string(path).map_err(|err| ConfigError::new(0, format!("couldn't read {}: {}", path.display(), err)))?;
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+        return parse_json(&text);
+    }
+    return parse_toml(&text);
+}
+
+// returns an object with every table and every key that isnt in a table
+pub fn parse_toml(text: &str) -> Result<JsonValue, ConfigError> {
+    let mut root: Vec<(String, JsonValue)> = Vec::new();
+    // the table were putting keys into right now, (name, is a [[list]] table)
+    let mut table: Option<(String, bool)> = None;
+    let mut fields: Vec<(String, JsonValue)> = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            let header = line.split('#').next().unwrap_or_default().trim();
+            let (name, is_list) = match header.strip_prefix("[[").and_then(|h| h.strip_suffix("]]")) {
+                Some(name) => (name.trim(), true),
+                None => match header.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+                    Some(name) => (name.trim(), false),
+                    None => return Err(ConfigError::new(line_number, format!("bad table header \"{}\"", header))),
+                },
+            };
+            check_key(name, line_number)?;
+
+            if let Some(finished) = table.take() {
+                add_table(&mut root, finished, std::mem::take(&mut fields), line_number)?;
+            }
+            table = Some((name.to_string(), is_list));
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(ConfigError::new(line_number, format!("expected key = value, got \"{}\"", line)));
+        };
+        let key = key.trim();
+        check_key(key, line_number)?;
+
+        let mut cursor = Cursor { chars: value.chars().collect(), pos: 0 };
+        let value = cursor.read_value().map_err(|err| ConfigError::new(line_number, err))?;
+        cursor.skip_whitespace();
+        if cursor.peek().is_some_and(|c| c != '#') {
+            return Err(ConfigError::new(line_number, format!("unexpected text after the value of \"{}\"", key)));
+        }
+
+        let target = if table.is_some() { &mut fields } else { &mut root };
+        if target.iter().any(|(name, _)| name == key) {
+            return Err(ConfigError::new(line_number, format!("\"{}\" is set twice", key)));
+        }
+        target.push((key.to_string(), value));
+    }
+
+    if let Some(finished) = table {
+        add_table(&mut root, finished, fields, text.lines().count())?;
+    }
+
+    return Ok(JsonValue::Object(root));
+}
+
+pub fn parse_json(text: &str) -> Result<JsonValue, ConfigError> {
+    let mut cursor = Cursor { chars: text.chars().collect(), pos: 0 };
+    let value = cursor.read_json_value().map_err(|err| ConfigError::new(cursor.line(), err))?;
+    cursor.skip_whitespace();
+    if cursor.peek().is_some() {
+        return Err(ConfigError::new(cursor.line(), "unexpected text after the end of the json".to_string()));
+    }
+    return Ok(value);
+}
+
+fn check_key(key: &str, line_number: usize) -> Result<(), ConfigError> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(ConfigError::new(line_number, format!("bad name \"{}\", only letters, numbers, _ and - are allowed", key)));
+    }
+    return Ok(());
+}
+
+fn add_table(root: &mut Vec<(String, JsonValue)>, table: (String, bool), fields: Vec<(String, JsonValue)>, line_number: usize) -> Result<(), ConfigError> {
+    let (name, is_list) = table;
+    let existing = root.iter_mut().find(|(key, _)| *key == name).map(|(_, value)| value);
+
+    match (existing, is_list) {
+        (None, true) => root.push((name, JsonValue::Array(vec![JsonValue::Object(fields)]))),
+        (None, false) => root.push((name, JsonValue::Object(fields))),
+        (Some(JsonValue::Array(tables)), true) => tables.push(JsonValue::Object(fields)),
+        (Some(_), _) => return Err(ConfigError::new(line_number, format!("\"{}\" is defined twice", name))),
+    }
+    return Ok(());
+}
+
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn peek(&self) -> Option<char> {
+        return self.chars.get(self.pos).copied();
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        return c;
+    }
+
+    // only the json parser needs this, toml gets one line at a time
+    fn line(&self) -> usize {
+        return self.chars[..self.pos.min(self.chars.len())].iter().filter(|c| **c == '\n').count() + 1;
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn read_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        return match self.peek() {
+            Some('"') => self.read_string(),
+            Some('\'') => {
+                self.pos += 1;
+                let mut out = String::new();
+                loop {
+                    match self.next() {
+                        Some('\'') => return Ok(JsonValue::String(out)),
+                        Some(c) => out.push(c),
+                        None => return Err("string is missing its closing '".to_string()),
+                    }
+                }
+            },
+            Some('[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() == Some(']') {
+                        self.pos += 1;
+                        return Ok(JsonValue::Array(values));
+                    }
+                    values.push(self.read_value()?);
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => (),
+                        Some(']') => return Ok(JsonValue::Array(values)),
+                        _ => return Err("expected , or ] in a list".to_string()),
+                    }
+                }
+            },
+            Some(_) => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| !c.is_whitespace() && c != ',' && c != ']' && c != '#') {
+                    self.pos += 1;
+                }
+                let word: String = self.chars[start..self.pos].iter().collect();
+                match word.as_str() {
+                    "true" => Ok(JsonValue::Bool(true)),
+                    "false" => Ok(JsonValue::Bool(false)),
+                    _ => {
+                        if let Ok(value) = word.parse::<i64>() {
+                            return Ok(JsonValue::Int(value));
+                        }
+                        word.parse::<f64>().map(JsonValue::Float).map_err(|_| format!("\"{}\" isnt a string, number, bool or list", word))
+                    },
+                }
+            },
+            None => Err("missing value".to_string()),
+        };
+    }
+
+    fn read_string(&mut self) -> Result<JsonValue, String> {
+        return self.read_raw_string().map(JsonValue::String);
+    }
+
+    fn read_raw_string(&mut self) -> Result<String, String> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('r') => out.push('\r'),
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    Some('/') => out.push('/'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.next()).collect();
+                        match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                            Some(c) => out.push(c),
+                            None => return Err(format!("bad escape \\u{} in a string", hex)),
+                        }
+                    },
+                    Some(c) => return Err(format!("unknown escape \\{} in a string", c)),
+                    None => return Err("string is missing its closing \"".to_string()),
+                },
+                Some(c) => out.push(c),
+                None => return Err("string is missing its closing \"".to_string()),
+            }
+        }
+    }
+
+    fn read_json_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        return match self.peek() {
+            Some('"') => self.read_string(),
+            Some('[') => {
+                self.pos += 1;
+                let mut values = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(']') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(values));
+                }
+                loop {
+                    values.push(self.read_json_value()?);
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => (),
+                        Some(']') => return Ok(JsonValue::Array(values)),
+                        _ => return Err("expected , or ] in a list".to_string()),
+                    }
+                }
+            },
+            Some('{') => {
+                self.pos += 1;
+                let mut fields: Vec<(String, JsonValue)> = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some('}') {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() != Some('"') {
+                        return Err("expected a \"key\" in an object".to_string());
+                    }
+                    let key = self.read_raw_string()?;
+                    self.skip_whitespace();
+                    if self.next() != Some(':') {
+                        return Err(format!("expected : after \"{}\"", key));
+                    }
+                    if fields.iter().any(|(name, _)| *name == key) {
+                        return Err(format!("\"{}\" is set twice", key));
+                    }
+                    let value = self.read_json_value()?;
+                    fields.push((key, value));
+                    self.skip_whitespace();
+                    match self.next() {
+                        Some(',') => (),
+                        Some('}') => return Ok(JsonValue::Object(fields)),
+                        _ => return Err("expected , or } in an object".to_string()),
+                    }
+                }
+            },
+            Some(_) => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '-' || c == '+' || c == '.') {
+                    self.pos += 1;
+                }
+                let word: String = self.chars[start..self.pos].iter().collect();
+                match word.as_str() {
+                    "true" => Ok(JsonValue::Bool(true)),
+                    "false" => Ok(JsonValue::Bool(false)),
+                    "null" => Ok(JsonValue::Null),
+                    "" => Err(format!("unexpected {:?}", self.peek().unwrap_or_default())),
+                    _ => {
+                        if let Ok(value) = word.parse::<i64>() {
+                            return Ok(JsonValue::Int(value));
+                        }
+                        word.parse::<f64>().map(JsonValue::Float).map_err(|_| format!("\"{}\" isnt a string, number, bool, null, list or object", word))
+                    },
+                }
+            },
+            None => Err("missing value".to_string()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn err_of(result: Result<JsonValue, ConfigError>) -> ConfigError {
+        return result.expect_err("should not parse");
+    }
+
+    #[test]
+    fn toml_values_and_tables() {
+        let text = "# a comment\n\
+            version = 2\n\
+            \n\
+            [settings]\n\
+            name = \"a \\\"b\\\"\\n\" # after the value\n\
+            path = 'C:\\demos'\n\
+            \n\
+            [[rule]]\n\
+            offset = -1\n\
+            angle = [0, 189.5, -2e3]\n\
+            \n\
+            [[rule]]\n\
+            enabled = false\n\
+            names = []\n";
+        let config = parse_toml(text).unwrap();
+
+        assert_eq!(config.get("version"), Some(&JsonValue::Int(2)));
+        let settings = config.get("settings").unwrap();
+        assert_eq!(settings.get("name").and_then(|v| v.as_str()), Some("a \"b\"\n"));
+        assert_eq!(settings.get("path").and_then(|v| v.as_str()), Some("C:\\demos"));
+
+        let rules = config.get("rule").and_then(|v| v.as_array()).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].get("offset"), Some(&JsonValue::Int(-1)));
+        assert_eq!(rules[0].get("angle"), Some(&JsonValue::Array(vec![JsonValue::Int(0), JsonValue::Float(189.5), JsonValue::Float(-2000.0)])));
+        assert_eq!(rules[1].get("enabled"), Some(&JsonValue::Bool(false)));
+        assert_eq!(rules[1].get("names"), Some(&JsonValue::Array(Vec::new())));
+    }
+
+    #[test]
+    fn toml_errors() {
+        assert_eq!(err_of(parse_toml("a = 1\n[table")), ConfigError::new(2, "bad table header \"[table\"".to_string()));
+        assert_eq!(err_of(parse_toml("[a.b]")).line, 1);
+        assert_eq!(err_of(parse_toml("just text")).line, 1);
+        assert_eq!(err_of(parse_toml("a = 1\na = 2")), ConfigError::new(2, "\"a\" is set twice".to_string()));
+        assert_eq!(err_of(parse_toml("[a]\n[a]")).line, 2);
+        assert_eq!(err_of(parse_toml("[a]\n[[a]]")).line, 2);
+        assert_eq!(err_of(parse_toml("a = \"open")).message, "string is missing its closing \"");
+        assert_eq!(err_of(parse_toml("a = 'open")).message, "string is missing its closing '");
+        assert_eq!(err_of(parse_toml("a = \"\\q\"")).message, "unknown escape \\q in a string");
+        assert_eq!(err_of(parse_toml("a = [1, 2")).message, "expected , or ] in a list");
+        assert_eq!(err_of(parse_toml("a = yes")).message, "\"yes\" isnt a string, number, bool or list");
+        assert_eq!(err_of(parse_toml("a =")).message, "missing value");
+        assert_eq!(err_of(parse_toml("a = 1 2")).message, "unexpected text after the value of \"a\"");
+    }
+
+    #[test]
+    fn json_values() {
+        let text = "{\n  \"rule\": [{\"offset\": -1, \"angle\": [0, 1.5e1]}, {}],\n  \"name\": \"\\u0041\\/b\",\n  \"on\": true,\n  \"none\": null\n}";
+        let config = parse_json(text).unwrap();
+
+        let rules = config.get("rule").and_then(|v| v.as_array()).unwrap();
+        assert_eq!(rules[0].get("offset"), Some(&JsonValue::Int(-1)));
+        assert_eq!(rules[0].get("angle"), Some(&JsonValue::Array(vec![JsonValue::Int(0), JsonValue::Float(15.0)])));
+        assert_eq!(rules[1], JsonValue::Object(Vec::new()));
+        assert_eq!(config.get("name").and_then(|v| v.as_str()), Some("A/b"));
+        assert_eq!(config.get("on"), Some(&JsonValue::Bool(true)));
+        assert_eq!(config.get("none"), Some(&JsonValue::Null));
+    }
+
+    #[test]
+    fn json_errors() {
+        assert_eq!(err_of(parse_json("{\"a\": 1,\n\"a\": 2}")), ConfigError::new(2, "\"a\" is set twice".to_string()));
+        assert_eq!(err_of(parse_json("{\n\n1: 2}")).line, 3);
+        assert_eq!(err_of(parse_json("{\"a\" 1}")).message, "expected : after \"a\"");
+        assert_eq!(err_of(parse_json("{\"a\": 1")).message, "expected , or } in an object");
+        assert_eq!(err_of(parse_json("[1 2]")).message, "expected , or ] in a list");
+        assert_eq!(err_of(parse_json("\"\\uzzzz\"")).message, "bad escape \\uzzzz in a string");
+        assert_eq!(err_of(parse_json("nope")).message, "\"nope\" isnt a string, number, bool, null, list or object");
+        assert_eq!(err_of(parse_json("{} {}")).message, "unexpected text after the end of the json");
+        assert_eq!(err_of(parse_json("")).message, "missing value");
+    }
+}
@@ -0,0 +1,100 @@
+use std::fmt;
+use crate::json::{JsonValue, ToJson};
+use crate::game_profile::GameCharacteristics;
+
+// everything parse_demo can fail with
+
+#[derive(Debug)]
+pub enum Error {
+    InvalidDemo(String), // not a demo file at all
+    Parse(ParseError), // it is a demo but something went wrong while reading the packets
+    UnsupportedGame(GameCharacteristics), // it is a demo but none of the game profiles fit it
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidDemo(reason) => write!(f, "Invalid demo file: {}", reason),
+            Error::Parse(err) => write!(f, "Parsing failed: {}", err),
+            Error::UnsupportedGame(info) => write!(f, "Unsupported game, none of the game profiles fit a demo with {}", info),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        return Error::Parse(err);
+    }
+}
+
+// where in the demo it went wrong
+// bit_offset is from the start of the file, packet_index is the index into Demo::packets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorPos {
+    pub bit_offset: usize,
+    pub packet_index: usize,
+    pub tick: i32,
+}
+
+impl fmt::Display for ErrorPos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bit {} (byte {}), packet {}, tick {}", self.bit_offset, self.bit_offset / 8, self.packet_index, self.tick)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    Truncated(ErrorPos), // the file ended before the stop packet, usually because the game crashed
+    Overflow(ErrorPos), // a packet tried to read past its own end
+    UnknownPacketType(i32, ErrorPos),
+    UnknownMessageType(i32, ErrorPos),
+    UnsupportedGame(ErrorPos), // what we found out about the game says its none of the ones we know, see DataManager::detect_game
+}
+
+impl ParseError {
+    pub fn pos(&self) -> ErrorPos {
+        match self {
+            ParseError::Truncated(pos) => *pos,
+            ParseError::Overflow(pos) => *pos,
+            ParseError::UnknownPacketType(_, pos) => *pos,
+            ParseError::UnknownMessageType(_, pos) => *pos,
+            ParseError::UnsupportedGame(pos) => *pos,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Truncated(pos) => write!(f, "the file ends before the stop packet at {}", pos),
+            ParseError::Overflow(pos) => write!(f, "read past the end of the packet at {}", pos),
+            ParseError::UnknownPacketType(packet_type, pos) => write!(f, "unknown packet type {} at {}", packet_type, pos),
+            ParseError::UnknownMessageType(msg_type, pos) => write!(f, "unknown net/svc message type {} at {}", msg_type, pos),
+            ParseError::UnsupportedGame(pos) => write!(f, "the demo is from a game none of the game profiles fit at {}", pos),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ToJson for ParseError {
+    fn to_json(&self) -> JsonValue {
+        let kind = match self {
+            ParseError::Truncated(_) => "Truncated",
+            ParseError::Overflow(_) => "Overflow",
+            ParseError::UnknownPacketType(_, _) => "UnknownPacketType",
+            ParseError::UnknownMessageType(_, _) => "UnknownMessageType",
+            ParseError::UnsupportedGame(_) => "UnsupportedGame",
+        };
+        let pos = self.pos();
+        return JsonValue::object(vec![
+            ("kind", JsonValue::String(kind.to_string())),
+            ("message", JsonValue::String(self.to_string())),
+            ("bit_offset", pos.bit_offset.to_json()),
+            ("packet_index", pos.packet_index.to_json()),
+            ("tick", pos.tick.to_json()),
+        ]);
+    }
+}
@@ -0,0 +1,409 @@
+// game profiles say how to read the demos of a game, every game and engine version numbers its messages and props differently
+// the ones iipdp knows about are built in, mods that change any of this can load their own profiles from a file:
+//
+//   [[game]]
+//   name = "Portal Stories: Mel"  # shows up in the dumps
+//   based_on = "Portal 2"         # optional, copies everything from a profile thats built in or earlier in the file
+//   game_directory = "portal_stories"
+//
+// a profile thats not based on another one needs all of these:
+//
+//   network_protocol = 2001       # from the demo header
+//   engine = "portal_2"           # portal_1_3420, portal_1_5135, portal_1_steampipe or portal_2, for the small differences in how messages are read
+//   net_svc_type_bits = 6         # optional, default 6
+//   send_prop_num_bits_bits = 7   # how many bits the num_bits of a send prop has
+//   sound_index_bits = 13         # optional, by default the one of the engine
+//   sound_flag_bits = 11          # optional, by default the one of the engine
+//   send_prop_types = ["Int", "Float", "Vector3", "Vector2", "String", "Array", "DataTable"]
+//   user_messages = ["Geiger", "Train", ...]  # in id order, the names are the ones in UserMessageType
+//   net_svc_messages = ["NetNop", ...]        # optional, in id order, by default the table of the engine
+//   server_classes = ["CProp_Portal"]         # optional, classes the datatables of the game always have
+//
+// game_directory is optional, with it the profile only works for demos recorded in that directory
+// profiles from a file win over the built in ones, and ones with a game_directory win over ones without
+//
+// the game is picked again while the demo is parsed, once with the game directory from SvcServerInfo
+// and once with the server classes from the datatables, see GameProfiles::detect
+
+use std::fmt;
+use std::fmt::Debug;
+use std::path::Path;
+use crate::enum_primitive::FromPrimitive;
+use crate::config::{load_config, parse_toml, ConfigError};
+use crate::json::JsonValue;
+use crate::structs::data_manager::Game;
+use crate::structs::demo_header::DemoHeader;
+use crate::structs::net_svc_message::NetSvcMessageTypes;
+use crate::structs::send_table::SendPropType;
+use crate::structs::user_message::UserMessageType;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameProfile {
+    pub name: String,
+    pub network_protocol: i32,
+    pub game_directory: Option<String>,
+    pub game: Game,
+    pub net_svc_type_bits: i32,
+    pub send_prop_num_bits_bits: i32,
+    pub sound_index_bits: i32, // for the sounds in SvcSounds
+    pub sound_flag_bits: i32,
+    pub send_prop_types: Vec<SendPropType>,
+    pub net_svc_messages: Vec<NetSvcMessageTypes>,
+    pub user_messages: Vec<UserMessageType>,
+    pub server_classes: Vec<String>, // a demo without all of these isnt from this game
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameProfiles {
+    pub profiles: Vec<GameProfile>,
+}
+
+impl GameProfiles {
+    // every version of portal and half-life 2 iipdp can read
+    pub fn builtin() -> Self {
+        use UserMessageType::*;
+
+        let portal_1_messages = vec![
+            Geiger, Train, HudText, SayText, SayText2, TextMsg, HudMsg, ResetHUD, GameTitle, ItemPickup, ShowMenu,
+            Shake, Fade, VguiMenu, Rumble, Battery, Damage, VoiceMask, RequestState, CloseCaption, HintText,
+            KeyHintText, SquadMemberDied, AmmoDenied, CreditsMsg, CreditsPortalMsg, LogoTimeMsg, AchievementEvent,
+            EntityPortalled, KillCam,
+        ];
+        let haptics_messages = vec![SPHapWeaponEvent, HapDmg, HapPunch, HapSetDrag, HapSetConstForce, HapMeleeContact];
+        let vote_messages = vec![CallVoteFailed, VoteStart, VotePass, VoteFailed, VoteSetup];
+        let portal_2_messages = vec![
+            Geiger, Train, HudText, SayText, SayText2, TextMsg, HudMsg, ResetHUD, GameTitle, ItemPickup, ShowMenu,
+            Shake, Tilt, Fade, VguiMenu, Rumble, Battery, Damage, VoiceMask, RequestState, CloseCaption,
+            CloseCaptionDirect, HintText, KeyHintText, SquadMemberDied, AmmoDenied, CreditsMsg, LogoTimeMsg,
+            AchievementEvent, UpdateJalopyRadar, CurrentTimescale, DesiredTimescale, CreditsPortalMsg, InventoryFlash,
+            IndicatorFlash, ControlHelperAnimate, TakePhoto, Flash, HudPingIndicator, OpenRadialMenu, AddLocator,
+            MpMapCompleted, MpMapIncomplete, MpMapCompletedData, MpTauntEarned, MpTauntUnlocked, MpTauntLocked,
+            MpAllTauntsLocked, PortalFXSurface, PaintWorld, PaintEntity, ChangePaintColor, PaintBombExplode,
+            RemoveAllPaint, PaintAllSurfaces, RemovePaint, StartSurvey, ApplyHitBoxDamageEffect,
+            SetMixLayerTriggerFactor, TransitionFade, ScoreboardTempUpdate, ChallengeModCheatSession, ChallengeModCloseAllUI,
+        ];
+
+        // hl2 and both episodes register the same ones, they all use hl2_usermessages.cpp
+        let hl2_messages = vec![
+            Geiger, Train, HudText, SayText, SayText2, TextMsg, HudMsg, ResetHUD, GameTitle, ItemPickup, ShowMenu,
+            Shake, Fade, VguiMenu, Rumble, Battery, Damage, VoiceMask, RequestState, CloseCaption, HintText,
+            KeyHintText, SquadMemberDied, AmmoDenied, CreditsMsg, LogoTimeMsg, AchievementEvent, UpdateJalopyRadar,
+        ];
+
+        let prop_types = vec![SendPropType::Int, SendPropType::Float, SendPropType::Vector3, SendPropType::Vector2,
+            SendPropType::String, SendPropType::Array, SendPropType::DataTable];
+        // 3420 doesnt have Vector2 yet
+
+        // enough to tell the games apart when the game directory doesnt, a mod usually keeps the classes of its game
+        let portal_classes = vec!["CProp_Portal".to_string()];
+        let hl2_classes = vec!["CHL2_Player".to_string()];
+        let ep2_classes = vec!["CHL2_Player".to_string(), "CPropJeepEpisodic".to_string()];
+        let prop_types_3420 = prop_types.iter().filter(|t| **t != SendPropType::Vector2).cloned().collect();
+
+        let mut profiles = vec![
+            GameProfile {
+                name: "Portal 3420".to_string(),
+                network_protocol: 14,
+                game_directory: None,
+                game: Game::PORTAL_1_3420,
+                net_svc_type_bits: 5,
+                send_prop_num_bits_bits: 6,
+                sound_index_bits: 13,
+                sound_flag_bits: 9,
+                send_prop_types: prop_types_3420,
+                net_svc_messages: NetSvcMessageTypes::table(3),
+                user_messages: portal_1_messages.clone(),
+                server_classes: portal_classes.clone(),
+            },
+            GameProfile {
+                name: "Portal 5135 (source unpack)".to_string(),
+                network_protocol: 15,
+                game_directory: None,
+                game: Game::PORTAL_1_5135,
+                net_svc_type_bits: 6,
+                send_prop_num_bits_bits: 7,
+                sound_index_bits: 13,
+                sound_flag_bits: 9,
+                send_prop_types: prop_types.clone(),
+                net_svc_messages: NetSvcMessageTypes::table(3),
+                user_messages: [portal_1_messages.clone(), haptics_messages.clone()].concat(),
+                server_classes: portal_classes.clone(),
+            },
+            GameProfile {
+                name: "Portal Steampipe".to_string(),
+                network_protocol: 24,
+                game_directory: None,
+                game: Game::PORTAL_1_1910503,
+                net_svc_type_bits: 6,
+                send_prop_num_bits_bits: 7,
+                sound_index_bits: 14,
+                sound_flag_bits: 11,
+                send_prop_types: prop_types.clone(),
+                net_svc_messages: NetSvcMessageTypes::table(3),
+                user_messages: [portal_1_messages, vote_messages, haptics_messages.clone()].concat(),
+                server_classes: portal_classes.clone(),
+            },
+            // demo protocol 4, every packet has a slot and there are 2 cmdinfos for split screen
+            GameProfile {
+                name: "Portal 2".to_string(),
+                network_protocol: 2001,
+                game_directory: None,
+                game: Game::PORTAL_2,
+                net_svc_type_bits: 6,
+                send_prop_num_bits_bits: 7,
+                sound_index_bits: 13,
+                sound_flag_bits: 11,
+                send_prop_types: prop_types,
+                net_svc_messages: NetSvcMessageTypes::table(4),
+                user_messages: portal_2_messages,
+                server_classes: portal_classes,
+            },
+        ];
+
+        // the hl2 games are on the same engine versions as portal 1, they only have different user messages
+        // so theyre told apart from portal by the game directory
+        let portal_1_profiles: Vec<GameProfile> = profiles.iter().filter(|p| p.game != Game::PORTAL_2).cloned().collect();
+        for (game_name, game_directory, server_classes) in [("Half-Life 2", "hl2", &hl2_classes), ("Half-Life 2: Episode One", "episodic", &hl2_classes), ("Half-Life 2: Episode Two", "ep2", &ep2_classes)] {
+            for portal_profile in &portal_1_profiles {
+                let (version, user_messages) = match portal_profile.game {
+                    Game::PORTAL_1_3420 => ("3420", hl2_messages.clone()),
+                    Game::PORTAL_1_5135 => ("5135 (source unpack)", [hl2_messages.clone(), haptics_messages.clone()].concat()),
+                    _ => ("Steampipe", [hl2_messages.clone(), haptics_messages.clone()].concat()),
+                };
+                profiles.push(GameProfile {
+                    name: format!("{} {}", game_name, version),
+                    game_directory: Some(game_directory.to_string()),
+                    user_messages: user_messages,
+                    server_classes: server_classes.clone(),
+                    ..portal_profile.clone()
+                });
+            }
+        }
+
+        return Self { profiles: profiles };
+    }
+
+    // the built in profiles plus the ones in the file
+    // toml, or json if the file ends in .json
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let mut profiles = Self::builtin();
+        profiles.add_from_config(&load_config(path)?)?;
+        return Ok(profiles);
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut profiles = Self::builtin();
+        profiles.add_from_config(&parse_toml(text)?)?;
+        return Ok(profiles);
+    }
+
+    // takes the parsed config file, the profiles are in a list called game
+    pub fn add_from_config(&mut self, config: &JsonValue) -> Result<(), ConfigError> {
+        let Some(games) = config.get("game").and_then(|g| g.as_array()) else {
+            return Err(ConfigError::new(0, "no [[game]] in the game profiles file".to_string()));
+        };
+        for (i, game) in games.iter().enumerate() {
+            let profile = GameProfile::from_config(game, self).map_err(|err| ConfigError::new(0, format!("game {}: {}", i + 1, err)))?;
+            self.profiles.push(profile);
+        }
+        return Ok(());
+    }
+
+    pub fn get(&self, name: &str) -> Option<&GameProfile> {
+        return self.profiles.iter().rev().find(|p| p.name == name);
+    }
+
+    // the profile for the game the demo is from going only by the header, None if we dont know the game
+    pub fn find(&self, header: &DemoHeader) -> Option<&GameProfile> {
+        return self.detect(&GameCharacteristics::from_header(header));
+    }
+
+    // the profile that fits everything we know about the demo so far, None if none of them do
+    // a matching game directory wins, then a profile for any directory, then a profile for another directory
+    // with the most matching server classes, which is a mod made in that game
+    pub fn detect(&self, info: &GameCharacteristics) -> Option<&GameProfile> {
+        let directory = info.server_game_directory.as_ref().unwrap_or(&info.game_directory);
+        let candidates = || self.profiles.iter().rev().filter(|p| p.fits(info));
+        return candidates().find(|p| p.game_directory.as_ref().is_some_and(|dir| dir.eq_ignore_ascii_case(directory)))
+            .or_else(|| candidates().find(|p| p.game_directory.is_none()))
+            .or_else(|| {
+                info.server_classes.as_ref()?;
+                return candidates().filter(|p| !p.server_classes.is_empty()).max_by_key(|p| p.server_classes.len());
+            });
+    }
+}
+
+impl GameProfile {
+    // false if the demo cant be from this game
+    pub fn fits(&self, info: &GameCharacteristics) -> bool {
+        // only portal 2 has demo protocol 4
+        if self.network_protocol != info.network_protocol || (self.game == Game::PORTAL_2) != (info.demo_protocol == 4) {
+            return false;
+        }
+        if let Some(classes) = &info.server_classes {
+            if !self.server_classes.iter().all(|class| classes.contains(class)) {
+                return false;
+            }
+        }
+        if let Some(id) = info.highest_user_message {
+            if id as usize >= self.user_messages.len() {
+                return false;
+            }
+        }
+        return true;
+    }
+
+    fn from_config(game: &JsonValue, profiles: &GameProfiles) -> Result<Self, String> {
+        let name = game.get("name").and_then(|v| v.as_str()).ok_or("needs name = \"...\"".to_string())?.to_string();
+        let get_int = |key: &str| -> Result<Option<i32>, String> {
+            return game.get(key).map(|v| v.as_i64().map(|v| v as i32).ok_or(format!("{} has to be a whole number", key))).transpose();
+        };
+
+        let mut profile = match game.get("based_on") {
+            Some(based_on) => {
+                let based_on = based_on.as_str().ok_or("based_on has to be a string".to_string())?;
+                profiles.get(based_on).ok_or(format!("based_on \"{}\" isnt a built in game or one earlier in the file", based_on))?.clone()
+            },
+            None => {
+                let engine = game.get("engine").and_then(|v| v.as_str()).ok_or("needs engine = \"...\" or based_on = \"...\"".to_string())?;
+                let game_type = match engine {
+                    "portal_1_3420" => Game::PORTAL_1_3420,
+                    "portal_1_5135" => Game::PORTAL_1_5135,
+                    "portal_1_steampipe" => Game::PORTAL_1_1910503,
+                    "portal_2" => Game::PORTAL_2,
+                    other => return Err(format!("unknown engine \"{}\", it has to be portal_1_3420, portal_1_5135, portal_1_steampipe or portal_2", other)),
+                };
+                GameProfile {
+                    name: String::new(),
+                    network_protocol: get_int("network_protocol")?.ok_or("needs network_protocol = ...".to_string())?,
+                    game_directory: None,
+                    game: game_type,
+                    net_svc_type_bits: 6,
+                    send_prop_num_bits_bits: get_int("send_prop_num_bits_bits")?.ok_or("needs send_prop_num_bits_bits = ...".to_string())?,
+                    // steampipe has more sounds and flags, portal 2 only more flags
+                    sound_index_bits: if game_type == Game::PORTAL_1_1910503 { 14 } else { 13 },
+                    sound_flag_bits: if game_type == Game::PORTAL_1_3420 || game_type == Game::PORTAL_1_5135 { 9 } else { 11 },
+                    send_prop_types: names_from_config(game, "send_prop_types")?.ok_or("needs send_prop_types = [...]".to_string())?,
+                    net_svc_messages: NetSvcMessageTypes::table(if game_type == Game::PORTAL_2 { 4 } else { 3 }),
+                    user_messages: names_from_config(game, "user_messages")?.ok_or("needs user_messages = [...]".to_string())?,
+                    server_classes: Vec::new(),
+                }
+            },
+        };
+
+        // everything can be changed on top of based_on
+        profile.name = name;
+        if let Some(dir) = game.get("game_directory") {
+            profile.game_directory = Some(dir.as_str().ok_or("game_directory has to be a string".to_string())?.to_string());
+        }
+        if let Some(network_protocol) = get_int("network_protocol")? {
+            profile.network_protocol = network_protocol;
+        }
+        if let Some(bits) = get_int("net_svc_type_bits")? {
+            profile.net_svc_type_bits = bits;
+        }
+        if let Some(bits) = get_int("send_prop_num_bits_bits")? {
+            profile.send_prop_num_bits_bits = bits;
+        }
+        if let Some(bits) = get_int("sound_index_bits")? {
+            profile.sound_index_bits = bits;
+        }
+        if let Some(bits) = get_int("sound_flag_bits")? {
+            profile.sound_flag_bits = bits;
+        }
+        if let Some(types) = names_from_config(game, "send_prop_types")? {
+            profile.send_prop_types = types;
+        }
+        if let Some(messages) = names_from_config(game, "net_svc_messages")? {
+            profile.net_svc_messages = messages;
+        }
+        if let Some(messages) = names_from_config(game, "user_messages")? {
+            profile.user_messages = messages;
+        }
+        if let Some(classes) = game.get("server_classes") {
+            let classes = classes.as_array().and_then(|classes| classes.iter().map(|c| c.as_str().map(String::from)).collect::<Option<Vec<String>>>());
+            profile.server_classes = classes.ok_or("server_classes has to be a list of class names".to_string())?;
+        }
+
+        // bit widths that dont fit in an i32 would just break the reader
+        if !(1..=8).contains(&profile.net_svc_type_bits) || !(1..=8).contains(&profile.send_prop_num_bits_bits) {
+            return Err("net_svc_type_bits and send_prop_num_bits_bits have to be between 1 and 8".to_string());
+        }
+        if !(1..=31).contains(&profile.sound_index_bits) || !(1..=31).contains(&profile.sound_flag_bits) {
+            return Err("sound_index_bits and sound_flag_bits have to be between 1 and 31".to_string());
+        }
+        // send prop types are read with 5 bits
+        if profile.send_prop_types.is_empty() || profile.send_prop_types.len() > 32 {
+            return Err("send_prop_types needs between 1 and 32 types".to_string());
+        }
+
+        return Ok(profile);
+    }
+}
+
+// a list of enum variants by their names, None if the key isnt there
+fn names_from_config<T: FromPrimitive + Debug>(game: &JsonValue, key: &str) -> Result<Option<Vec<T>>, String> {
+    let Some(values) = game.get(key) else {
+        return Ok(None);
+    };
+    let values = values.as_array().ok_or(format!("{} has to be a list of names", key))?;
+    return values.iter().map(|value| {
+        let name = value.as_str().ok_or(format!("{} has to be a list of names", key))?;
+        return from_name(name).ok_or(format!("unknown name \"{}\" in {}", name, key));
+    }).collect::<Result<Vec<T>, String>>().map(Some);
+}
+
+// the names are the same as in the enums
+fn from_name<T: FromPrimitive + Debug>(name: &str) -> Option<T> {
+    return (0..).map_while(T::from_i64).find(|value| format!("{:?}", value) == name);
+}
+
+// what we know about the game a demo is from, filled in while its parsed
+// demos only have the ids of user messages and not their names, so all we can check is that the game has that many
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameCharacteristics {
+    pub network_protocol: i32,
+    pub demo_protocol: i32,
+    pub game_directory: String, // from the header
+    pub server_game_directory: Option<String>, // from SvcServerInfo
+    pub server_classes: Option<Vec<String>>, // from the datatables
+    pub highest_user_message: Option<i32>, // highest user message id in the demo so far
+}
+
+impl GameCharacteristics {
+    pub fn from_header(header: &DemoHeader) -> Self {
+        Self {
+            network_protocol: header.network_protocol,
+            demo_protocol: header.demo_protocol,
+            game_directory: header.game_directory.clone(),
+            server_game_directory: None,
+            server_classes: None,
+            highest_user_message: None,
+        }
+    }
+
+    pub fn add_user_message(&mut self, id: i32) {
+        self.highest_user_message = Some(self.highest_user_message.map_or(id, |highest| highest.max(id)));
+    }
+}
+
+// for the unsupported game error
+impl fmt::Display for GameCharacteristics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "network protocol {}, demo protocol {}, game directory \"{}\"", self.network_protocol, self.demo_protocol, self.game_directory)?;
+        if let Some(dir) = self.server_game_directory.as_ref().filter(|dir| **dir != self.game_directory) {
+            write!(f, " (\"{}\" in SvcServerInfo)", dir)?;
+        }
+        if let Some(classes) = &self.server_classes {
+            write!(f, ", {} server classes", classes.len())?;
+            // the player class usually says which game it is
+            if let Some(player) = classes.iter().find(|c| c.ends_with("Player") && *c != "CBasePlayer") {
+                write!(f, ", player class {}", player)?;
+            }
+        }
+        if let Some(id) = self.highest_user_message {
+            write!(f, ", user message ids up to {}", id)?;
+        }
+        return Ok(());
+    }
+}
@@ -1,5 +1,12 @@
 use crate::structs::datatables_manager::FlattenedProp;
 use crate::structs::demo::Demo;
+use iipdp::json::ToJson;
+use iipdp::analysis::view_track::{get_view_track, write_view_track_csv};
+use iipdp::analysis::player_state::{get_player_states, write_player_states_csv};
+use iipdp::analysis::portals::{get_portal_events, write_portal_report};
+use iipdp::analysis::inputs::{get_input_track, write_input_track_json, write_input_timeline_svg};
+use iipdp::analysis::movement::{MovementStats, BHOP_WINDOW};
+use iipdp::analysis::run::Segment;
 use crate::structs::packet::{PacketDataType, PacketType};
 use crate::info_processor::net_svc_message::NetSvcMessageDataTypes;
 use crate::structs::{packet_data_types as pdt, net_svc_message};
@@ -9,13 +16,12 @@ use crate::structs::stringtable::{write_stringtables_data_to_file, StringTableEn
 use crate::structs::send_table::{write_send_table_data_to_file, SendPropType};
 use crate::structs::utils::{bitflags_to_string, ServerClass};
 use std::path::Path;
-use std::fs::{self, File};
-use std::io::{Read, Write};
-use std::process::exit;
+use std::fs::File;
+use std::io::Write;
 use std::io;
 use std::io::{Seek, SeekFrom};
 
-pub fn print_header_info(demo: Demo) {
+pub fn print_header_info(demo: &Demo) {
     println!("File Stamp:       {}", demo.header.demo_file_stamp);
     println!("Demo Protocol:    {}", demo.header.demo_protocol);
     println!("Network Protocol: {}", demo.header.network_protocol);
@@ -23,63 +29,97 @@ pub fn print_header_info(demo: Demo) {
     println!("Client Name:      {}", demo.header.client_name);
     println!("Map Name:         {}", demo.header.map_name);
     println!("Game Directory:   {}", demo.header.game_directory);
+    println!("Game:             {}", demo.data_manager.game_name);
     println!("Playback Time:    {:.3}", demo.header.playback_time);
     println!("Playback Ticks:   {}", demo.header.playback_ticks);
     println!("Playback Frames:  {}", demo.header.playback_frames);
     println!("Sign On Length:   {}", demo.header.sign_on_length);
+    println!("Tick Interval:    {} ({:?})", demo.tick_interval(), demo.data_manager.tick_interval_source);
 
-    print!("\n");
+    println!();
 
+    print_time_info(demo);
+}
+
+// the part of print_header_info thats actually interesting for runs
+pub fn print_time_info(demo: &Demo) {
     let measured_ticks_and_time = demo.data_manager.get_measured_ticks_and_time();
     let adjusted_ticks_and_time = demo.data_manager.get_adjusted_ticks_and_time();
 
     println!("Measured Ticks:   {}",  (measured_ticks_and_time.0));
 
-    if measured_ticks_and_time.1 < 60f32 {
-        println!("Measured Time:    {}", format!("{:.3}", measured_ticks_and_time.1));
-    } else {
-        let minutes = (measured_ticks_and_time.1 / 60f32).floor();
-        let seconds = (measured_ticks_and_time.1 - (60f32 * minutes)).floor();
-        let millis = (measured_ticks_and_time.1 - (60f32 * minutes)).fract();
-        println!("Measured Time:    {}", format!("{}:{:02}.{:.0}", minutes, seconds, millis * 1000.0));
-    }
+    println!("Measured Time:    {}", format_time(measured_ticks_and_time.1));
 
     // only print adjusted time if there were any adjustments made
     if measured_ticks_and_time != adjusted_ticks_and_time {
         println!("\nAdjusted Ticks:   {}",  (adjusted_ticks_and_time.0));
 
-        if adjusted_ticks_and_time.1 < 60f32 {
-            println!("Adjusted Time:    {}", format!("{:.3}", adjusted_ticks_and_time.1));
-        } else {
-            let minutes = (adjusted_ticks_and_time.1 / 60f32).floor();
-            let seconds = (adjusted_ticks_and_time.1 - (60f32 * minutes)).floor();
-            let millis = (adjusted_ticks_and_time.1 - (60f32 * minutes)).fract();
-            println!("Adjusted Time:    {}", format!("{}:{:02}.{:.0}", minutes, seconds, millis * 1000.0));
+        println!("Adjusted Time:    {}", format_time(adjusted_ticks_and_time.1));
+        if let (Some(rule), Some(tick)) = (&demo.data_manager.adj_start_rule, demo.data_manager.adj_start_tick) {
+            println!("Started By:       {} (tick {})", rule, tick);
         }
+        if let (Some(rule), Some(tick)) = (&demo.data_manager.adj_end_rule, demo.data_manager.adj_end_tick) {
+            println!("Ended By:         {} (tick {})", rule, tick);
+        }
+    }
+}
+
+pub fn print_movement_stats(stats: &MovementStats) {
+    let percent = |ratio: Option<f32>| ratio.map(|r| format!("{:.1}%", r * 100.0)).unwrap_or_else(|| "-".to_string());
+
+    println!("Jump Presses:     {}", stats.jump_presses);
+    if stats.has_player_states {
+        println!("Jumps:            {}", stats.jumps);
+        println!("Crouch Jumps:     {}", stats.crouch_jumps);
+        println!("Bhop Attempts:    {} (jumps at most {} ticks after landing)", stats.bhop_attempts, BHOP_WINDOW);
+        println!("Perfect Bhops:    {} ({})", stats.perfect_bhops, percent(stats.perfect_bhop_ratio()));
+        println!("Ground Ticks:     {} on average before a bhop", stats.average_bhop_ground_ticks().map(|t| format!("{:.2}", t)).unwrap_or_else(|| "-".to_string()));
+        println!("Strafe Sync:      {} ({} ticks in the air)", percent(stats.strafe_sync()), stats.strafe_ticks);
+    } else {
+        // no player entity so no idea when the player is on the ground
+        println!("Strafe Sync:      {} ({} ticks, on the ground too)", percent(stats.strafe_sync()), stats.strafe_ticks);
     }
 }
 
+// one row per map of the run
+pub fn print_segment_table(segments: &[Segment]) {
+    println!("\n{:<4}{:<28}{:<7}{:<9}{:<12}{:<12}Rules", "#", "Map", "Demos", "Ticks", "Time", "Total");
+    for (i, segment) in segments.iter().enumerate() {
+        let rules = segment.rules();
+        println!("{:<4}{:<28}{:<7}{:<9}{:<12}{:<12}{}",
+            i + 1,
+            segment.map_name,
+            segment.demos.len(),
+            segment.ticks,
+            format_time(segment.time),
+            format_time(segment.cumulative_time),
+            if rules.is_empty() { "-".to_string() } else { rules.join(", ") },
+        );
+    }
+
+    let total_ticks: i32 = segments.iter().map(|s| s.ticks).sum();
+    let total_time = segments.last().map(|s| s.cumulative_time).unwrap_or(0.0);
+    println!("\nTotal Ticks: {}", total_ticks);
+    println!("Total Time:  {}", format_time(total_time));
+}
+
+// seconds under a minute, minutes:seconds.millis otherwise
+pub fn format_time(time: f32) -> String {
+    if time < 60f32 {
+        return format!("{:.3}", time);
+    }
+    // whole milliseconds so 1:05.007 doesnt turn into 1:05.7
+    let millis = (time as f64 * 1000.0).round() as u64;
+    return format!("{}:{:02}.{:03}", millis / 60_000, millis / 1000 % 60, millis % 1000);
+}
+
 // i should really move all of this out to their own functions
 #[allow(unused)]
-pub fn dump_file(file_path: &String, demo: Demo) {
-    let mut file = fs::File::create(file_path.trim_end_matches(".dem").to_owned() + "-demo_dump.txt").unwrap_or_else( |err| {
-        println!("Something went wrong when trying to create the file: {}", err);
-        io::stdin().read_line(&mut String::new()).unwrap();
-        exit(1);
-    });
-    
-    println!("File created at: {}\n", file_path.trim_end_matches(".dem").to_owned() + "-demo_dump.txt");
-
+pub fn dump_file(file: &mut File, file_path: &str, demo: Demo) {
     file.write_all("Generated by IIPDP v0.3.0\n".as_bytes());
     file.write_fmt(format_args!("File Name: {}\n", Path::new(&file_path).file_name().unwrap().to_str().unwrap()));
     
-    if demo.header.network_protocol < 15 {
-        file.write_all("Presumed game: Portal 3420\n\n".as_bytes());
-    } else if demo.header.network_protocol == 24 {
-        file.write_all("Presumed game: Portal Steampipe\n\n".as_bytes());
-    } else if demo.header.network_protocol == 15 {
-        file.write_all("Presumed game: Portal 5135 (source unpack)\n\n".as_bytes());
-    }
+    file.write_fmt(format_args!("Presumed game: {}\n\n", demo.data_manager.game_name));
     
     file.write_all(("File Stamp:       ".to_owned() + &demo.header.demo_file_stamp + "\n").as_bytes());
     file.write_all(("Demo Protocol:    ".to_owned() + &demo.header.demo_protocol.to_string() + "\n").as_bytes());
@@ -98,7 +138,7 @@ pub fn dump_file(file_path: &String, demo: Demo) {
         let cur_packet_type = packet.packet_type;
         
         if cur_packet_type == PacketType::SignOn || cur_packet_type == PacketType::Packet {
-            let packet_data: pdt::PP = packet.data.into();
+            let Ok(packet_data) = pdt::PP::try_from(packet.data) else { continue; };
             if cur_packet_type == PacketType::SignOn {
                 file.write_fmt(format_args!("[{}] SIGNON (1)\n", &packet.tick));
             } else {
@@ -117,19 +157,27 @@ pub fn dump_file(file_path: &String, demo: Demo) {
             file.write_fmt(format_args!("\t\tViewOrigin2:       {}\n", cmd_info.view_origin2));
             file.write_fmt(format_args!("\t\tLocalViewAngles2:  {}\n", cmd_info.local_view_angles2));
 
+            if let Some(cmd_info) = packet_data.split_screen_cmd_info {
+                file.write_all("\tSplit Screen CmdInfo:\n".as_bytes());
+                file.write_fmt(format_args!("\t\tFlags: {}\n", bitflags_to_string(cmd_info.flags.iter_names())));
+                file.write_fmt(format_args!("\t\tViewAngles:        {}\n", cmd_info.view_angles));
+                file.write_fmt(format_args!("\t\tViewOrigin:        {}\n", cmd_info.view_origin));
+                file.write_fmt(format_args!("\t\tLocalViewAngles:   {}\n", cmd_info.local_view_angles));
+            }
+
             file.write_fmt(format_args!("\tInSequence: {}\n", packet_data.in_sequence));
             file.write_fmt(format_args!("\tOutSequence: {}\n", packet_data.out_sequence));
             file.write_fmt(format_args!("\tData Size (bytes): {}", packet_data.size));
-            net_svc_message::write_msg_data_to_file(&mut file, packet_data.messages, &demo.data_manager);
+            net_svc_message::write_msg_data_to_file(file, packet_data.messages, &demo.data_manager);
         } else if cur_packet_type == PacketType::SyncTick {
             let _ = file.write_fmt(format_args!("[{}] SYNCTICK (3)\n", packet.tick));
         } else if cur_packet_type == PacketType::ConsoleCmd {
-            let packet_data: pdt::ConsoleCmd = packet.data.into();
+            let Ok(packet_data) = pdt::ConsoleCmd::try_from(packet.data) else { continue; };
             file.write_fmt(format_args!("[{}] CONSOLECMD (4)\n", packet.tick));
             file.write_fmt(format_args!("\tData Size (bytes): {}\n", packet_data.size));
             file.write_fmt(format_args!("\tData:\n\t\t{}\n", packet_data.data));
         } else if cur_packet_type == PacketType::UserCmd {
-            let packet_data: pdt::UserCmd = packet.data.into();
+            let Ok(packet_data) = pdt::UserCmd::try_from(packet.data) else { continue; };
             file.write_fmt(format_args!("[{}] USERCMD (5)\n", packet.tick));
             file.write_fmt(format_args!("\tCmd: {}\n", packet_data.cmd));
             file.write_fmt(format_args!("\tData Size (bytes): {}\n", packet_data.size));
@@ -151,7 +199,7 @@ pub fn dump_file(file_path: &String, demo: Demo) {
             file.write_fmt(format_args!("{} ", user_cmd_info.side_move.map(|i| {i.to_string()}).unwrap_or_else(|| {"Null".to_string()})));
             file.write_fmt(format_args!("{}\n", user_cmd_info.up_move.map(|i| {i.to_string()}).unwrap_or_else(|| {"Null".to_string()})));
 
-            file.write_fmt(format_args!("\t\tButtons: {}\n", bitflags_to_string(user_cmd_info.buttons.iter_names())));
+            file.write_fmt(format_args!("\t\tButtons: {}\n", user_cmd_info.buttons.map(|b| {bitflags_to_string(b.iter_names())}).unwrap_or_else(|| {"Null".to_string()})));
             file.write_fmt(format_args!("\t\tImpulse: {}\n", user_cmd_info.impulse.map(|i| {i.to_string()}).unwrap_or_else(|| {"Null".to_string()}))); 
 
             file.write_fmt(format_args!("\t\tWeapon Select, Subtype: {}, {}\n", user_cmd_info.weapon_select.map(|i| {i.to_string()}).unwrap_or_else(|| {"Null".to_string()}), 
@@ -161,12 +209,12 @@ pub fn dump_file(file_path: &String, demo: Demo) {
                                                                     user_cmd_info.mouse_dy.map(|i| {i.to_string()}).unwrap_or_else(|| {"Null".to_string()})));
 
         } else if cur_packet_type == PacketType::DataTables {
-            let packet_data: pdt::DataTables = packet.data.into();
+            let Ok(packet_data) = pdt::DataTables::try_from(packet.data) else { continue; };
             file.write_fmt(format_args!("[{}] DATATABLES (6)\n", packet.tick));
             file.write_fmt(format_args!("\tData Size (bytes): {}", packet_data.size));
             file.write_fmt(format_args!("\n\t{} send tables", packet_data.send_table_count));
             for table in packet_data.send_tables {
-                write_send_table_data_to_file(&mut file, table);
+                write_send_table_data_to_file(file, table);
             }
             file.write_fmt(format_args!("\n\t{} server classes", packet_data.class_count));
             for class in packet_data.server_classes {
@@ -177,27 +225,53 @@ pub fn dump_file(file_path: &String, demo: Demo) {
         } else if cur_packet_type == PacketType::Stop {
             file.write_fmt(format_args!("[{}] STOP (7)\n", packet.tick));
         } else if cur_packet_type == PacketType::StringTables {
-            let packet_data: pdt::StringTables = packet.data.into();
-            file.write_fmt(format_args!("[{}] STRINGTABLES (8)\n", packet.tick));
-            write_stringtables_data_to_file(&mut file, packet_data);
+            let Ok(packet_data) = pdt::StringTables::try_from(packet.data) else { continue; };
+            file.write_fmt(format_args!("[{}] STRINGTABLES ({})\n", packet.tick, if demo.header.demo_protocol == 4 { 9 } else { 8 }));
+            write_stringtables_data_to_file(file, packet_data, &demo.data_manager);
+        } else if cur_packet_type == PacketType::CustomData {
+            let Ok(packet_data) = pdt::CustomData::try_from(packet.data) else { continue; };
+            file.write_fmt(format_args!("[{}] CUSTOMDATA (8)\n", packet.tick));
+            file.write_fmt(format_args!("\tCallback Index: {}\n", packet_data.callback_index));
+            file.write_fmt(format_args!("\tData Size (bytes): {}\n", packet_data.size));
         } else if cur_packet_type == PacketType::Unknown {
             file.write_fmt(format_args!("[{}] Unknown packet type (most likely a bug)\n", packet.tick));
         }
         file.write_all("\n".as_bytes());
     }
-    println!("Dumping done!");
+    if let Some(err) = &demo.parse_error {
+        file.write_fmt(format_args!("Parsing stopped here, {}\n", err));
+    }
 }
 
-#[allow(unused)]
-pub fn dump_flattened_classes(file_path: &String, prop_lookup: Vec<(ServerClass, Vec<FlattenedProp>)>) {
-    let mut file = fs::File::create(file_path.trim_end_matches(".dem").to_owned() + "-fc_dump.txt").unwrap_or_else( |err| {
-        println!("Something went wrong when trying to create the file: {}", err);
-        io::stdin().read_line(&mut String::new()).unwrap();
-        exit(1);
-    });
-    
-    println!("File created at: {}\n", file_path.trim_end_matches(".dem").to_owned() + "-fc_dump.txt");
+// same as dump_file but as json, for tools that dont want to parse the text dump
+pub fn dump_json(mut file: &File, demo: &Demo) -> io::Result<()> {
+    return file.write_all((demo.to_json().to_pretty_string() + "\n").as_bytes());
+}
+
+// one row per tick with the view origin, angles and horizontal speed
+pub fn dump_csv(mut file: &File, demo: &Demo) -> io::Result<()> {
+    return write_view_track_csv(&mut file, &get_view_track(demo));
+}
+
+pub fn dump_player_csv(mut file: &File, demo: &Demo) -> io::Result<()> {
+    return write_player_states_csv(&mut file, &get_player_states(demo));
+}
+
+pub fn dump_portals(mut file: &File, demo: &Demo) -> io::Result<()> {
+    return write_portal_report(&mut file, &get_portal_events(demo));
+}
 
+// for input display overlays, the svg is for looking at a run without a video
+pub fn dump_inputs_json(mut file: &File, demo: &Demo) -> io::Result<()> {
+    return write_input_track_json(&mut file, &get_input_track(demo), demo.tick_interval());
+}
+
+pub fn dump_inputs_svg(mut file: &File, demo: &Demo) -> io::Result<()> {
+    return write_input_timeline_svg(&mut file, &get_input_track(demo), demo.tick_interval());
+}
+
+#[allow(unused)]
+pub fn dump_flattened_classes(mut file: &File, file_path: &str, prop_lookup: Vec<(ServerClass, Vec<FlattenedProp>)>) {
     file.write_all("Generated by IIPDP v0.3.0\n".as_bytes());
     file.write_fmt(format_args!("File Name: {}\n", Path::new(&file_path).file_name().unwrap().to_str().unwrap()));
 
@@ -240,61 +314,51 @@ pub fn dump_flattened_classes(file_path: &String, prop_lookup: Vec<(ServerClass,
 }
 
 #[allow(unused)]
-pub fn verifier_dump(file_path: &String, demo: Demo, dumpfile: &Option<&File>) {
-    let mut file: &File;
-    let creator: File;
-    if let None = dumpfile {
-        // single demo
-        creator = File::create(file_path.trim_end_matches(".dem").to_owned() + "-vdump.txt").unwrap_or_else( |err| {
-            println!("Something went wrong when trying to create the file: {}", err);
-            io::stdin().read_line(&mut String::new()).unwrap();
-            exit(1);
-        });
-        file = &creator;
+// the same file gets passed in for every demo in a folder, the beginning only gets written once
+pub fn verifier_dump(mut file: &File, file_path: &str, demo: Demo) {
+    // if file is empty
+    if file.seek(SeekFrom::End(0)).unwrap() == 0 {
         write_dump_beginning(file, &demo);
-    } else {
-        file = dumpfile.unwrap();
-        
-        // if file is empty
-        if file.seek(SeekFrom::End(0)).unwrap() == 0 {
-            write_dump_beginning(file, &demo);
-        }
     }
 
     file.write_fmt(format_args!("\n\nFile Name: {}\n", Path::new(&file_path).file_name().unwrap().to_str().unwrap()));
 
-    let signon: &PacketDataType = &demo.packets[0].data;
-    if let PacketDataType::Packet(p) = signon {
-        let server_count: &NetSvcMessageDataTypes = &p.messages[0].data;
-        if let NetSvcMessageDataTypes::SvcServerInfo(s) = server_count {
-            file.write_fmt(format_args!("Server Count: {}\n", s.server_count));
-        } else {
-            println!("Couldn't find server count for whatever reason :(");
-        }
-    } else {
-        println!("No SignOn packet found! Either iipdp is broken or the demo is bad.");
+    // a demo that stopped early can be missing any of the packets below, that goes in the file instead of crashing
+    if let Some(err) = &demo.parse_error {
+        file.write_fmt(format_args!("===WARNING: DEMO IS INCOMPLETE, {}===\n", err));
+    }
+
+    match demo.packets.first().map(|p| &p.data) {
+        Some(PacketDataType::Packet(p)) => {
+            if let Some(NetSvcMessageDataTypes::SvcServerInfo(s)) = p.messages.first().map(|m| &m.data) {
+                file.write_fmt(format_args!("Server Count: {}\n", s.server_count));
+            } else {
+                println!("Couldn't find server count for whatever reason :(");
+            }
+        },
+        _ => println!("No SignOn packet found! Either iipdp is broken or the demo is bad."),
     }
     file.write_fmt(format_args!("Client Name (Header): {}\n", demo.header.client_name));
     if demo.header.network_protocol < 15 {
         file.write_all("3420 demo, no friends ID\n".as_bytes());
     } else {
-        let stringtables = &demo.packets
-            .iter()
-            .find(|p| {p.packet_type == PacketType::StringTables})
-            .expect("NO STRINGTABLES PACKET IN THIS UNPACK DEMO!!! THIS IS BAD!!!")
-            .data;
         // this sucks a little bit less than before
-        if let PacketDataType::StringTables(s) = &stringtables {
-            let table = s.tables
-                .iter()
-                .find(|x| {x.name == "userinfo"}).unwrap();
-            if let StringTableEntryDataTypes::PlayerInfo(pui) = &table.table_entries[0].entry_data {
+        let player_info = demo.packets
+            .iter()
+            .filter(|p| p.packet_type == PacketType::StringTables)
+            .find_map(|p| if let PacketDataType::StringTables(s) = &p.data { Some(s) } else { None })
+            .and_then(|s| s.tables.iter().find(|x| x.name == "userinfo"))
+            .and_then(|table| table.table_entries.first());
+        match player_info.map(|entry| &entry.entry_data) {
+            Some(StringTableEntryDataTypes::PlayerInfo(pui)) => {
                 file.write_fmt(format_args!("Client Name (userinfo): {}\n", pui.name));
                 file.write_fmt(format_args!("Friends ID: {}", pui.friends_id));
                 if pui.name != demo.header.client_name {
                     file.write_all("\n===WARNING: CLIENT NAMES DON'T MATCH!!!===".as_bytes());
                 }
-            }
+            },
+            Some(_) => {},
+            None => { file.write_all("===WARNING: NO USERINFO STRING TABLE, NO FRIENDS ID===".as_bytes()); },
         }
     }
 }
@@ -303,11 +367,5 @@ pub fn verifier_dump(file_path: &String, demo: Demo, dumpfile: &Option<&File>) {
 #[allow(unused)]
 fn write_dump_beginning(mut file: &File, demo: &Demo) {
     file.write_all("Generated by IIPDP v0.3.0\n".as_bytes());
-    if demo.header.network_protocol < 15 {
-        file.write_all("Presumed game: Portal 3420\n\n".as_bytes());
-    } else if demo.header.network_protocol == 24 {
-        file.write_all("Presumed game: Portal Steampipe\n\n".as_bytes());
-    } else if demo.header.network_protocol == 15 {
-        file.write_all("Presumed game: Portal 5135 (source unpack)\n\n".as_bytes());
-    }
+    file.write_fmt(format_args!("Presumed game: {}\n\n", demo.data_manager.game_name));
 }
\ No newline at end of file
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::fmt;
+
+// json output without pulling in serde
+// every struct that ends up in the export implements ToJson, the field names are the same as the rust ones
+// so they only change if the structs change
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>), // a vec and not a map so the fields stay in the order they were written in
+}
+
+impl JsonValue {
+    pub fn object(fields: Vec<(&str, JsonValue)>) -> Self {
+        return JsonValue::Object(fields.into_iter().map(|(name, value)| (name.to_string(), value)).collect());
+    }
+
+    // these are for reading config files (see config.rs), None if the value is something else
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        return match self {
+            JsonValue::Object(fields) => fields.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        };
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        return match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        };
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        return match self {
+            JsonValue::Int(value) => Some(*value),
+            _ => None,
+        };
+    }
+
+    // ints are numbers too
+    pub fn as_f64(&self) -> Option<f64> {
+        return match self {
+            JsonValue::Int(value) => Some(*value as f64),
+            JsonValue::Float(value) => Some(*value),
+            _ => None,
+        };
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        return match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        };
+    }
+
+    // same as to_string but with newlines and indentation
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write_pretty(&mut out, 0);
+        return out;
+    }
+
+    fn write_pretty(&self, out: &mut String, indent: usize) {
+        match self {
+            JsonValue::Array(values) if !values.is_empty() => {
+                out.push_str("[\n");
+                for (i, value) in values.iter().enumerate() {
+                    out.push_str(&"  ".repeat(indent + 1));
+                    value.write_pretty(out, indent + 1);
+                    out.push_str(if i + 1 < values.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&"  ".repeat(indent));
+                out.push(']');
+            },
+            JsonValue::Object(fields) if !fields.is_empty() => {
+                out.push_str("{\n");
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    out.push_str(&"  ".repeat(indent + 1));
+                    write_escaped(out, name);
+                    out.push_str(": ");
+                    value.write_pretty(out, indent + 1);
+                    out.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+                }
+                out.push_str(&"  ".repeat(indent));
+                out.push('}');
+            },
+            _ => out.push_str(&self.to_string()),
+        }
+    }
+}
+
+fn write_escaped(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(value) => write!(f, "{}", value),
+            JsonValue::Int(value) => write!(f, "{}", value),
+            // json has no nan or infinity
+            JsonValue::Float(value) if !value.is_finite() => write!(f, "null"),
+            JsonValue::Float(value) => write!(f, "{}", value),
+            JsonValue::String(value) => {
+                let mut out = String::new();
+                write_escaped(&mut out, value);
+                write!(f, "{}", out)
+            },
+            JsonValue::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            },
+            JsonValue::Object(fields) => {
+                write!(f, "{{")?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ",")?;
+                    }
+                    let mut out = String::new();
+                    write_escaped(&mut out, name);
+                    write!(f, "{}:{}", out, value)?;
+                }
+                write!(f, "}}")
+            },
+        }
+    }
+}
+
+pub trait ToJson {
+    fn to_json(&self) -> JsonValue;
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> JsonValue { return JsonValue::Bool(*self); }
+}
+
+impl ToJson for i32 {
+    fn to_json(&self) -> JsonValue { return JsonValue::Int(*self as i64); }
+}
+
+impl ToJson for u8 {
+    fn to_json(&self) -> JsonValue { return JsonValue::Int(*self as i64); }
+}
+
+impl ToJson for usize {
+    fn to_json(&self) -> JsonValue { return JsonValue::Int(*self as i64); }
+}
+
+// u64 doesnt fit in an i64 and most json parsers cant do big numbers anyway so these are strings
+impl ToJson for u64 {
+    fn to_json(&self) -> JsonValue { return JsonValue::String(self.to_string()); }
+}
+
+// going through the string keeps 0.015 as 0.015 instead of 0.014999999664723873
+impl ToJson for f32 {
+    fn to_json(&self) -> JsonValue { return JsonValue::Float(self.to_string().parse().unwrap_or(f64::NAN)); }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> JsonValue { return JsonValue::String(self.to_string()); }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> JsonValue { return JsonValue::String(self.clone()); }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> JsonValue {
+        return match self {
+            Some(value) => value.to_json(),
+            None => JsonValue::Null,
+        };
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> JsonValue { return JsonValue::Array(self.iter().map(|v| v.to_json()).collect()); }
+}
+
+// sorted so the output is the same every time
+impl<T: ToJson> ToJson for HashMap<String, T> {
+    fn to_json(&self) -> JsonValue {
+        let mut keys: Vec<&String> = self.keys().collect();
+        keys.sort();
+        return JsonValue::Object(keys.into_iter().map(|k| (k.clone(), self[k].to_json())).collect());
+    }
+}
+
+// implements ToJson for a struct as an object with the given fields
+macro_rules! json_object {
+    ($t:ty { $($field:ident),* $(,)? }) => {
+        impl $crate::json::ToJson for $t {
+            #[allow(unused)]
+            fn to_json(&self) -> $crate::json::JsonValue {
+                use $crate::json::ToJson;
+                return $crate::json::JsonValue::object(vec![$((stringify!($field), self.$field.to_json())),*]);
+            }
+        }
+    };
+}
+
+// for enums without data, written as the name of the variant
+macro_rules! json_debug_string {
+    ($($t:ty),* $(,)?) => {
+        $(impl $crate::json::ToJson for $t {
+            fn to_json(&self) -> $crate::json::JsonValue {
+                return $crate::json::JsonValue::String(format!("{:?}", self));
+            }
+        })*
+    };
+}
+
+// bitflags are written as a list of the names of the flags that are set
+macro_rules! json_flags {
+    ($($t:ty),* $(,)?) => {
+        $(impl $crate::json::ToJson for $t {
+            fn to_json(&self) -> $crate::json::JsonValue {
+                return $crate::json::JsonValue::Array(self.iter_names().map(|(name, _)| $crate::json::JsonValue::String(name.to_string())).collect());
+            }
+        })*
+    };
+}
+
+pub(crate) use json_object;
+pub(crate) use json_debug_string;
+pub(crate) use json_flags;
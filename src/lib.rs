@@ -0,0 +1,99 @@
+// iipdp as a library, main.rs is just the cli on top of this
+// parse_demo is what you want most of the time, everything else is public if you need to dig deeper
+
+// explicit returns and x: x everywhere is just how this codebase is written
+#![allow(clippy::needless_return, clippy::redundant_field_names, clippy::new_without_default)]
+
+#[macro_use] extern crate enum_primitive;
+
+pub mod structs;
+pub mod bitreader;
+pub mod parser;
+pub mod adjust_time;
+pub mod error;
+pub mod json;
+pub mod analysis;
+pub mod config;
+pub mod game_profile;
+
+pub use structs::demo::Demo;
+pub use structs::demo_header::DemoHeader;
+pub use structs::packet::Packet;
+pub use structs::net_svc_message::NetSvcMessage;
+pub use structs::user_message::UserMessage;
+pub use error::Error;
+pub use adjust_time::TimingRules;
+pub use game_profile::GameProfiles;
+
+use bitreader::BitReader;
+use error::ParseError;
+use structs::data_manager::Game;
+
+// the header is always 1072 bytes, anything shorter cant be a demo
+const HEADER_SIZE: usize = 1072;
+
+// how parse_demo_with_options should parse the demo
+// change only what you need on top of the default, like ParseOptions { full: false, ..Default::default() }
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub full: bool, // parse datatables, string tables and entities, a lot slower but you need it for anything other than timing
+    pub best_effort: bool, // dont fail on parse errors, keep every packet before the error and put the error in Demo::parse_error
+    pub rules: TimingRules, // how the demo is timed, see adjust_time.rs
+    pub profiles: GameProfiles, // the games the demo can be from, see game_profile.rs
+}
+
+// everything gets parsed, with the portal 1 rules and only the games that are built in
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            full: true,
+            best_effort: false,
+            rules: TimingRules::portal(),
+            profiles: GameProfiles::builtin(),
+        }
+    }
+}
+
+// parses everything including datatables, string tables and entities
+pub fn parse_demo(bytes: &[u8]) -> Result<Demo, Error> {
+    return parse_demo_with_options(bytes, &ParseOptions::default());
+}
+
+// a bad header or a game none of the profiles fit is always an error, with best_effort anything else isnt
+pub fn parse_demo_with_options(bytes: &[u8], options: &ParseOptions) -> Result<Demo, Error> {
+    let rules = &options.rules;
+    if bytes.len() < HEADER_SIZE {
+        return Err(Error::InvalidDemo(format!("file is {} bytes, shorter than the header", bytes.len())));
+    }
+
+    let mut reader = BitReader::new(bytes.to_vec());
+    let mut demo = Demo::new();
+
+    demo.header = DemoHeader::parse(&mut reader);
+    if demo.header.demo_file_stamp != "HL2DEMO" {
+        return Err(Error::InvalidDemo(format!("bad file stamp \"{}\"", demo.header.demo_file_stamp)));
+    }
+
+    demo.data_manager.get_info_from_header(&demo.header, &options.profiles);
+    if demo.data_manager.game == Game::UNKNOWN {
+        return Err(Error::UnsupportedGame(demo.data_manager.game_characteristics.clone()));
+    }
+    demo.data_manager.timing_rules = rules.clone();
+    // entity rules cant work without the entities
+    demo.data_manager.dumping = options.full || rules.needs_entities();
+    let result = parser::get_packets(&mut reader, &mut demo);
+    // also for demos that stopped early, everything before the error still counts
+    adjust_time::adjust_after_parsing(&mut demo.data_manager);
+    if let Err(err) = result {
+        // the profile might have fit the header, but the rest of the demo says otherwise
+        if let ParseError::UnsupportedGame(_) = err {
+            return Err(Error::UnsupportedGame(demo.data_manager.game_characteristics.clone()));
+        }
+        if !options.best_effort {
+            return Err(Error::Parse(err));
+        }
+        demo.parse_error = Some(err);
+    }
+
+    return Ok(demo);
+}
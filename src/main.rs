@@ -1,95 +1,287 @@
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
+
 use std::fs;
 use std::env;
 use std::fs::File;
 use std::io;
+use std::io::IsTerminal;
 use std::path::Path;
 use std::process::exit;
 use std::time::Instant;
 use std::ffi::OsStr;
 
-use args::Args;
-use structs::demo::Demo;
-use structs::demo_header::DemoHeader;
-use bitreader::BitReader;
-
-#[macro_use] extern crate enum_primitive;
-use bitflags;
+use args::{Args, Command, Format};
+use iipdp::structs;
+use iipdp::{Demo, ParseOptions, TimingRules, GameProfiles};
+use iipdp::analysis::run::{RunDemo, get_segments, natural_cmp, write_livesplit_splits};
+use iipdp::analysis::movement::{MovementStats, get_movement_stats};
 
-// declaring modules
-mod structs;
-mod bitreader;
+// declaring modules, everything else lives in the library
 mod info_processor;
-mod parser;
-mod adjust_time;
 mod args;
 
-fn main() {
-    let args: Args = Args::parse(env::args().collect());
+// exit codes, also listed in the usage text
+const EXIT_OK: i32 = 0;
+const EXIT_INVALID_DEMO: i32 = 1;
+const EXIT_BAD_ARGS: i32 = 2;
+const EXIT_PARSE_ERROR: i32 = 3;
 
-    let path: &Path = Path::new(&args.demo_name);
+// drag and drop opens a console window that closes as soon as we exit, so wait for enter first
+// scripts and ci dont have anyone to press enter so dont wait if stdin isnt a terminal
+fn exit_with(args: &Args, code: i32) -> ! {
+    if !args.no_pause && io::stdin().is_terminal() {
+        io::stdin().read_line(&mut String::new()).unwrap_or_default();
+    }
+    exit(code);
+}
 
-    if path.is_file() {
-        if path.extension().unwrap_or_else(|| {OsStr::new("nope")}) == "dem" {
-            let mut main_reader: BitReader = BitReader::new(fs::read(args.demo_name.clone()).unwrap_or_else(|err| {
-                println!(r#"Demo file reading failed because of: {} ¯\_(ツ)_/¯"#, err);
-                io::stdin().read_line(&mut String::new()).unwrap();
-                exit(1);
-            }));
-            println!("Parsing...\n");
-            let start_time = Instant::now();
-        
-            let mut demo = Demo::new();
-        
-            demo.header = DemoHeader::parse(&mut main_reader);
+// reads and parses one demo, only parses everything if we are dumping
+// demos that got cut off by a crash still get parsed up to where they stop
+fn read_demo(path: &Path, dump: bool, quiet: bool, rules: &TimingRules, profiles: &GameProfiles) -> Option<Demo> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            eprintln!(r#"Demo file reading failed because of: {} ¯\_(ツ)_/¯"#, err);
+            return None;
+        },
+    };
+    if !quiet {
+        println!("Parsing...\n");
+    }
 
-            if demo.header.demo_file_stamp != "HL2DEMO" {
-                println!("Invalid demo file");
-                io::stdin().read_line(&mut String::new()).unwrap();
-                return;
+    let options = ParseOptions { full: dump, best_effort: true, rules: rules.clone(), profiles: profiles.clone() };
+    match iipdp::parse_demo_with_options(&bytes, &options) {
+        Ok(demo) => {
+            if let Some(err) = &demo.parse_error {
+                eprintln!("Warning: demo is incomplete, {}\nEverything before that is still used.\n", err);
+            }
+            if demo.data_manager.has_unknown_user_messages() {
+                eprintln!("Warning: the demo has user messages {} doesn't have, it might be from another game.\n", demo.data_manager.game_name);
+            }
+            if !demo.has_expected_tick_rate() {
+                eprintln!("Warning: demo runs at {:.2} ticks per second instead of 66.67, its time uses the demos own tick interval ({}).\n", demo.tick_rate(), demo.tick_interval());
             }
+            return Some(demo);
+        },
+        Err(err) => {
+            eprintln!("{}", err);
+            if let iipdp::Error::UnsupportedGame(_) = err {
+                eprintln!("If it's a mod, a game profile for it can be loaded with --games, see games/example.toml");
+            }
+            return None;
+        },
+    }
+}
 
-            demo.data_manager.get_info_from_header(&demo.header);
-            demo.data_manager.dumping = args.dump;
-            demo.packets = parser::get_packets(&mut main_reader, &mut demo);
-        
-            if !args.dump {
-                info_processor::print_header_info(demo);
-                println!("\nParsed in {:?}", Instant::now().duration_since(start_time));
-            } else {
-                if args.fc {
-                    info_processor::dump_flattened_classes(&args.demo_name, demo.data_manager.dt_mgr.prop_lookup);
-                    println!("\nDumped in {:?}", Instant::now().duration_since(start_time));
-                } else if args.v {
-                    info_processor::verifier_dump(&args.demo_name, demo, &None);
-                    println!("\nDumped in {:?}", Instant::now().duration_since(start_time));
-                } else {
-                    info_processor::dump_file(&args.demo_name, demo);
-                    println!("\nDumped in {:?}", Instant::now().duration_since(start_time));
-                }
+fn is_demo(path: &Path) -> bool {
+    return path.extension().unwrap_or_else(|| {OsStr::new("nope")}) == "dem";
+}
+
+// the suffix that gets added to the demo name for the output file
+fn output_suffix(args: &Args) -> &'static str {
+    return match (args.command, args.format) {
+        (Command::Verify, _) => "-vdump.txt",
+        (_, Format::FlattenedClasses) => "-fc_dump.txt",
+        (_, Format::Json) => "-demo_dump.json",
+        (_, Format::Csv) => "-ticks.csv",
+        (_, Format::PlayerCsv) => "-player.csv",
+        (_, Format::Portals) => "-portals.txt",
+        (_, Format::InputsJson) => "-inputs.json",
+        (_, Format::InputsSvg) => "-inputs.svg",
+        (_, Format::Lss) => "-splits.lss",
+        (_, Format::Text) => "-demo_dump.txt",
+    };
+}
+
+// next to the demo by default
+// --output is the file for a single demo and the folder to put the files in for a folder of demos
+fn output_path(args: &Args, demo_path: &Path, in_folder: bool) -> String {
+    return match &args.output {
+        Some(output) if !in_folder => output.clone(),
+        Some(dir) => {
+            let name = demo_path.file_name().unwrap_or_default().to_string_lossy();
+            Path::new(dir).join(name.trim_end_matches(".dem").to_owned() + output_suffix(args)).to_string_lossy().into_owned()
+        },
+        None => demo_path.to_string_lossy().trim_end_matches(".dem").to_owned() + output_suffix(args),
+    };
+}
+
+fn create_output(args: &Args, path: &str) -> File {
+    let file = File::create(path).unwrap_or_else(|err| {
+        eprintln!("Something went wrong when trying to create the file: {}", err);
+        exit_with(args, EXIT_INVALID_DEMO);
+    });
+    if !args.quiet {
+        println!("File created at: {}\n", path);
+    }
+    return file;
+}
+
+// does whatever the command says with one parsed demo
+fn run_command(args: &Args, demo_path: &Path, demo: Demo, in_folder: bool, vdump_file: Option<&File>) {
+    let demo_name = demo_path.to_string_lossy().into_owned();
+
+    match args.command {
+        Command::Info => info_processor::print_header_info(&demo),
+        Command::Time => info_processor::print_time_info(&demo),
+        Command::Stats => info_processor::print_movement_stats(&get_movement_stats(&demo)),
+        Command::Verify => {
+            let Some(file) = vdump_file else { return; };
+            info_processor::verifier_dump(file, &demo_name, demo);
+        },
+        Command::Dump | Command::Export => {
+            let mut file = create_output(args, &output_path(args, demo_path, in_folder));
+            let result = match args.format {
+                Format::Text => {
+                    info_processor::dump_file(&mut file, &demo_name, demo);
+                    Ok(())
+                },
+                Format::FlattenedClasses => {
+                    info_processor::dump_flattened_classes(&file, &demo_name, demo.data_manager.dt_mgr.prop_lookup);
+                    Ok(())
+                },
+                Format::Json => info_processor::dump_json(&file, &demo),
+                Format::Csv => info_processor::dump_csv(&file, &demo),
+                Format::PlayerCsv => info_processor::dump_player_csv(&file, &demo),
+                Format::Portals => info_processor::dump_portals(&file, &demo),
+                Format::InputsJson => info_processor::dump_inputs_json(&file, &demo),
+                Format::InputsSvg => info_processor::dump_inputs_svg(&file, &demo),
+                Format::Lss => Ok(()), // only for run
+            };
+            if let Err(err) = result {
+                eprintln!("Something went wrong when writing the file: {}", err);
+                exit_with(args, EXIT_INVALID_DEMO);
+            } else if !args.quiet {
+                println!("Dumping done!");
             }
+        },
+        Command::Run | Command::Help => (),
+    }
+}
+
+// every demo in the folder in the order they were recorded
+fn get_demo_paths(path: &Path) -> Vec<std::path::PathBuf> {
+    let mut files: Vec<_> = fs::read_dir(path).unwrap()
+                                          .map(|r| r.unwrap().path())
+                                          .filter(|p| is_demo(p))
+                                          .collect();
+    files.sort_by(|a, b| natural_cmp(&a.to_string_lossy(), &b.to_string_lossy()));
+    return files;
+}
+
+// the run command, returns the exit code
+fn time_run(args: &Args, path: &Path, rules: &TimingRules, profiles: &GameProfiles) -> i32 {
+    let mut exit_code = EXIT_OK;
+    let paths = if path.is_dir() { get_demo_paths(path) } else { vec![path.to_path_buf()] };
+
+    let mut demos: Vec<RunDemo> = Vec::new();
+    let mut game_name = String::new();
+    for demo_path in paths {
+        if !args.quiet {
+            println!("File Name: {:?}", demo_path.file_name().unwrap_or_default());
+        }
+        let Some(demo) = read_demo(&demo_path, false, args.quiet, rules, profiles) else {
+            exit_code = exit_code.max(EXIT_INVALID_DEMO);
+            continue;
+        };
+        if demo.parse_error.is_some() {
+            exit_code = exit_code.max(EXIT_PARSE_ERROR);
+        }
+        if game_name.is_empty() {
+            game_name = demo.header.game_directory.clone();
+        }
+        demos.push(RunDemo::new(demo_path.file_name().unwrap_or_default().to_string_lossy().into_owned(), &demo));
+    }
+
+    let segments = get_segments(demos);
+    if args.format == Format::Lss {
+        let file = create_output(args, &output_path(args, path, false));
+        if let Err(err) = write_livesplit_splits(&mut &file, &game_name, &segments) {
+            eprintln!("Something went wrong when writing the file: {}", err);
+            return EXIT_INVALID_DEMO;
+        }
+        if !args.quiet {
+            println!("Dumping done!");
+        }
+    } else {
+        info_processor::print_segment_table(&segments);
+    }
+    return exit_code;
+}
+
+fn main() {
+    let args: Args = Args::parse(env::args().collect()).unwrap_or_else(|err| {
+        eprintln!("Error: {}\nRun \"iipdp --help\" to see how to use iipdp.", err);
+        exit(EXIT_BAD_ARGS);
+    });
+
+    if args.command == Command::Help {
+        Args::print_usage();
+        return;
+    }
+
+    let rules = match &args.rules {
+        Some(rules_path) => TimingRules::load(Path::new(rules_path)).unwrap_or_else(|err| {
+            eprintln!("Error in the rules file: {}", err);
+            exit_with(&args, EXIT_BAD_ARGS);
+        }),
+        None => TimingRules::portal(),
+    };
+    let profiles = match &args.games {
+        Some(games_path) => GameProfiles::load(Path::new(games_path)).unwrap_or_else(|err| {
+            eprintln!("Error in the game profiles file: {}", err);
+            exit_with(&args, EXIT_BAD_ARGS);
+        }),
+        None => GameProfiles::builtin(),
+    };
+
+    let path: &Path = Path::new(&args.demo_name);
+    // info and time only need the header and the packets, everything else wants all the data
+    let full = !matches!(args.command, Command::Info | Command::Time);
+    let done_text = if full { "Dumped" } else { "Parsed" };
+    let mut exit_code = EXIT_OK;
+
+    if args.command == Command::Run && (path.is_file() || path.is_dir()) {
+        exit_code = time_run(&args, path, &rules, &profiles);
+    } else if path.is_file() {
+        if !is_demo(path) {
+            eprintln!("Invalid file!");
+            exit_with(&args, EXIT_INVALID_DEMO);
+        }
+
+        let start_time = Instant::now();
+        let Some(demo) = read_demo(path, full, args.quiet, &rules, &profiles) else {
+            exit_with(&args, EXIT_INVALID_DEMO);
+        };
+        if demo.parse_error.is_some() {
+            exit_code = EXIT_PARSE_ERROR;
+        }
+
+        let vdump_file = if args.command == Command::Verify {
+            Some(create_output(&args, &output_path(&args, path, false)))
         } else {
-            println!("Invalid file!");
-            io::stdin().read_line(&mut String::new()).unwrap();
-            return;
+            None
+        };
+        run_command(&args, path, demo, false, vdump_file.as_ref());
+        if !args.quiet {
+            println!("\n{} in {:?}", done_text, Instant::now().duration_since(start_time));
         }
     } else if path.is_dir() {
-        let mut vdumpfile: Option<&File> = None;
-        let creator: File;
-        if args.v && args.dump {
-            let new_path = path.to_str().unwrap().to_string().trim_end_matches(".dem").to_owned() + "-vdump.txt";
-            creator = File::create(new_path.clone()).unwrap_or_else(|_| {
-                println!("Error when creating vdump file :(");
-                exit(1);
-            });
-            println!("File created at: {}\n", new_path);
-            vdumpfile = Some(&creator);
+        // every demo in the folder goes into the same vdump file, so --output is that file and not a folder
+        let vdump_file = if args.command == Command::Verify {
+            Some(create_output(&args, &output_path(&args, path, false)))
+        } else {
+            None
+        };
+        if let (Some(dir), Command::Dump | Command::Export) = (&args.output, args.command) {
+            if let Err(err) = fs::create_dir_all(dir) {
+                eprintln!("Couldn't create the output folder: {}", err);
+                exit_with(&args, EXIT_INVALID_DEMO);
+            }
         }
-        let start_time = Instant::now();
-        let mut files: Vec<_> = fs::read_dir(path).unwrap()
-                                              .map(|r| r.unwrap())
-                                              .collect();
-        files.sort_by_key(|dir| dir.path());
 
+        let start_time = Instant::now();
+        let files = get_demo_paths(path);
 
         let mut total_measured_ticks: i32 = 0;
         let mut total_measured_time: f32 = 0.0;
@@ -97,86 +289,58 @@ fn main() {
         let mut total_adjusted_ticks: i32 = 0;
         let mut total_adjusted_time: f32 = 0.0;
 
+        let mut total_stats = MovementStats::new();
+
         for file in files {
-            if file.path().extension().unwrap_or_else(|| {OsStr::new("nope")}) == "dem" {
-                println!("\n\nFile Name: {:?}", file.file_name());
-                
-                let mut main_reader: BitReader = BitReader::new(fs::read(file.path()).unwrap_or_else(|err| {
-                    println!(r#"Demo file reading failed because of: {} ¯\_(ツ)_/¯"#, err);
-                    io::stdin().read_line(&mut String::new()).unwrap();
-                    exit(1);
-                }));
-            
-                println!("Parsing...\n");
-                let start_time = Instant::now();
-            
-                let mut demo = Demo::new();
-
-                demo.header = DemoHeader::parse(&mut main_reader);
-
-                if demo.header.demo_file_stamp != "HL2DEMO" {
-                    println!("Invalid demo file");
-                    io::stdin().read_line(&mut String::new()).unwrap();
-                    return;
-                }
-
-                demo.data_manager.get_info_from_header(&demo.header);
-                demo.data_manager.dumping = args.dump;
-                demo.packets = parser::get_packets(&mut main_reader, &mut demo);
-            
-                if demo.header.demo_file_stamp != "HL2DEMO" {
-                    println!("Invalid demo file");
-                    io::stdin().read_line(&mut String::new()).unwrap();
-                    return;
-                }
-            
-                total_measured_ticks += demo.data_manager.get_measured_ticks_and_time().0;
-                total_measured_time += demo.data_manager.get_measured_ticks_and_time().1;
-
-                total_adjusted_ticks += demo.data_manager.get_adjusted_ticks_and_time().0;
-                total_adjusted_time += demo.data_manager.get_adjusted_ticks_and_time().1;
-
-                if args.dump {
-                    if args.fc {
-                        info_processor::dump_flattened_classes(&args.demo_name, demo.data_manager.dt_mgr.prop_lookup);
-                        println!("\nDumped in {:?}", Instant::now().duration_since(start_time));
-                    } else if args.v {
-                        info_processor::verifier_dump(&file.file_name().to_string_lossy().into_owned(), demo, &vdumpfile);
-                        println!("\nDumped in {:?}", Instant::now().duration_since(start_time));
-                    } else {
-                        info_processor::dump_file(&args.demo_name, demo);
-                        println!("\nDumped in {:?}", Instant::now().duration_since(start_time));
-                    }
-                } else {
-                    info_processor::print_header_info(demo);
-                    println!("\nParsed in {:?}", Instant::now().duration_since(start_time));
-                }
+            if !args.quiet {
+                println!("\n\nFile Name: {:?}", file.file_name().unwrap_or_default());
             }
-        }
 
-        println!("\n\nTotal Measured Ticks: {}", total_measured_ticks);
+            let start_time = Instant::now();
+            // one broken demo shouldnt stop the rest from being parsed
+            let Some(demo) = read_demo(&file, full, args.quiet, &rules, &profiles) else {
+                exit_code = exit_code.max(EXIT_INVALID_DEMO);
+                continue;
+            };
+            if demo.parse_error.is_some() {
+                exit_code = exit_code.max(EXIT_PARSE_ERROR);
+            }
+        
+            total_measured_ticks += demo.data_manager.get_measured_ticks_and_time().0;
+            total_measured_time += demo.data_manager.get_measured_ticks_and_time().1;
+
+            total_adjusted_ticks += demo.data_manager.get_adjusted_ticks_and_time().0;
+            total_adjusted_time += demo.data_manager.get_adjusted_ticks_and_time().1;
 
-        let mut minutes = (total_measured_time / 60f32).floor();
-        let mut seconds = (total_measured_time - (60f32 * minutes)).floor();
-        let mut millis = (total_measured_time - (60f32 * minutes)).fract();
+            if args.command == Command::Stats {
+                total_stats.add(&get_movement_stats(&demo));
+            }
+            run_command(&args, &file, demo, true, vdump_file.as_ref());
+            if !args.quiet {
+                println!("\n{} in {:?}", done_text, Instant::now().duration_since(start_time));
+            }
+        }
 
-        println!("Total Measured Time: {}:{:02}.{:.0}", minutes, seconds, millis * 1000.0);
+        if !args.quiet {
+            println!("\n\nTotal Measured Ticks: {}", total_measured_ticks);
+            println!("Total Measured Time: {}", info_processor::format_time(total_measured_time));
 
-        if total_adjusted_ticks != total_measured_ticks {
-            println!("\nTotal Adjusted Ticks: {}", total_adjusted_ticks);
+            if total_adjusted_ticks != total_measured_ticks {
+                println!("\nTotal Adjusted Ticks: {}", total_adjusted_ticks);
+                println!("Total Adjusted Time: {}", info_processor::format_time(total_adjusted_time));
+            }
 
-            minutes = (total_adjusted_time / 60f32).floor();
-            seconds = (total_adjusted_time - (60f32 * minutes)).floor();
-            millis = (total_adjusted_time - (60f32 * minutes)).fract();
+            if args.command == Command::Stats {
+                println!("\nAll Demos:");
+                info_processor::print_movement_stats(&total_stats);
+            }
 
-            println!("Total Adjusted Time: {}:{:02}.{:.0}", minutes, seconds, millis * 1000.0);
+            println!("\nParsed all files in: {:?}", Instant::now().duration_since(start_time));
         }
-            
-
-        println!("\nParsed all files in: {:?}", Instant::now().duration_since(start_time));
     } else {
-        println!("Something went wrong with your arguments.");
+        eprintln!("Couldn't find \"{}\", it has to be a demo or a folder with demos.", args.demo_name);
+        exit_with(&args, EXIT_INVALID_DEMO);
     }
-    
-    io::stdin().read_line(&mut String::new()).unwrap();
+
+    exit_with(&args, exit_code);
 }
@@ -1,11 +1,13 @@
-use crate::adjust_time::{try_adjust_for_wakeup, try_adjust_for_glados_death};
+use crate::adjust_time::{try_adjust_for_fix_angle, try_adjust_for_console_command, try_adjust_for_position};
 use crate::structs::cmd_info::CmdInfo;
 use crate::structs::data_manager::DataManager;
 use crate::structs::demo::Demo;
-use crate::structs::net_svc_message::{parse, NetSvcMessageTypes};
+use crate::structs::net_svc_message::{parse, NetSvcMessageTypes, NetSvcMessageDataTypes};
+use crate::structs::entities::PacketEntitiesData;
 use crate::structs::packet::{Packet, PacketDataType, PacketType};
 use crate::bitreader::BitReader;
-use crate::structs::packet_data_types::{PP, ConsoleCmd, UserCmd, SyncTick, StringTables, DataTables, Stop};
+use crate::error::ParseError;
+use crate::structs::packet_data_types::{PP, ConsoleCmd, UserCmd, SyncTick, StringTables, DataTables, Stop, CustomData};
 use crate::structs::stringtable::StringTable;
 use crate::structs::user_cmd_info::UserCmdInfo;
 use crate::structs::send_table::SendTable;
@@ -19,38 +21,92 @@ use crate::structs::datatables_manager::DataTablesManager;
 */
 
 // takes reader and demo references
-// returns a vector of Packet;
-pub fn get_packets(reader: &mut BitReader, demo: &mut Demo) -> Vec<Packet> {
-    let mut packets: Vec<Packet> = Vec::new();
+// puts every packet into demo.packets as its parsed so if something goes wrong everything before it is still there
+// returns where and why it stopped if it didnt make it to the stop packet;
+pub fn get_packets(reader: &mut BitReader, demo: &mut Demo) -> Result<(), ParseError> {
+    let packets = &mut demo.packets;
+    let mut skipped_entities_read = false;
 
     loop {
         let mut cur_packet: Packet = Packet::new();
+        let packet_offset = reader.current;
         let packet_type = reader.read_int(8);
-        cur_packet.packet_type = PacketType::from_int(packet_type);
+        cur_packet.packet_type = PacketType::from_int(packet_type, demo.data_manager.demo_protocol);
+        demo.data_manager.cur_packet_index = packets.len();
+
+        if let Some(bit_offset) = reader.overflowed {
+            return Err(ParseError::Truncated(demo.data_manager.error_pos(bit_offset)));
+        }
+        // same as with messages, if we dont know what it is we dont know how long it is
+        if cur_packet.packet_type == PacketType::Unknown {
+            return Err(ParseError::UnknownPacketType(packet_type, demo.data_manager.error_pos(packet_offset)));
+        }
+
+        // the signon packets have entity data that we cant read until we have the datatables and the stringtables
+        // by the time the first normal packet comes around we have everything so read it then
+        if !skipped_entities_read && cur_packet.packet_type == PacketType::Packet && !demo.data_manager.dt_mgr.prop_lookup.is_empty() {
+            read_skipped_entities(reader, packets, &mut demo.data_manager);
+            skipped_entities_read = true;
+        }
+
         if cur_packet.packet_type != PacketType::Stop {
             cur_packet.tick = reader.read_int(32);
             if cur_packet.tick > 0 {
                 demo.data_manager.last_packet_tick = cur_packet.tick;
             }
+            demo.data_manager.cur_tick = cur_packet.tick;
+            if demo.data_manager.demo_protocol == 4 {
+                cur_packet.slot = Some(reader.read_int(8)); // which split screen player the packet is for
+            }
 
-            cur_packet.data = read_packet_data(reader, cur_packet.packet_type, &mut demo.data_manager, cur_packet.tick);
+            cur_packet.data = read_packet_data(reader, cur_packet.packet_type, &mut demo.data_manager, cur_packet.tick)?;
         } else {
             cur_packet.tick = reader.read_int(24); // last int is 3 bytes for whatever reason
 
-            cur_packet.data = read_packet_data(reader, cur_packet.packet_type, &mut demo.data_manager, cur_packet.tick);
+            cur_packet.data = read_packet_data(reader, cur_packet.packet_type, &mut demo.data_manager, cur_packet.tick)?;
+
+            if let Some(bit_offset) = reader.overflowed {
+                return Err(ParseError::Truncated(demo.data_manager.error_pos(bit_offset)));
+            }
 
             packets.push(cur_packet);
 
-            return packets;
+            return Ok(());
+        }
+
+        if let Some(bit_offset) = reader.overflowed {
+            return Err(ParseError::Truncated(demo.data_manager.error_pos(bit_offset)));
         }
         
         packets.push(cur_packet);
     }
 }
 
+// goes back and reads the SvcPacketEntities messages that came before the datatables and stringtables
+// uses a copy of the reader so the position of the original one doesnt change
+fn read_skipped_entities(reader: &BitReader, packets: &mut [Packet], demo_data_mgr: &mut DataManager) {
+    let mut entity_reader = reader.clone();
+
+    for packet in packets.iter_mut() {
+        demo_data_mgr.cur_tick = packet.tick;
+        if let PacketDataType::Packet(data) = &mut packet.data {
+            for message in data.messages.iter_mut() {
+                if let NetSvcMessageDataTypes::SvcPacketEntities(msg_data) = &mut message.data {
+                    if msg_data.data.is_none() {
+                        entity_reader.current = msg_data.data_index;
+                        entity_reader.fetch();
+                        msg_data.data = Some(PacketEntitiesData::parse(&mut entity_reader, demo_data_mgr, msg_data.updated_entries, msg_data.is_delta));
+                        demo_data_mgr.apply_packet_entities(msg_data);
+                    }
+                }
+            }
+        }
+    }
+}
+
 // takes reader reference, the current packet type, a reference to the demos data manager and the current packets tick
 // returns packet data wrapped in PacketDataType enum;
-fn read_packet_data(reader: &mut BitReader, packet_type: PacketType, demo_data_mgr: &mut DataManager, cur_tick: i32) -> PacketDataType {
+fn read_packet_data(reader: &mut BitReader, packet_type: PacketType, demo_data_mgr: &mut DataManager, cur_tick: i32) -> Result<PacketDataType, ParseError> {
     let packet_data: PacketDataType;
 
     match packet_type {
@@ -58,21 +114,28 @@ fn read_packet_data(reader: &mut BitReader, packet_type: PacketType, demo_data_m
             let mut data = PP::new();
 
             data.cmd_info = CmdInfo::parse(reader);
+            if demo_data_mgr.demo_protocol == 4 {
+                data.split_screen_cmd_info = Some(CmdInfo::parse(reader));
+            }
                         
             data.in_sequence = reader.read_int(32);
             data.out_sequence = reader.read_int(32);
             data.size = reader.read_int(32); // in bytes!!!
 
+            if packet_type == PacketType::Packet {
+                try_adjust_for_position(&data.cmd_info.view_origin, cur_tick, demo_data_mgr);
+            }
+
             // some optimization
             // if we are paused and past the point of adjustment and not dumping we skip any data after the size variable to go faster
             // same thing for every other packet type
-            if !demo_data_mgr.dumping && demo_data_mgr.paused && demo_data_mgr.adj_end_tick != 0 {
-                reader.skip(data.size as i32 * 8);
+            if !demo_data_mgr.dumping && demo_data_mgr.paused && demo_data_mgr.adj_end_tick.is_some() {
+                reader.skip(data.size * 8);
             } else {
-                data.messages = parse(&mut reader.split_and_skip(data.size as i32 * 8), demo_data_mgr, data.size);
+                data.messages = parse(&mut reader.split_and_skip(data.size * 8), demo_data_mgr, data.size)?;
 
                 if data.messages.iter().find(|m| {m.msg_type == NetSvcMessageTypes::SvcFixAngle}).is_some() && cur_tick != 0 {
-                    try_adjust_for_wakeup(&data.messages, cur_tick, demo_data_mgr);
+                    try_adjust_for_fix_angle(&data.messages, cur_tick, demo_data_mgr);
                 }
                 if data.messages.iter().find(|m| {m.msg_type == NetSvcMessageTypes::SvcSetPause}).is_some() {
                     demo_data_mgr.paused = check_for_pause(&data.messages, demo_data_mgr)
@@ -85,53 +148,43 @@ fn read_packet_data(reader: &mut BitReader, packet_type: PacketType, demo_data_m
             let mut data = ConsoleCmd::new();
 
             data.size = reader.read_int(32);
-            data.data = reader.read_ascii_string((data.size * 8) as i32);
+            data.data = reader.read_ascii_string(data.size * 8);
 
-            try_adjust_for_glados_death(&data.data, cur_tick, demo_data_mgr);
+            try_adjust_for_console_command(&data.data, cur_tick, demo_data_mgr);
             
             packet_data = PacketDataType::ConsoleCmd(data);
         },
         PacketType::DataTables => {
-            let mut data = DataTables::new();
-            data.size = reader.read_int(32);
-            if demo_data_mgr.dumping {
-                let index_before_parsing = reader.current;
-            
-                while reader.read_bool() {
-                    let table = SendTable::parse(reader, demo_data_mgr);
-                    data.send_tables.push(table);
-                }
+            let size = reader.read_int(32);
+            let index_before_parsing = reader.current;
 
-                data.class_count = reader.read_int(16);
-                for _ in 0..data.class_count {
-                    let mut server_class = ServerClass::new();
-                    // if we didnt get an SvcClassInfo yet (?)
-                    if demo_data_mgr.server_class_info == Vec::new() {
-                        server_class.datatable_id = reader.read_int(16);
-                    } else {
-                        server_class.datatable_id = reader.read_int(log2_of_x_plus_one(demo_data_mgr.server_class_info.len() as i32));
-                    }
-                    server_class.class_name = reader.read_ascii_string_nulled();
-                    server_class.data_table_name = reader.read_ascii_string_nulled();
+            // always read even when not dumping, the server classes are the best hint for which game the demo is from
+            let mut data = read_datatables(reader, demo_data_mgr, size);
+            let prop_settings = (demo_data_mgr.send_prop_amount_of_bits_to_get_num_bits, demo_data_mgr.send_prop_type_list.clone());
+            demo_data_mgr.game_characteristics.server_classes = Some(data.server_classes.iter().map(|c| c.class_name.clone()).collect());
+            if !demo_data_mgr.detect_game() {
+                return Err(ParseError::UnsupportedGame(demo_data_mgr.error_pos(index_before_parsing)));
+            }
+            // the game we have now reads send props differently so everything we read is wrong
+            if prop_settings != (demo_data_mgr.send_prop_amount_of_bits_to_get_num_bits, demo_data_mgr.send_prop_type_list.clone()) {
+                reader.current = index_before_parsing;
+                reader.fetch();
+                data = read_datatables(reader, demo_data_mgr, size);
+            }
 
-                    data.server_classes.push(server_class);
-                }
+            if demo_data_mgr.dumping {
                 // untitledparser gets class info from svcclassinfo on some condition but ill get it here at all times
                 demo_data_mgr.server_class_info = data.server_classes.clone();
-                
-                data.send_table_count = data.send_tables.len() as i32;
 
                 // probably a bad idea to clone here but ¯\_(ツ)_/¯
                 let mut dt_mgr = DataTablesManager::new(data.clone(), log2_of_x_plus_one(demo_data_mgr.server_class_info.len() as i32));
-                dt_mgr.flatten_classes(&demo_data_mgr);
+                dt_mgr.flatten_classes(demo_data_mgr);
                 demo_data_mgr.dt_mgr = dt_mgr;
-
-                reader.current = index_before_parsing + (data.size * 8) as usize;
-                reader.fetch();
-            } else {
-                reader.skip(data.size * 8);
             }
 
+            reader.current = index_before_parsing + (size * 8) as usize;
+            reader.fetch();
+
             packet_data = PacketDataType::DataTables(data);
         },
         PacketType::Stop => {
@@ -146,17 +199,36 @@ fn read_packet_data(reader: &mut BitReader, packet_type: PacketType, demo_data_m
                 data.table_count = reader.read_int(8);
 
                 for _ in 0..data.table_count {
-                    data.tables.push(StringTable::parse(reader));
+                    let mut table = StringTable::parse(reader);
+                    // this packet doesnt have the info needed to read updates to the table so we keep the one from SvcCreateStringTable
+                    if let Some(created_table) = demo_data_mgr.get_stringtable(&table.name) {
+                        table.max_entries = created_table.max_entries;
+                        table.user_data_fixed_size = created_table.user_data_fixed_size;
+                        table.user_data_size = created_table.user_data_size;
+                        table.user_data_size_bits = created_table.user_data_size_bits;
+                    }
+                    data.tables.push(table);
                 }
                 
                 reader.current = index_before_parsing + (data.size * 8) as usize;
                 reader.fetch();
+
+                demo_data_mgr.stringtables = data.clone().tables;
+                demo_data_mgr.update_instance_baselines();
             } else {
                 reader.skip(data.size * 8);
             }
-            demo_data_mgr.stringtables = data.clone().tables;
             packet_data = PacketDataType::StringTables(data);
         },
+        PacketType::CustomData => {
+            let mut data = CustomData::new();
+
+            data.callback_index = reader.read_int(32);
+            data.size = reader.read_int(32);
+            data.data = reader.read_bytes(data.size);
+
+            packet_data = PacketDataType::CustomData(data);
+        },
         PacketType::SyncTick => {
             packet_data = PacketDataType::SyncTick(SyncTick); // synctick also contains no data
         },
@@ -169,7 +241,7 @@ fn read_packet_data(reader: &mut BitReader, packet_type: PacketType, demo_data_m
             data.cmd = reader.read_int(32);
             data.size = reader.read_int(32);
             if demo_data_mgr.dumping {
-                data.data = UserCmdInfo::parse(&mut reader.split_and_skip((data.size * 8) as i32));
+                data.data = UserCmdInfo::parse(&mut reader.split_and_skip(data.size * 8));
             } else {
                 reader.skip(data.size * 8);
             }
@@ -178,5 +250,34 @@ fn read_packet_data(reader: &mut BitReader, packet_type: PacketType, demo_data_m
         }
     }
 
-    return packet_data;
+    return Ok(packet_data);
+}
+
+// reads the send tables and server classes of a datatables packet
+fn read_datatables(reader: &mut BitReader, demo_data_mgr: &DataManager, size: i32) -> DataTables {
+    let mut data = DataTables::new();
+    data.size = size;
+
+    while reader.read_bool() {
+        let table = SendTable::parse(reader, demo_data_mgr);
+        data.send_tables.push(table);
+    }
+
+    data.class_count = reader.read_int(16);
+    for _ in 0..data.class_count {
+        let mut server_class = ServerClass::new();
+        // if we didnt get an SvcClassInfo yet (?)
+        if demo_data_mgr.server_class_info == Vec::new() {
+            server_class.datatable_id = reader.read_int(16);
+        } else {
+            server_class.datatable_id = reader.read_int(log2_of_x_plus_one(demo_data_mgr.server_class_info.len() as i32));
+        }
+        server_class.class_name = reader.read_ascii_string_nulled();
+        server_class.data_table_name = reader.read_ascii_string_nulled();
+
+        data.server_classes.push(server_class);
+    }
+    data.send_table_count = data.send_tables.len() as i32;
+
+    return data;
 }
@@ -1,5 +1,6 @@
 use crate::bitreader::BitReader;
 use crate::structs::utils::Vec3;
+use crate::json::{json_object, json_flags};
 
 // cmdinfo is present in every Packet packet and contains information about
 // where the player is looking and what their position is
@@ -54,4 +55,8 @@ bitflags::bitflags! {
         const UseAngles2 = 1 << 1;
         const NoInterp = 1 << 2; // don't interpolate between this and last view
     }
-}
\ No newline at end of file
+}
+
+// json export, see json.rs
+json_object!(CmdInfo { flags, view_origin, view_angles, local_view_angles, view_origin2, view_angles2, local_view_angles2 });
+json_flags!(InterpFlags);
@@ -1,24 +1,46 @@
+use std::collections::HashMap;
 use crate::structs::utils::{GameEventList, ServerClass};
 use crate::structs::demo_header::DemoHeader;
 use crate::structs::user_message::UserMessageType;
 use crate::structs::send_table::SendPropType;
-use crate::structs::stringtable::StringTable;
+use crate::structs::net_svc_message::NetSvcMessageTypes;
+use crate::structs::stringtable::{StringTable, StringTableEntryDataTypes};
+use crate::structs::entities::{EntityProp, EntityPropValue, read_instance_baseline};
+use crate::structs::entity_manager::EntityManager;
+use crate::structs::netsvc_types::SvcPacketEntities;
 use crate::structs::datatables_manager::DataTablesManager;
 use crate::structs::packet_data_types::DataTables;
+use crate::error::ErrorPos;
+use crate::adjust_time::TimingRules;
+use crate::game_profile::{GameProfile, GameProfiles, GameCharacteristics};
 
 // will be used more later in development
 // for now this stores information vital for parsing the demo
 // all of it is inferred from the demo header
 // later all the datatable and stringtable stuff will be stored here so that its data can be fetched by other messages
 
+// 66.67 ticks per second, what every portal demo should have
+pub const DEFAULT_TICK_INTERVAL: f32 = 0.015;
+
+// where the tick interval came from, from worst to best
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TickIntervalSource {
+    Default,
+    Header, // playback_time / playback_ticks, rounded and missing if the game crashed
+    ServerInfo, // the servers actual tick interval from SvcServerInfo
+}
+
+// which version of the engine the demo is from, for the small differences in how some messages are read
+// the hl2 games use the portal 1 ones since theyre on the same engine versions, the name of the actual game is in game_name
 #[allow(non_camel_case_types)]
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Game {
     PORTAL_1_3420,
     PORTAL_1_5135,
     PORTAL_1_1910503, // steampipe
+    PORTAL_2,
 
-    UNKNOWN
+    UNKNOWN // none of the game profiles fit, parsing stops with an unsupported game error
 }
 
 
@@ -29,16 +51,33 @@ pub struct DataManager {
     pub game_event_list: GameEventList,
     pub user_message_list: Vec<UserMessageType>,
     pub game: Game,
+    pub game_name: String, // name of the game profile, "Unknown" if there wasnt one for the demo
+    pub game_profiles: GameProfiles, // to pick the game again when we know more about it
+    pub game_characteristics: GameCharacteristics,
     pub net_svc_type_bits: i32,
-    pub adj_start_tick: i32,
-    pub adj_end_tick: i32,
+    pub net_svc_message_list: Vec<NetSvcMessageTypes>, // message id -> type
+    pub timing_rules: TimingRules,
+    pub map_name: String, // for rules that only work on one map
+    pub adj_start_tick: Option<i32>,
+    pub adj_end_tick: Option<i32>,
+    pub adj_start_rule: Option<String>, // name of the rule that set the tick above
+    pub adj_end_rule: Option<String>,
+    pub inside_box: Vec<bool>, // rule index -> was the player in the box of that position rule last packet
     pub stringtables: Vec<StringTable>,
     pub server_class_info: Vec<ServerClass>,
     pub send_prop_amount_of_bits_to_get_num_bits: i32, // hehehe
     pub send_prop_type_list: Vec<SendPropType>,
+    pub sound_index_bits: i32,
+    pub sound_flag_bits: i32,
     pub paused: bool,
     pub dumping: bool,
     pub dt_mgr: DataTablesManager,
+    pub tick_interval: f32, // seconds per tick
+    pub tick_interval_source: TickIntervalSource,
+    pub cur_tick: i32, // tick of the packet thats being parsed right now
+    pub cur_packet_index: usize, // and its index, both are only used for errors
+    pub entity_mgr: EntityManager,
+    pub instance_baselines: HashMap<i32, Vec<EntityProp>>, // class id -> props that every entity of that class starts with
 }
 
 impl DataManager {
@@ -50,190 +89,163 @@ impl DataManager {
             last_packet_tick: 0,
             user_message_list: Vec::new(),
             game: Game::UNKNOWN,
+            game_name: "Unknown".to_string(),
+            game_profiles: GameProfiles { profiles: Vec::new() },
+            game_characteristics: GameCharacteristics::from_header(&DemoHeader::new()),
             net_svc_type_bits: 6, // default for everything other than 3420 iirc
-            adj_start_tick: 0,
-            adj_end_tick: 0,
+            net_svc_message_list: NetSvcMessageTypes::table(3),
+            timing_rules: TimingRules::portal(),
+            map_name: String::new(),
+            adj_start_tick: None,
+            adj_end_tick: None,
+            adj_start_rule: None,
+            adj_end_rule: None,
+            inside_box: Vec::new(),
             stringtables: Vec::new(),
             server_class_info: Vec::new(),
             send_prop_amount_of_bits_to_get_num_bits: 0,
             send_prop_type_list: Vec::new(),
+            sound_index_bits: 13,
+            sound_flag_bits: 9,
             paused: false,
             dumping: false,
-            dt_mgr: DataTablesManager::new(DataTables::new(), 0)
+            dt_mgr: DataTablesManager::new(DataTables::new(), 0),
+            tick_interval: DEFAULT_TICK_INTERVAL,
+            tick_interval_source: TickIntervalSource::Default,
+            cur_tick: 0,
+            cur_packet_index: 0,
+            entity_mgr: EntityManager::new(),
+            instance_baselines: HashMap::new(),
+        }
+    }
+
+    // reads the instancebaseline stringtable using the flattened classes
+    // has to be called every time the stringtables change
+    pub fn update_instance_baselines(&mut self) {
+        if self.dt_mgr.prop_lookup.is_empty() {
+            return;
+        }
+
+        let table = match self.stringtables.iter().find(|t| t.name == "instancebaseline") {
+            Some(table) => table,
+            None => return,
+        };
+
+        for entry in &table.table_entries {
+            if let StringTableEntryDataTypes::InstanceBaseline(baseline) = &entry.entry_data {
+                let class_id = match entry.name.parse::<i32>() {
+                    Ok(class_id) => class_id,
+                    Err(_) => continue,
+                };
+
+                if let Some((_, f_props)) = self.dt_mgr.prop_lookup.get(class_id as usize) {
+                    if let Some(props) = read_instance_baseline(&baseline.data, f_props, self.demo_protocol) {
+                        self.instance_baselines.insert(class_id, props);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn get_stringtable(&self, name: &str) -> Option<&StringTable> {
+        return self.stringtables.iter().find(|t| t.name == name);
+    }
+
+    pub fn get_instance_baseline(&self, class_id: i32) -> Option<&Vec<EntityProp>> {
+        return self.instance_baselines.get(&class_id);
+    }
+
+    // where we are right now for parse errors
+    pub fn error_pos(&self, bit_offset: usize) -> ErrorPos {
+        return ErrorPos { bit_offset: bit_offset, packet_index: self.cur_packet_index, tick: self.cur_tick };
+    }
+
+    // applies the entity data of the message to the entity table
+    pub fn apply_packet_entities(&mut self, msg: &SvcPacketEntities) {
+        self.entity_mgr.apply(msg, self.cur_tick, &self.instance_baselines, &self.dt_mgr.prop_lookup);
+    }
+
+    // current value of a prop of an entity
+    pub fn get_entity_prop(&self, ent_index: i32, prop_name: &str) -> Option<&EntityPropValue> {
+        let entity = self.entity_mgr.get_entity(ent_index)?;
+        let prop_index = self.dt_mgr.get_prop_index(entity.class_id, prop_name)?;
+        return entity.props[prop_index].as_ref();
+    }
+
+    // value a prop of an entity had on the given tick, works after the demo is parsed
+    pub fn get_entity_prop_at_tick(&self, ent_index: i32, prop_name: &str, tick: i32) -> Option<&EntityPropValue> {
+        let lifetime = self.entity_mgr.get_lifetime_at_tick(ent_index, tick)?;
+        let prop_index = self.dt_mgr.get_prop_index(lifetime.class_id, prop_name)?;
+        return lifetime.get_prop_at_tick(prop_index, tick);
+    }
+
+    // garbage from a broken header or message doesnt replace what we have
+    pub fn set_tick_interval(&mut self, tick_interval: f32, source: TickIntervalSource) {
+        if tick_interval.is_finite() && tick_interval > 0.0 {
+            self.tick_interval = tick_interval;
+            self.tick_interval_source = source;
         }
     }
 
     // all of the info i need (for now) can be inferred from the demo header
-    pub fn get_info_from_header(&mut self, header: &DemoHeader) {
+    // and the game profile that matches it, see game_profile.rs
+    pub fn get_info_from_header(&mut self, header: &DemoHeader, profiles: &GameProfiles) {
         self.demo_protocol = header.demo_protocol;
         self.network_protocol = header.network_protocol;
-        match header.network_protocol {
-            14 => {
-                self.game = Game::PORTAL_1_3420;
-                self.net_svc_type_bits = 5;
-                self.user_message_list = vec![
-                    UserMessageType::Geiger,
-                    UserMessageType::Train,
-                    UserMessageType::HudText,
-                    UserMessageType::SayText,
-                    UserMessageType::SayText2,
-                    UserMessageType::TextMsg,
-                    UserMessageType::HudMsg,
-                    UserMessageType::ResetHUD,
-                    UserMessageType::GameTitle,
-                    UserMessageType::ItemPickup,
-                    UserMessageType::ShowMenu,
-                    UserMessageType::Shake,
-                    UserMessageType::Fade,
-                    UserMessageType::VguiMenu,
-                    UserMessageType::Rumble,
-                    UserMessageType::Battery,
-                    UserMessageType::Damage,
-                    UserMessageType::VoiceMask,
-                    UserMessageType::RequestState,
-                    UserMessageType::CloseCaption,
-                    UserMessageType::HintText,
-                    UserMessageType::KeyHintText,
-                    UserMessageType::SquadMemberDied,
-                    UserMessageType::AmmoDenied,
-                    UserMessageType::CreditsMsg,
-                    UserMessageType::CreditsPortalMsg,
-                    UserMessageType::LogoTimeMsg,
-                    UserMessageType::AchievementEvent,
-                    UserMessageType::EntityPortalled,
-                    UserMessageType::KillCam
-                ];
-                self.send_prop_amount_of_bits_to_get_num_bits = 6;
-                self.send_prop_type_list = Vec::new();
-                self.send_prop_type_list.push(SendPropType::Int);
-                self.send_prop_type_list.push(SendPropType::Float);
-                self.send_prop_type_list.push(SendPropType::Vector3);
-                self.send_prop_type_list.push(SendPropType::String);
-                self.send_prop_type_list.push(SendPropType::Array);
-                self.send_prop_type_list.push(SendPropType::DataTable);
-            },
-            15 => {
-                self.game = Game::PORTAL_1_5135;
-                self.user_message_list = vec![
-                    UserMessageType::Geiger,
-                    UserMessageType::Train,
-                    UserMessageType::HudText,
-                    UserMessageType::SayText,
-                    UserMessageType::SayText2,
-                    UserMessageType::TextMsg,
-                    UserMessageType::HudMsg,
-                    UserMessageType::ResetHUD,
-                    UserMessageType::GameTitle,
-                    UserMessageType::ItemPickup,
-                    UserMessageType::ShowMenu,
-                    UserMessageType::Shake,
-                    UserMessageType::Fade,
-                    UserMessageType::VguiMenu,
-                    UserMessageType::Rumble,
-                    UserMessageType::Battery,
-                    UserMessageType::Damage,
-                    UserMessageType::VoiceMask,
-                    UserMessageType::RequestState,
-                    UserMessageType::CloseCaption,
-                    UserMessageType::HintText,
-                    UserMessageType::KeyHintText,
-                    UserMessageType::SquadMemberDied,
-                    UserMessageType::AmmoDenied,
-                    UserMessageType::CreditsMsg,
-                    UserMessageType::CreditsPortalMsg,
-                    UserMessageType::LogoTimeMsg,
-                    UserMessageType::AchievementEvent,
-                    UserMessageType::EntityPortalled,
-                    UserMessageType::KillCam,
-                    UserMessageType::SPHapWeaponEvent,
-                    UserMessageType::HapDmg,
-                    UserMessageType::HapPunch,
-                    UserMessageType::HapSetDrag,
-                    UserMessageType::HapSetConstForce,
-                    UserMessageType::HapMeleeContact
-                ];
-                self.send_prop_amount_of_bits_to_get_num_bits = 7;
-                self.send_prop_type_list = Vec::new();
-                self.send_prop_type_list.push(SendPropType::Int);
-                self.send_prop_type_list.push(SendPropType::Float);
-                self.send_prop_type_list.push(SendPropType::Vector3);
-                self.send_prop_type_list.push(SendPropType::Vector2);
-                self.send_prop_type_list.push(SendPropType::String);
-                self.send_prop_type_list.push(SendPropType::Array);
-                self.send_prop_type_list.push(SendPropType::DataTable);
-            },
-            24 => {
-                self.game = Game::PORTAL_1_1910503;
-                self.user_message_list = vec![
-                    UserMessageType::Geiger,
-                    UserMessageType::Train,
-                    UserMessageType::HudText,
-                    UserMessageType::SayText,
-                    UserMessageType::SayText2,
-                    UserMessageType::TextMsg,
-                    UserMessageType::HudMsg,
-                    UserMessageType::ResetHUD,
-                    UserMessageType::GameTitle,
-                    UserMessageType::ItemPickup,
-                    UserMessageType::ShowMenu,
-                    UserMessageType::Shake,
-                    UserMessageType::Fade,
-                    UserMessageType::VguiMenu,
-                    UserMessageType::Rumble,
-                    UserMessageType::Battery,
-                    UserMessageType::Damage,
-                    UserMessageType::VoiceMask,
-                    UserMessageType::RequestState,
-                    UserMessageType::CloseCaption,
-                    UserMessageType::HintText,
-                    UserMessageType::KeyHintText,
-                    UserMessageType::SquadMemberDied,
-                    UserMessageType::AmmoDenied,
-                    UserMessageType::CreditsMsg,
-                    UserMessageType::CreditsPortalMsg,
-                    UserMessageType::LogoTimeMsg,
-                    UserMessageType::AchievementEvent,
-                    UserMessageType::EntityPortalled,
-                    UserMessageType::KillCam,
-                    UserMessageType::CallVoteFailed,
-                    UserMessageType::VoteStart,
-                    UserMessageType::VotePass,
-                    UserMessageType::VoteFailed,
-                    UserMessageType::VoteSetup,
-                    UserMessageType::SPHapWeaponEvent,
-                    UserMessageType::HapDmg,
-                    UserMessageType::HapPunch,
-                    UserMessageType::HapSetDrag,
-                    UserMessageType::HapSetConstForce,
-                    UserMessageType::HapMeleeContact
-                ];
-                self.send_prop_amount_of_bits_to_get_num_bits = 7;
-                self.send_prop_type_list = Vec::new();
-                self.send_prop_type_list.push(SendPropType::Int);
-                self.send_prop_type_list.push(SendPropType::Float);
-                self.send_prop_type_list.push(SendPropType::Vector3);
-                self.send_prop_type_list.push(SendPropType::Vector2);
-                self.send_prop_type_list.push(SendPropType::String);
-                self.send_prop_type_list.push(SendPropType::Array);
-                self.send_prop_type_list.push(SendPropType::DataTable);
-            },
-            _ => self.game = Game::UNKNOWN
+        self.map_name = header.map_name.clone();
+        // only an estimate until we get to SvcServerInfo
+        if header.playback_ticks > 0 {
+            self.set_tick_interval(header.playback_time / header.playback_ticks as f32, TickIntervalSource::Header);
+        }
+        self.game_profiles = profiles.clone();
+        self.game_characteristics = GameCharacteristics::from_header(header);
+        self.detect_game();
+    }
+
+    // picks the game profile again with everything in game_characteristics
+    // false and Game::UNKNOWN if none of them fit
+    pub fn detect_game(&mut self) -> bool {
+        let Some(profile) = self.game_profiles.detect(&self.game_characteristics).cloned() else {
+            self.game = Game::UNKNOWN;
+            self.game_name = "Unknown".to_string();
+            return false;
         };
+        if profile.name != self.game_name {
+            self.apply_game_profile(&profile);
+        }
+        return true;
+    }
+
+    // the demo has a user message the game profile doesnt, so its probably from another game
+    pub fn has_unknown_user_messages(&self) -> bool {
+        return self.game_characteristics.highest_user_message.is_some_and(|id| id as usize >= self.user_message_list.len());
+    }
+
+    pub fn apply_game_profile(&mut self, profile: &GameProfile) {
+        self.game = profile.game;
+        self.game_name = profile.name.clone();
+        self.net_svc_type_bits = profile.net_svc_type_bits;
+        self.net_svc_message_list = profile.net_svc_messages.clone();
+        self.user_message_list = profile.user_messages.clone();
+        self.send_prop_amount_of_bits_to_get_num_bits = profile.send_prop_num_bits_bits;
+        self.send_prop_type_list = profile.send_prop_types.clone();
+        self.sound_index_bits = profile.sound_index_bits;
+        self.sound_flag_bits = profile.sound_flag_bits;
     }
 
     // add one because 0th tick (?)
     pub fn get_measured_ticks_and_time(&self) -> (i32, f32) {
-        return (self.last_packet_tick + 1, ((self.last_packet_tick as f32) + 1f32) * 0.015)
+        return (self.last_packet_tick + 1, ((self.last_packet_tick as f32) + 1f32) * self.tick_interval)
     }
 
     // this has a bunch of "+ 1" because yes
     pub fn get_adjusted_ticks_and_time(&self) -> (i32, f32) {
-        if self.adj_end_tick == 0 && self.adj_start_tick != 0 {
-            return (self.last_packet_tick - self.adj_start_tick + 1, ((self.last_packet_tick - self.adj_start_tick + 1) as f32 * 0.015));
-        } else if self.adj_end_tick != 0 && self.adj_start_tick == 0 {
-            return (self.adj_end_tick + 1, ((self.adj_end_tick + 1) as f32 * 0.015));
-        } else {
-            return self.get_measured_ticks_and_time();
-        }
+        let ticks = match (self.adj_start_tick, self.adj_end_tick) {
+            (Some(start), None) => self.last_packet_tick - start + 1,
+            (None, Some(end)) => end + 1,
+            (Some(start), Some(end)) => end - start + 1,
+            (None, None) => return self.get_measured_ticks_and_time(),
+        };
+        return (ticks, ticks as f32 * self.tick_interval);
     }
 }
\ No newline at end of file
@@ -29,24 +29,47 @@ impl DataTablesManager {
         // cloning this so it lives for the entire scope of this function
         let send_tables_clone = self.datatables.send_tables.clone(); // i hate this but it has to be done
         for table in &send_tables_clone {
-            table_lookup.insert(&table.name, &table);
+            table_lookup.insert(&table.name, table);
         }
     
         for server_class in &data_mgr.server_class_info {
-            let table: &SendTable = &send_tables_clone[server_class.datatable_id as usize];
-            let exc = self.gather_excludes(&table_lookup, &table);
-            self.gather_props(&table_lookup, &exc, &table, server_class);
+            // the class id and the send table index dont always line up so look it up by name
+            let Some(table) = table_lookup.get(server_class.data_table_name.as_str()).copied() else {
+                // a broken demo can name a table it never sent, the class just ends up with no props
+                if server_class.datatable_id == self.prop_lookup.len() as i32 {
+                    self.prop_lookup.push((server_class.clone(), Vec::new()));
+                }
+                continue;
+            };
+            let exc = self.gather_excludes(&table_lookup, table);
+            self.gather_props(&table_lookup, &exc, table, server_class);
             let mut f_props = self.prop_lookup[server_class.datatable_id as usize].1.clone();
-            self.sort_props(&mut f_props);
+            self.sort_props(&mut f_props, data_mgr.demo_protocol);
+            self.prop_lookup[server_class.datatable_id as usize].1 = f_props;
         }
     }
     
+    // index of a prop in the flattened props of a class, this is what entity props are indexed by
+    pub fn get_prop_index(&self, class_id: i32, prop_name: &str) -> Option<usize> {
+        return self.prop_lookup.get(class_id as usize)?.1.iter().position(|p| p.name == prop_name);
+    }
+
+    // some classes have more than one prop with the same name, like the player with its local and non local tables
+    pub fn get_prop_indices(&self, class_id: i32, prop_name: &str) -> Vec<usize> {
+        let Some((_, f_props)) = self.prop_lookup.get(class_id as usize) else {
+            return Vec::new();
+        };
+        return f_props.iter().enumerate().filter(|(_, p)| p.name == prop_name).map(|(i, _)| i).collect();
+    }
+
     fn gather_excludes(&mut self, table_lookup: &HashMap<&str, &SendTable>, table: &SendTable) -> HashSet<(String, String)> {
         let mut excludes: HashSet<(String, String)> = HashSet::new();
     
         for prop in &table.prop_list {
             if prop.send_prop_type == SendPropType::DataTable {
-                excludes.extend(self.gather_excludes(table_lookup, &table_lookup[prop.exclude_dt_name.clone().unwrap().as_str()]));
+                if let Some(sub_table) = prop.exclude_dt_name.as_deref().and_then(|name| table_lookup.get(name)) {
+                    excludes.extend(self.gather_excludes(table_lookup, sub_table));
+                }
             } else if prop.flags.contains(PropFlag::Exclude) {
                 excludes.insert((prop.name.clone(), prop.exclude_dt_name.clone().unwrap()));
             }
@@ -62,7 +85,9 @@ impl DataTablesManager {
                 continue;
             }
             if prop.send_prop_type == SendPropType::DataTable {
-                let sub_table = table_lookup[prop.exclude_dt_name.clone().unwrap().as_str()];
+                let Some(sub_table) = prop.exclude_dt_name.as_deref().and_then(|name| table_lookup.get(name)).copied() else {
+                    continue;
+                };
                 if prop.flags.contains(PropFlag::Collapsible) {
                     self.iterate_props(table_lookup, sub_table, excludes, server_class, f_props);
                 } else {
@@ -89,14 +114,35 @@ impl DataTablesManager {
         self.prop_lookup[server_class.datatable_id as usize].1.append(&mut f_props);
     }
 
-    fn sort_props(&mut self, f_props: &mut Vec<FlattenedProp>) {
-        // theres some magic if the demo has demo protocol 4 buuuuut
-        // im not doing that yet so itll have to wait :)
+    fn sort_props(&mut self, f_props: &mut [FlattenedProp], demo_protocol: i32) {
+        if demo_protocol != 4 {
+            // the old engine only moves the props that change often to the front
+            let mut start = 0;
+            for i in 0..f_props.len() {
+                if f_props[i].prop_info.flags.contains(PropFlag::ChangesOften) {
+                    f_props.swap(i, start);
+                    start += 1;
+                }
+            }
+            return;
+        }
+
+        // the new engine goes through every priority from low to high and moves the props with it to the front,
+        // props that change often count as priority 64. same swapping as the engine so the indices line up
+        let mut priorities: Vec<i32> = f_props.iter().filter_map(|p| p.prop_info.priority).collect();
+        priorities.push(64);
+        priorities.sort();
+        priorities.dedup();
+
         let mut start = 0;
-        for i in 0..f_props.len() {
-            if f_props[i].prop_info.flags.contains(PropFlag::ChangesOften) {
-                f_props.swap(i, start);
-                start += 1;
+        for priority in priorities {
+            let first = start; // start moves during the pass, the pass still goes from where it began to the end
+            for i in first..f_props.len() {
+                let prop = &f_props[i].prop_info;
+                if prop.priority == Some(priority) || (priority == 64 && prop.flags.contains(PropFlag::ChangesOften)) {
+                    f_props.swap(i, start);
+                    start += 1;
+                }
             }
         }
     }
@@ -1,6 +1,8 @@
 use crate::structs::demo_header::DemoHeader;
 use crate::structs::packet::Packet;
-use crate::structs::data_manager::DataManager;
+use crate::structs::data_manager::{DataManager, DEFAULT_TICK_INTERVAL};
+use crate::error::ParseError;
+use crate::json::{JsonValue, ToJson};
 
 // struct to store all information on the current demo
 
@@ -8,10 +10,43 @@ pub struct Demo {
     pub header: DemoHeader,
     pub packets: Vec<Packet>,
     pub data_manager: DataManager,
+    pub parse_error: Option<ParseError>, // only set when parsing with best_effort, packets has everything before it
 }
 
 impl Demo {
     pub fn new() -> Self {
-        Self { header: DemoHeader::new(), packets: Vec::new(), data_manager: DataManager::new() }
+        Self { header: DemoHeader::new(), packets: Vec::new(), data_manager: DataManager::new(), parse_error: None }
+    }
+
+    // seconds per tick, see DataManager::tick_interval_source for where it came from
+    pub fn tick_interval(&self) -> f32 {
+        return self.data_manager.tick_interval;
+    }
+
+    pub fn tick_rate(&self) -> f32 {
+        return 1.0 / self.data_manager.tick_interval;
+    }
+
+    // false if the demo wasnt recorded at 66.67 ticks per second, which means it probably isnt from a normal game
+    pub fn has_expected_tick_rate(&self) -> bool {
+        return (self.tick_rate() - 1.0 / DEFAULT_TICK_INTERVAL).abs() < 0.05;
+    }
+
+    // false if parsing stopped before the stop packet
+    pub fn is_complete(&self) -> bool {
+        return self.parse_error.is_none();
+    }
+}
+
+// the whole demo as json, data_manager isnt in here since its just state for parsing
+impl ToJson for Demo {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::object(vec![
+            ("header", self.header.to_json()),
+            ("tick_interval", self.tick_interval().to_json()),
+            ("complete", self.is_complete().to_json()),
+            ("parse_error", self.parse_error.to_json()),
+            ("packets", self.packets.to_json()),
+        ]);
     }
 }
@@ -1,4 +1,5 @@
 use crate::bitreader::BitReader;
+use crate::json::{json_object};
 
 // the first 1072 bytes of the demo contain the "header" which
 // contatins all of the important info about the demo
@@ -52,3 +53,6 @@ impl DemoHeader {
         }
     }
 }
+
+// json export, see json.rs
+json_object!(DemoHeader { demo_file_stamp, demo_protocol, network_protocol, server_name, client_name, map_name, game_directory, playback_time, playback_ticks, playback_frames, sign_on_length });
@@ -0,0 +1,430 @@
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use crate::bitreader::BitReader;
+use crate::structs::data_manager::DataManager;
+use crate::structs::datatables_manager::FlattenedProp;
+use crate::structs::send_table::{SendTableProp, SendPropType, PropFlag, FloatParseType};
+use crate::structs::utils::{Vec2, Vec3, log2_of_x_plus_one};
+use crate::json::{JsonValue, ToJson, json_object, json_debug_string};
+
+// everything entity related
+// you read the props of an entity by going through the flattened props of its class (see datatables_manager.rs)
+// the message only tells you the indices of the props that changed and their new values
+
+// https://github.com/UncraftedName/UntitledParser/blob/master/DemoParser/src/Parser/Components/Messages/SvcPacketEntities.cs
+// https://github.com/UncraftedName/UntitledParser/blob/master/DemoParser/src/Parser/Components/Abstract/EntityProperties.cs
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntityPropValue {
+    Int(i32),
+    Float(f32),
+    Vector3(Vec3),
+    Vector2(Vec2),
+    String(String),
+    Array(Vec<EntityPropValue>),
+}
+
+impl fmt::Display for EntityPropValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Int(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{}", value),
+            Self::Vector3(value) => write!(f, "{}", value.to_string().trim_end()),
+            Self::Vector2(value) => write!(f, "{}", value.to_string().trim_end()),
+            Self::String(value) => write!(f, "\"{}\"", value),
+            Self::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            },
+        }
+    }
+}
+
+// a single prop that was sent, index points into the flattened props of the entitys class
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityProp {
+    pub index: usize,
+    pub value: EntityPropValue,
+}
+
+fn read_int_prop(reader: &mut BitReader, prop: &SendTableProp) -> Option<i32> {
+    let bits = prop.num_bits?;
+    // the bit count comes from the datatables, anything outside of this would make the bit reader shift too far
+    if !(1..=32).contains(&bits) {
+        return None;
+    }
+    if prop.flags.contains(PropFlag::Unsigned) {
+        return Some(reader.read_bits(bits) as u32 as i32);
+    } else {
+        return Some(reader.read_signed_int(bits));
+    }
+}
+
+fn read_float_prop(reader: &mut BitReader, prop: &SendTableProp) -> Option<f32> {
+    match prop.float_parse_type {
+        FloatParseType::Standard => {
+            let bits = prop.num_bits?;
+            let low = prop.low_value?;
+            let high = prop.high_value?;
+            if !(1..=32).contains(&bits) {
+                return None;
+            }
+            let value = reader.read_bits(bits);
+            return Some(low + (high - low) * (value as f32 / ((1u64 << bits) - 1) as f32));
+        },
+        FloatParseType::Coord => Some(reader.read_vector_coord()),
+        FloatParseType::BitCoordMp => Some(reader.read_vector_coord_mp(false, false)),
+        FloatParseType::BitCoordMpLp => Some(reader.read_vector_coord_mp(false, true)),
+        FloatParseType::BitCoordMpInt => Some(reader.read_vector_coord_mp(true, false)),
+        FloatParseType::NoScale => Some(f32::from_bits(reader.read_bits(32) as u32)),
+        FloatParseType::Normal => Some(reader.read_bit_normal()),
+        FloatParseType::BitCellChord => Some(reader.read_bit_cell_coord(prop.num_bits?, false, false)),
+        FloatParseType::BitCellChordLp => Some(reader.read_bit_cell_coord(prop.num_bits?, false, true)),
+        FloatParseType::BitCellChordInt => Some(reader.read_bit_cell_coord(prop.num_bits?, true, false)),
+        FloatParseType::None => None,
+    }
+}
+
+fn read_vec3_prop(reader: &mut BitReader, prop: &SendTableProp) -> Option<Vec3> {
+    let x = read_float_prop(reader, prop)?;
+    let y = read_float_prop(reader, prop)?;
+    let z = if prop.flags.contains(PropFlag::Normal) {
+        // only the sign of z is sent, the rest is figured out from x and y
+        let sign = reader.read_bool();
+        let squared = x * x + y * y;
+        let abs_z = if squared < 1f32 { (1f32 - squared).sqrt() } else { 0f32 };
+        if sign { -abs_z } else { abs_z }
+    } else {
+        read_float_prop(reader, prop)?
+    };
+
+    return Some(Vec3 { x: x, y: y, z: z });
+}
+
+// reads a single value of the given prop
+// None if we dont know how to read it, after that the rest of the data cant be read either
+pub fn read_prop_value(reader: &mut BitReader, prop: &SendTableProp, array_element: Option<&SendTableProp>) -> Option<EntityPropValue> {
+    let value = match prop.send_prop_type {
+        SendPropType::Int => EntityPropValue::Int(read_int_prop(reader, prop)?),
+        SendPropType::Float => EntityPropValue::Float(read_float_prop(reader, prop)?),
+        SendPropType::Vector3 => EntityPropValue::Vector3(read_vec3_prop(reader, prop)?),
+        SendPropType::Vector2 => {
+            let x = read_float_prop(reader, prop)?;
+            let y = read_float_prop(reader, prop)?;
+            EntityPropValue::Vector2(Vec2 { x: x, y: y })
+        },
+        SendPropType::String => {
+            let length = reader.read_int(9); // in bytes
+            EntityPropValue::String(reader.read_ascii_string(length * 8))
+        },
+        SendPropType::Array => {
+            let element = array_element?;
+            let count = reader.read_int(log2_of_x_plus_one(prop.num_elements?));
+            let mut values: Vec<EntityPropValue> = Vec::new();
+            for _ in 0..count {
+                values.push(read_prop_value(reader, element, None)?);
+            }
+            EntityPropValue::Array(values)
+        },
+        // flattened props never contain datatables
+        SendPropType::DataTable => return None,
+    };
+
+    return Some(value);
+}
+
+// demo protocol 4 sends the prop indices differently, the first bit says if small steps between indices get their own shorter encoding
+// returns None at the end of the list
+fn read_field_index(reader: &mut BitReader, last_index: i32, new_way: bool) -> Option<i32> {
+    if new_way && reader.read_bool() {
+        return Some(last_index + 1);
+    }
+
+    let mut step: i32;
+    if new_way && reader.read_bool() {
+        step = reader.read_int(3);
+    } else {
+        step = reader.read_int(7);
+        // bits 5 and 6 say how many more bits there are
+        let extra_bits = match step & 96 {
+            32 => 2,
+            64 => 4,
+            96 => 7,
+            _ => 0,
+        };
+        if extra_bits != 0 {
+            step = (step & !96) | (reader.read_int(extra_bits) << 5);
+        }
+    }
+
+    if step == 0xFFF {
+        return None;
+    }
+    return Some(last_index.saturating_add(step).saturating_add(1));
+}
+
+// reads the list of changed props for an entity of a class with the given flattened props
+// returns None if the data doesnt make sense (prop index out of range or a prop we cant read)
+pub fn read_entity_props(reader: &mut BitReader, f_props: &[FlattenedProp], demo_protocol: i32) -> Option<Vec<EntityProp>> {
+    let mut props: Vec<EntityProp> = Vec::new();
+    let mut index: i32 = -1;
+    let new_way = demo_protocol == 4 && reader.read_bool();
+
+    loop {
+        if demo_protocol == 4 {
+            match read_field_index(reader, index, new_way) {
+                Some(next) => index = next,
+                None => break,
+            }
+        } else if reader.read_bool() {
+            index = index.saturating_add(reader.read_ubit_var()).saturating_add(1);
+        } else {
+            break;
+        }
+        if index < 0 || index as usize >= f_props.len() {
+            return None;
+        }
+
+        let f_prop = &f_props[index as usize];
+        let value = read_prop_value(reader, &f_prop.prop_info, f_prop.array_element_prop_info.as_ref())?;
+        props.push(EntityProp { index: index as usize, value: value });
+    }
+
+    return Some(props);
+}
+
+// instance baselines are just a list of props on their own
+// returns None if the data doesnt make sense
+pub fn read_instance_baseline(data: &[u8], f_props: &[FlattenedProp], demo_protocol: i32) -> Option<Vec<EntityProp>> {
+    let mut reader = BitReader::new(data.to_vec());
+    let props = read_entity_props(&mut reader, f_props, demo_protocol)?;
+    if reader.overflowed.is_some() {
+        return None;
+    }
+    return Some(props);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntityUpdateType {
+    EnterPvs,
+    LeavePvs,
+    Delete,
+    Delta,
+}
+
+impl From<EntityUpdateType> for &str {
+    fn from(value: EntityUpdateType) -> Self {
+        match value {
+            EntityUpdateType::EnterPvs => "EnterPVS",
+            EntityUpdateType::LeavePvs => "LeavePVS",
+            EntityUpdateType::Delete => "Delete",
+            EntityUpdateType::Delta => "Delta",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EntityUpdate {
+    pub update_type: EntityUpdateType,
+    pub ent_index: i32,
+    pub class_id: Option<i32>, // None if we dont know what the entity is
+    pub serial: Option<i32>, // only sent on EnterPvs
+    pub props: Vec<EntityProp>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PacketEntitiesData {
+    pub updates: Vec<EntityUpdate>,
+    pub deletions: Vec<i32>, // explicit deletions that come after the updates in delta packets
+    pub complete: bool, // false if we had to stop reading somewhere (missing class info etc.)
+}
+
+impl PacketEntitiesData {
+    pub fn new() -> Self {
+        Self { updates: Vec::new(), deletions: Vec::new(), complete: true }
+    }
+
+    // expects the reader to be at the start of the entity data
+    // this only reads the data, applying it to the entity table is done by EntityManager::apply
+    pub fn parse(reader: &mut BitReader, data_mgr: &DataManager, updated_entries: i32, is_delta: bool) -> Self {
+        let mut data = Self::new();
+        let mut ent_index: i32 = -1;
+
+        for _ in 0..updated_entries {
+            ent_index += reader.read_ubit_var() + 1;
+
+            if reader.read_bool() {
+                // leave pvs, second bit says if the entity gets deleted
+                let update_type = if reader.read_bool() { EntityUpdateType::Delete } else { EntityUpdateType::LeavePvs };
+                let class_id = data_mgr.entity_mgr.get_entity(ent_index).map(|e| e.class_id);
+                data.updates.push(EntityUpdate { update_type: update_type, ent_index: ent_index, class_id: class_id, serial: None, props: Vec::new() });
+            } else if reader.read_bool() {
+                // enter pvs, we get the class and serial and then the props
+                let class_id = reader.read_int(data_mgr.dt_mgr.server_class_bits);
+                let serial = reader.read_int(10);
+
+                let props = match data_mgr.dt_mgr.prop_lookup.get(class_id as usize) {
+                    Some((_, f_props)) => read_entity_props(reader, f_props, data_mgr.demo_protocol),
+                    None => None,
+                };
+
+                match props {
+                    Some(props) => data.updates.push(EntityUpdate { update_type: EntityUpdateType::EnterPvs, ent_index: ent_index, class_id: Some(class_id), serial: Some(serial), props: props }),
+                    None => { data.complete = false; return data; }
+                }
+            } else {
+                // delta, only the changed props are sent
+                let class_id = match data_mgr.entity_mgr.get_entity(ent_index) {
+                    Some(entity) => entity.class_id,
+                    None => { data.complete = false; return data; }
+                };
+
+                let props = match data_mgr.dt_mgr.prop_lookup.get(class_id as usize) {
+                    Some((_, f_props)) => read_entity_props(reader, f_props, data_mgr.demo_protocol),
+                    None => None,
+                };
+
+                match props {
+                    Some(props) => data.updates.push(EntityUpdate { update_type: EntityUpdateType::Delta, ent_index: ent_index, class_id: Some(class_id), serial: None, props: props }),
+                    None => { data.complete = false; return data; }
+                }
+            }
+        }
+
+        if is_delta {
+            while reader.read_bool() {
+                data.deletions.push(reader.read_int(11));
+            }
+        }
+
+        return data;
+    }
+}
+
+// temp entities are one off effects (bullet impacts, portal shots, etc.), theyre never stored in the entity table
+#[derive(Debug, Clone)]
+pub struct TempEntity {
+    pub delay: f32, // in seconds
+    pub class_id: i32,
+    pub props: Vec<EntityProp>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TempEntitiesData {
+    pub entities: Vec<TempEntity>,
+    pub complete: bool, // false if we had to stop reading somewhere
+}
+
+impl TempEntitiesData {
+    pub fn new() -> Self {
+        Self { entities: Vec::new(), complete: true }
+    }
+
+    // expects the reader to be at the start of the temp entity data
+    pub fn parse(reader: &mut BitReader, data_mgr: &DataManager, num_entries: i32) -> Self {
+        let mut data = Self::new();
+        let mut class_id: Option<i32> = None;
+
+        for _ in 0..num_entries {
+            let mut delay = 0f32;
+            if reader.read_bool() {
+                delay = reader.read_signed_int(8) as f32 / 100f32;
+            }
+
+            // if theres no class we use the class of the last temp entity
+            if reader.read_bool() {
+                class_id = Some(reader.read_int(data_mgr.dt_mgr.server_class_bits) - 1); // the server adds one to it for whatever reason
+            }
+
+            let props = match class_id.and_then(|class_id| data_mgr.dt_mgr.prop_lookup.get(class_id as usize)) {
+                Some((_, f_props)) => read_entity_props(reader, f_props, data_mgr.demo_protocol),
+                None => None,
+            };
+
+            match props {
+                Some(props) => data.entities.push(TempEntity { delay: delay, class_id: class_id.unwrap(), props: props }),
+                None => { data.complete = false; return data; }
+            }
+        }
+
+        return data;
+    }
+}
+
+#[allow(unused)]
+pub fn write_entity_data_to_file(file: &mut File, data: &PacketEntitiesData, data_mgr: &DataManager) {
+    file.write_fmt(format_args!("\n\t\t{} updates:", data.updates.len()));
+    for update in &data.updates {
+        let type_str: &str = update.update_type.into();
+        let class_name = match update.class_id {
+            Some(class_id) => data_mgr.server_class_info.get(class_id as usize).map(|c| c.class_name.clone()).unwrap_or_else(|| class_id.to_string()),
+            None => "Unknown".to_string(),
+        };
+        file.write_fmt(format_args!("\n\t\t\t{}: {} ({})", type_str, update.ent_index, class_name));
+        if let Some(serial) = update.serial {
+            file.write_fmt(format_args!(", Serial: {}", serial));
+        }
+
+        let f_props = update.class_id.and_then(|class_id| data_mgr.dt_mgr.prop_lookup.get(class_id as usize));
+        for prop in &update.props {
+            let prop_name = f_props.map(|(_, f_props)| f_props[prop.index].name.clone()).unwrap_or_else(|| prop.index.to_string());
+            file.write_fmt(format_args!("\n\t\t\t\t{}: {}", prop_name, prop.value));
+        }
+    }
+
+    if !data.deletions.is_empty() {
+        file.write_fmt(format_args!("\n\t\tDeleted: {:?}", data.deletions));
+    }
+
+    if !data.complete {
+        file.write_all("\n\t\tCOULDNT READ THE REST OF THE ENTITY DATA".as_bytes());
+    }
+}
+
+#[allow(unused)]
+pub fn write_temp_entity_data_to_file(file: &mut File, data: &TempEntitiesData, data_mgr: &DataManager) {
+    for temp_entity in &data.entities {
+        let (class_name, f_props) = match data_mgr.dt_mgr.prop_lookup.get(temp_entity.class_id as usize) {
+            Some((class, f_props)) => (class.class_name.clone(), Some(f_props)),
+            None => (temp_entity.class_id.to_string(), None),
+        };
+        file.write_fmt(format_args!("\n\t\t\t{}, Delay: {}", class_name, temp_entity.delay));
+
+        for prop in &temp_entity.props {
+            let prop_name = f_props.map(|f_props| f_props[prop.index].name.clone()).unwrap_or_else(|| prop.index.to_string());
+            file.write_fmt(format_args!("\n\t\t\t\t{}: {}", prop_name, prop.value));
+        }
+    }
+
+    if !data.complete {
+        file.write_all("\n\t\tCOULDNT READ THE REST OF THE TEMP ENTITY DATA".as_bytes());
+    }
+}
+
+// json export, see json.rs
+json_object!(EntityProp { index, value });
+json_object!(EntityUpdate { update_type, ent_index, class_id, serial, props });
+json_object!(PacketEntitiesData { updates, deletions, complete });
+json_object!(TempEntity { delay, class_id, props });
+json_object!(TempEntitiesData { entities, complete });
+json_debug_string!(EntityUpdateType);
+
+impl ToJson for EntityPropValue {
+    fn to_json(&self) -> JsonValue {
+        return match self {
+            EntityPropValue::Int(value) => value.to_json(),
+            EntityPropValue::Float(value) => value.to_json(),
+            EntityPropValue::Vector3(value) => value.to_json(),
+            EntityPropValue::Vector2(value) => value.to_json(),
+            EntityPropValue::String(value) => value.to_json(),
+            EntityPropValue::Array(values) => values.to_json(),
+        };
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use crate::structs::entities::{EntityProp, EntityPropValue, EntityUpdateType};
+use crate::structs::netsvc_types::SvcPacketEntities;
+use crate::structs::datatables_manager::FlattenedProp;
+use crate::structs::utils::{EHandle, ServerClass};
+
+// keeps track of every entity for the entire demo
+// every SvcPacketEntities message gets applied here in order so at any point during parsing
+// this has the current state of every entity, and after parsing you can ask for the value of a prop at any tick
+
+// max amount of entities, entity indices are 11 bits
+pub const MAX_EDICTS: usize = 1 << 11;
+
+#[derive(Debug, Clone)]
+pub struct Entity {
+    pub class_id: i32,
+    pub serial: i32,
+    pub in_pvs: bool,
+    pub props: Vec<Option<EntityPropValue>>, // same order as the flattened props of the class, None if we never got a value
+}
+
+impl Entity {
+    pub fn new(class_id: i32, serial: i32, prop_count: usize) -> Self {
+        Self { class_id: class_id, serial: serial, in_pvs: true, props: vec![None; prop_count] }
+    }
+
+    fn apply_props(&mut self, props: &[EntityProp]) {
+        for prop in props {
+            if prop.index < self.props.len() {
+                self.props[prop.index] = Some(prop.value.clone());
+            }
+        }
+    }
+}
+
+// everything that happened to an entity from when it was created until it got deleted
+// entity indices get reused so one index can have a bunch of these
+#[derive(Debug, Clone)]
+pub struct EntityLifetime {
+    pub class_id: i32,
+    pub serial: i32,
+    pub created_tick: i32,
+    pub deleted_tick: Option<i32>,
+    pub prop_history: HashMap<usize, Vec<(i32, EntityPropValue)>>, // prop index -> every (tick, value) it had
+}
+
+impl EntityLifetime {
+    pub fn new(class_id: i32, serial: i32, created_tick: i32) -> Self {
+        Self { class_id: class_id, serial: serial, created_tick: created_tick, deleted_tick: None, prop_history: HashMap::new() }
+    }
+
+    fn record_props(&mut self, props: &[EntityProp], tick: i32) {
+        for prop in props {
+            self.prop_history.entry(prop.index).or_default().push((tick, prop.value.clone()));
+        }
+    }
+
+    pub fn alive_at_tick(&self, tick: i32) -> bool {
+        return self.created_tick <= tick && self.deleted_tick.map(|t| tick < t).unwrap_or(true);
+    }
+
+    // the last value the prop was set to on or before the given tick
+    pub fn get_prop_at_tick(&self, prop_index: usize, tick: i32) -> Option<&EntityPropValue> {
+        return self.get_prop_change_at_tick(prop_index, tick).map(|(_, value)| value);
+    }
+
+    // same but also the tick it was set on
+    pub fn get_prop_change_at_tick(&self, prop_index: usize, tick: i32) -> Option<&(i32, EntityPropValue)> {
+        let history = self.prop_history.get(&prop_index)?;
+        let count = history.partition_point(|(t, _)| *t <= tick);
+        if count == 0 {
+            return None;
+        }
+        return Some(&history[count - 1]);
+    }
+}
+
+pub struct EntityManager {
+    pub entities: Vec<Option<Entity>>,
+    pub baselines: [Vec<Option<Entity>>; 2], // the server can tell us to save entities to one of these and start new ones from them
+    pub history: Vec<Vec<EntityLifetime>>, // entity index -> every entity that had that index
+}
+
+impl EntityManager {
+    pub fn new() -> Self {
+        Self {
+            entities: vec![None; MAX_EDICTS],
+            baselines: [vec![None; MAX_EDICTS], vec![None; MAX_EDICTS]],
+            history: vec![Vec::new(); MAX_EDICTS],
+        }
+    }
+
+    pub fn get_entity(&self, ent_index: i32) -> Option<&Entity> {
+        return self.entities.get(ent_index as usize)?.as_ref();
+    }
+
+    // the entity that had this index on the given tick
+    pub fn get_lifetime_at_tick(&self, ent_index: i32, tick: i32) -> Option<&EntityLifetime> {
+        return self.history.get(ent_index as usize)?.iter().rev().find(|l| l.alive_at_tick(tick));
+    }
+
+    // only returns the entity if the serial still matches, otherwise the handle points to an entity thats gone
+    pub fn resolve_ehandle(&self, handle: &EHandle) -> Option<&Entity> {
+        return self.get_entity(handle.ent_index()).filter(|e| e.serial == handle.serial());
+    }
+
+    pub fn resolve_ehandle_at_tick(&self, handle: &EHandle, tick: i32) -> Option<&EntityLifetime> {
+        return self.get_lifetime_at_tick(handle.ent_index(), tick).filter(|l| l.serial == handle.serial());
+    }
+
+    fn delete_entity(&mut self, ent_index: usize, tick: i32) {
+        self.entities[ent_index] = None;
+        if let Some(lifetime) = self.history[ent_index].last_mut() {
+            if lifetime.deleted_tick.is_none() {
+                lifetime.deleted_tick = Some(tick);
+            }
+        }
+    }
+
+    // applies an already parsed SvcPacketEntities message
+    // base_line is which baseline slot the message uses and update_baseline tells us to save the new entities to the other one
+    pub fn apply(&mut self, msg: &SvcPacketEntities, tick: i32, instance_baselines: &HashMap<i32, Vec<EntityProp>>, prop_lookup: &[(ServerClass, Vec<FlattenedProp>)]) {
+        let data = match &msg.data {
+            Some(data) => data,
+            None => return,
+        };
+        let base_line = msg.base_line as usize;
+        let update_baseline = msg.update_baseline;
+
+        if update_baseline {
+            self.baselines[1 - base_line] = self.baselines[base_line].clone();
+        }
+
+        // a full update starts over, the engine deletes every entity and everything in the message gets created again
+        if !msg.is_delta {
+            for index in 0..MAX_EDICTS {
+                if self.entities[index].is_some() {
+                    self.delete_entity(index, tick);
+                }
+            }
+        }
+
+        for update in &data.updates {
+            let index = update.ent_index as usize;
+            if index >= MAX_EDICTS {
+                continue;
+            }
+
+            match update.update_type {
+                EntityUpdateType::EnterPvs => {
+                    let class_id = update.class_id.unwrap();
+                    let serial = update.serial.unwrap();
+                    let prop_count = prop_lookup.get(class_id as usize).map(|(_, f_props)| f_props.len()).unwrap_or(0);
+
+                    // the engine builds the entity again every time it enters the pvs, even if its the same one coming back,
+                    // so props that went back to their baseline value while it was gone get that value again.
+                    // it starts from the entity baseline if theres one for this class, otherwise from the instance baseline
+                    let mut entity = Entity::new(class_id, serial, prop_count);
+                    match &self.baselines[base_line][index] {
+                        Some(baseline) if baseline.class_id == class_id => entity.props = baseline.props.clone(),
+                        _ => {
+                            if let Some(props) = instance_baselines.get(&class_id) {
+                                entity.apply_props(props);
+                            }
+                        },
+                    }
+                    entity.apply_props(&update.props);
+                    let start_props: Vec<EntityProp> = entity.props.iter().enumerate()
+                        .filter_map(|(i, value)| value.clone().map(|value| EntityProp { index: i, value: value }))
+                        .collect();
+
+                    // the same entity coming back keeps its history, otherwise its a new one
+                    let old_props = self.entities[index].as_ref()
+                        .filter(|e| e.class_id == class_id && e.serial == serial)
+                        .map(|e| e.props.clone());
+                    if let Some(old_props) = old_props {
+                        // only the props that are different now go into the history
+                        let changed: Vec<EntityProp> = start_props.into_iter()
+                            .filter(|p| old_props.get(p.index).and_then(|v| v.as_ref()) != Some(&p.value))
+                            .collect();
+                        if let Some(lifetime) = self.history[index].last_mut() {
+                            lifetime.record_props(&changed, tick);
+                        }
+                    } else {
+                        if self.entities[index].is_some() {
+                            self.delete_entity(index, tick);
+                        }
+
+                        // save every value the entity starts with so queries work from the first tick
+                        let mut lifetime = EntityLifetime::new(class_id, serial, tick);
+                        lifetime.record_props(&start_props, tick);
+                        self.history[index].push(lifetime);
+                    }
+                    self.entities[index] = Some(entity);
+
+                    if update_baseline {
+                        self.baselines[1 - base_line][index] = self.entities[index].clone();
+                    }
+                },
+                EntityUpdateType::LeavePvs => {
+                    if let Some(entity) = self.entities[index].as_mut() {
+                        entity.in_pvs = false;
+                    }
+                },
+                EntityUpdateType::Delete => self.delete_entity(index, tick),
+                EntityUpdateType::Delta => {
+                    if let Some(entity) = self.entities[index].as_mut() {
+                        entity.in_pvs = true;
+                        entity.apply_props(&update.props);
+                        if let Some(lifetime) = self.history[index].last_mut() {
+                            lifetime.record_props(&update.props, tick);
+                        }
+                    }
+                },
+            }
+        }
+
+        for ent_index in &data.deletions {
+            if (*ent_index as usize) < MAX_EDICTS {
+                self.delete_entity(*ent_index as usize, tick);
+            }
+        }
+    }
+}
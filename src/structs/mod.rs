@@ -13,4 +13,6 @@ pub mod netsvc_types;
 pub mod user_message;
 pub mod stringtable;
 pub mod send_table;
-pub mod datatables_manager;
\ No newline at end of file
+pub mod datatables_manager;
+pub mod entities;
+pub mod entity_manager;
@@ -12,9 +12,13 @@ use crate::bitreader::BitReader;
 use crate::structs::net_svc_message::NetSvcMessageTypes as nsmt;
 use crate::structs::net_svc_message::NetSvcMessageDataTypes as nsmdt;
 use crate::structs::netsvc_types as nt;
-use crate::structs::utils::{GameEventList, bitflags_to_string};
-use crate::structs::data_manager::DataManager;
+use crate::structs::utils::{GameEventList, EntityMessageDataType, bitflags_to_string};
+use crate::structs::data_manager::{DataManager, Game};
+use crate::error::ParseError;
 use crate::structs::user_message::write_usermsg_data_to_file;
+use crate::structs::stringtable::write_stringtable_updates_to_file;
+use crate::structs::entities::{write_entity_data_to_file, write_temp_entity_data_to_file};
+use crate::json::{JsonValue, ToJson, json_debug_string};
 
 #[derive(Debug, Clone)]
 pub enum NetSvcMessageDataTypes {
@@ -55,350 +59,431 @@ pub enum NetSvcMessageDataTypes {
     SvcPaintmapData(SvcPaintmapData),
 }
 
-// implementing into<> for every type is necessary for
+// implementing try_from<> for every type is necessary for
 // getting every messages data later for dumping
+// asking for the wrong type gives you the original value back instead of panicking
 // there is probably a way to do this with 4 lines with some external crate
 // but i dont want to add more dependencies if i dont have to
-impl Into<NetDisconnect> for NetSvcMessageDataTypes {
-    fn into(self) -> NetDisconnect {
-        match self {
-            NetSvcMessageDataTypes::NetDisconnect(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for NetDisconnect {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::NetDisconnect(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<NetFile> for NetSvcMessageDataTypes {
-    fn into(self) -> NetFile {
-        match self {
-            NetSvcMessageDataTypes::NetFile(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for NetFile {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::NetFile(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<NetSetConVar> for NetSvcMessageDataTypes {
-    fn into(self) -> NetSetConVar {
-        match self {
-            NetSvcMessageDataTypes::NetSetConVar(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for NetSetConVar {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::NetSetConVar(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<NetSignonState> for NetSvcMessageDataTypes {
-    fn into(self) -> NetSignonState {
-        match self {
-            NetSvcMessageDataTypes::NetSignonState(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for NetSignonState {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::NetSignonState(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<NetSplitScreenUser> for NetSvcMessageDataTypes {
-    fn into(self) -> NetSplitScreenUser {
-        match self {
-            NetSvcMessageDataTypes::NetSplitScreenUser(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for NetSplitScreenUser {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::NetSplitScreenUser(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<NetStringCmd> for NetSvcMessageDataTypes {
-    fn into(self) -> NetStringCmd {
-        match self {
-            NetSvcMessageDataTypes::NetStringCmd(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for NetStringCmd {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::NetStringCmd(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<NetTick> for NetSvcMessageDataTypes {
-    fn into(self) -> NetTick {
-        match self {
-            NetSvcMessageDataTypes::NetTick(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for NetTick {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::NetTick(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcBspDecal> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcBspDecal {
-        match self {
-            NetSvcMessageDataTypes::SvcBspDecal(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcBspDecal {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcBspDecal(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcClassInfo> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcClassInfo {
-        match self {
-            NetSvcMessageDataTypes::SvcClassInfo(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcClassInfo {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcClassInfo(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcCmdKeyValues> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcCmdKeyValues {
-        match self {
-            NetSvcMessageDataTypes::SvcCmdKeyValues(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcCmdKeyValues {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcCmdKeyValues(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcCreateStringTable> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcCreateStringTable {
-        match self {
-            NetSvcMessageDataTypes::SvcCreateStringTable(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcCreateStringTable {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcCreateStringTable(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcCrosshairAngle> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcCrosshairAngle {
-        match self {
-            NetSvcMessageDataTypes::SvcCrosshairAngle(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcCrosshairAngle {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcCrosshairAngle(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcGetCvarValue> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcGetCvarValue {
-        match self {
-            NetSvcMessageDataTypes::SvcGetCvarValue(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcGetCvarValue {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcGetCvarValue(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcEntityMessage> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcEntityMessage {
-        match self {
-            NetSvcMessageDataTypes::SvcEntityMessage(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcEntityMessage {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcEntityMessage(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcFixAngle> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcFixAngle {
-        match self {
-            NetSvcMessageDataTypes::SvcFixAngle(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcFixAngle {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcFixAngle(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcGameEvent> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcGameEvent {
-        match self {
-            NetSvcMessageDataTypes::SvcGameEvent(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcGameEvent {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcGameEvent(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcGameEventList> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcGameEventList {
-        match self {
-            NetSvcMessageDataTypes::SvcGameEventList(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcGameEventList {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcGameEventList(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcMenu> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcMenu {
-        match self {
-            NetSvcMessageDataTypes::SvcMenu(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcMenu {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcMenu(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcPacketEntities> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcPacketEntities {
-        match self {
-            NetSvcMessageDataTypes::SvcPacketEntities(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcPacketEntities {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcPacketEntities(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcPaintmapData> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcPaintmapData {
-        match self {
-            NetSvcMessageDataTypes::SvcPaintmapData(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcPaintmapData {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcPaintmapData(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcPrefetch> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcPrefetch {
-        match self {
-            NetSvcMessageDataTypes::SvcPrefetch(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcPrefetch {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcPrefetch(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcPrint> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcPrint {
-        match self {
-            NetSvcMessageDataTypes::SvcPrint(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcPrint {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcPrint(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcSendTable> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcSendTable {
-        match self {
-            NetSvcMessageDataTypes::SvcSendTable(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcSendTable {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcSendTable(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcServerInfo> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcServerInfo {
-        match self {
-            NetSvcMessageDataTypes::SvcServerInfo(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcServerInfo {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcServerInfo(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcSetPause> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcSetPause {
-        match self {
-            NetSvcMessageDataTypes::SvcSetPause(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcSetPause {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcSetPause(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcSetView> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcSetView {
-        match self {
-            NetSvcMessageDataTypes::SvcSetView(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcSetView {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcSetView(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcSounds> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcSounds {
-        match self {
-            NetSvcMessageDataTypes::SvcSounds(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcSounds {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcSounds(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcSplitScreen> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcSplitScreen {
-        match self {
-            NetSvcMessageDataTypes::SvcSplitScreen(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcSplitScreen {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcSplitScreen(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcTempEntities> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcTempEntities {
-        match self {
-            NetSvcMessageDataTypes::SvcTempEntities(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcTempEntities {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcTempEntities(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcUpdateStringTable> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcUpdateStringTable {
-        match self {
-            NetSvcMessageDataTypes::SvcUpdateStringTable(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcUpdateStringTable {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcUpdateStringTable(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcUserMessage> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcUserMessage {
-        match self {
-            NetSvcMessageDataTypes::SvcUserMessage(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcUserMessage {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcUserMessage(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcVoiceData> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcVoiceData {
-        match self {
-            NetSvcMessageDataTypes::SvcVoiceData(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcVoiceData {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcVoiceData(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SvcVoiceInit> for NetSvcMessageDataTypes {
-    fn into(self) -> SvcVoiceInit {
-        match self {
-            NetSvcMessageDataTypes::SvcVoiceInit(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<NetSvcMessageDataTypes> for SvcVoiceInit {
+    type Error = NetSvcMessageDataTypes;
+
+    fn try_from(value: NetSvcMessageDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            NetSvcMessageDataTypes::SvcVoiceInit(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub enum NetSvcMessageTypes {
-    Unknown,
-    NetNop,
-    NetDisconnect,
-    NetFile,
-    NetSplitScreenUser,
-    NetTick,
-    NetStringCmd,
-    NetSetConVar,
-    NetSignonState,
-    SvcPrint,
-    SvcServerInfo,
-    SvcSendTable,
-    SvcClassInfo,
-    SvcSetPause,
-    SvcCreateStringTable,
-    SvcUpdateStringTable,
-    SvcVoiceInit,
-    SvcVoiceData,
-    SvcSounds,
-    SvcSetView,
-    SvcFixAngle,
-    SvcCrosshairAngle,
-    SvcBspDecal,
-    SvcSplitScreen,
-    SvcUserMessage,
-    SvcEntityMessage,
-    SvcGameEvent,
-    SvcPacketEntities,
-    SvcTempEntities,
-    SvcPrefetch,
-    SvcMenu,
-    SvcGameEventList,
-    SvcGetCvarValue,
-    SvcCmdKeyValues,
-    SvcPaintmapData,
-}
-
-// these values are pretty much the same in p1 and p2 (except for SvcPrint)
-// this is gonna become larger once i do other games
+enum_from_primitive! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum NetSvcMessageTypes {
+        Unknown,
+        NetNop,
+        NetDisconnect,
+        NetFile,
+        NetSplitScreenUser,
+        NetTick,
+        NetStringCmd,
+        NetSetConVar,
+        NetSignonState,
+        SvcPrint,
+        SvcServerInfo,
+        SvcSendTable,
+        SvcClassInfo,
+        SvcSetPause,
+        SvcCreateStringTable,
+        SvcUpdateStringTable,
+        SvcVoiceInit,
+        SvcVoiceData,
+        SvcSounds,
+        SvcSetView,
+        SvcFixAngle,
+        SvcCrosshairAngle,
+        SvcBspDecal,
+        SvcSplitScreen,
+        SvcUserMessage,
+        SvcEntityMessage,
+        SvcGameEvent,
+        SvcPacketEntities,
+        SvcTempEntities,
+        SvcPrefetch,
+        SvcMenu,
+        SvcGameEventList,
+        SvcGetCvarValue,
+        SvcCmdKeyValues,
+        SvcPaintmapData,
+    }
+}
+
+// these values are pretty much the same in p1 and p2
+// demo protocol 4 added NetSplitScreenUser at 3 which moves the other net messages up by one, and SvcPrint went to 16
 impl NetSvcMessageTypes {
-    pub fn from_int(value: i32) -> NetSvcMessageTypes {
+    pub fn from_int(value: i32, demo_protocol: i32) -> NetSvcMessageTypes {
+        if demo_protocol == 4 {
+            match value {
+                3 => return NetSvcMessageTypes::NetSplitScreenUser,
+                4 => return NetSvcMessageTypes::NetTick,
+                5 => return NetSvcMessageTypes::NetStringCmd,
+                6 => return NetSvcMessageTypes::NetSetConVar,
+                7 => return NetSvcMessageTypes::NetSignonState,
+                16 => return NetSvcMessageTypes::SvcPrint,
+                _ => (),
+            }
+        }
+
         return match value {
             0 => NetSvcMessageTypes::NetNop,
             1 => NetSvcMessageTypes::NetDisconnect,
@@ -416,7 +501,6 @@ impl NetSvcMessageTypes {
             13 => NetSvcMessageTypes::SvcUpdateStringTable,
             14 => NetSvcMessageTypes::SvcVoiceInit,
             15 => NetSvcMessageTypes::SvcVoiceData,
-            16 => NetSvcMessageTypes::SvcPrint, // 16 is svc print in demo protocol 4, just adding it here so i dont have to later
             17 => NetSvcMessageTypes::SvcSounds,
             18 => NetSvcMessageTypes::SvcSetView,
             19 => NetSvcMessageTypes::SvcFixAngle,
@@ -437,6 +521,11 @@ impl NetSvcMessageTypes {
             _ => NetSvcMessageTypes::Unknown,
         }
     }
+
+    // every id in order, for the game profiles
+    pub fn table(demo_protocol: i32) -> Vec<NetSvcMessageTypes> {
+        return (0..34).map(|id| Self::from_int(id, demo_protocol)).collect();
+    }
 }
 
 #[derive(Debug)]
@@ -451,24 +540,26 @@ impl NetSvcMessage {
     }
 }
 
-pub fn parse(reader: &mut BitReader, demo_data_mgr: &mut DataManager, size: i32) -> Vec<NetSvcMessage> {
+pub fn parse(reader: &mut BitReader, demo_data_mgr: &mut DataManager, size: i32) -> Result<Vec<NetSvcMessage>, ParseError> {
     let mut messages: Vec<NetSvcMessage> = Vec::new();
-    let start_index = reader.current;
+    let end_index = reader.current + (size * 8) as usize;
 
-    while ((start_index + (size * 8) as usize) - reader.current) > 6 {
+    while reader.current + 6 < end_index {
         let mut cur_message: NetSvcMessage = NetSvcMessage::new();
 
+        let msg_index = reader.current;
         let msg_type = reader.read_int(demo_data_mgr.net_svc_type_bits);
 
-        cur_message.msg_type = nsmt::from_int(msg_type);
+        cur_message.msg_type = demo_data_mgr.net_svc_message_list.get(msg_type as usize).copied().unwrap_or(nsmt::Unknown);
 
         match cur_message.msg_type {
-            nsmt::Unknown => cur_message.data = nsmdt::Unknown,
+            // we dont know how long it is so theres no way to keep going
+            nsmt::Unknown => return Err(ParseError::UnknownMessageType(msg_type, demo_data_mgr.error_pos(msg_index))),
             nsmt::NetNop => cur_message.data = nsmdt::NetNop(nt::NetNop),
             nsmt::NetDisconnect => cur_message.data = nsmdt::NetDisconnect(nt::NetDisconnect::parse(reader)),
             nsmt::NetFile => cur_message.data = nsmdt::NetFile(nt::NetFile::parse(reader)),
             nsmt::NetSetConVar => cur_message.data = nsmdt::NetSetConVar(nt::NetSetConVar::parse(reader)),
-            nsmt::NetSignonState => cur_message.data = nsmdt::NetSignonState(nt::NetSignonState::parse(reader)),
+            nsmt::NetSignonState => cur_message.data = nsmdt::NetSignonState(nt::NetSignonState::parse(reader, demo_data_mgr)),
             nsmt::NetSplitScreenUser => cur_message.data = nsmdt::NetSplitScreenUser(nt::NetSplitScreenUser::parse(reader)),
             nsmt::NetStringCmd => cur_message.data = nsmdt::NetStringCmd(nt::NetStringCmd::parse(reader)),
             nsmt::NetTick => cur_message.data = nsmdt::NetTick(nt::NetTick::parse(reader)),
@@ -477,54 +568,66 @@ pub fn parse(reader: &mut BitReader, demo_data_mgr: &mut DataManager, size: i32)
             nsmt::SvcCmdKeyValues => cur_message.data = nsmdt::SvcCmdKeyValues(nt::SvcCmdKeyValues::parse(reader)),
             nsmt::SvcCreateStringTable => cur_message.data = nsmdt::SvcCreateStringTable(nt::SvcCreateStringTable::parse(reader, demo_data_mgr)),
             nsmt::SvcCrosshairAngle => cur_message.data = nsmdt::SvcCrosshairAngle(nt::SvcCrosshairAngle::parse(reader)),
-            nsmt::SvcEntityMessage => cur_message.data = nsmdt::SvcEntityMessage(nt::SvcEntityMessage::parse(reader)),
+            nsmt::SvcEntityMessage => cur_message.data = nsmdt::SvcEntityMessage(nt::SvcEntityMessage::parse(reader, demo_data_mgr)),
             nsmt::SvcFixAngle => cur_message.data = nsmdt::SvcFixAngle(nt::SvcFixAngle::parse(reader)),
             nsmt::SvcGameEvent => cur_message.data = nsmdt::SvcGameEvent(nt::SvcGameEvent::parse(reader, &mut demo_data_mgr.game_event_list)),
             nsmt::SvcGameEventList => cur_message.data = nsmdt::SvcGameEventList(nt::SvcGameEventList::parse(reader, &mut demo_data_mgr.game_event_list)),
             nsmt::SvcGetCvarValue => cur_message.data = nsmdt::SvcGetCvarValue(nt::SvcGetCvarValue::parse(reader)),
             nsmt::SvcMenu => cur_message.data = nsmdt::SvcMenu(nt::SvcMenu::parse(reader)),
-            nsmt::SvcPacketEntities => cur_message.data = nsmdt::SvcPacketEntities(nt::SvcPacketEntities::parse(reader)),
+            nsmt::SvcPacketEntities => cur_message.data = nsmdt::SvcPacketEntities(nt::SvcPacketEntities::parse(reader, demo_data_mgr)),
             nsmt::SvcPaintmapData => cur_message.data = nsmdt::SvcPaintmapData(nt::SvcPaintmapData::parse(reader)),
-            nsmt::SvcPrefetch => cur_message.data = nsmdt::SvcPrefetch(nt::SvcPrefetch::parse(reader, &demo_data_mgr)),
+            nsmt::SvcPrefetch => cur_message.data = nsmdt::SvcPrefetch(nt::SvcPrefetch::parse(reader, demo_data_mgr)),
             nsmt::SvcPrint => cur_message.data = nsmdt::SvcPrint(nt::SvcPrint::parse(reader)),
             nsmt::SvcSendTable => cur_message.data = nsmdt::SvcSendTable(nt::SvcSendTable::parse(reader)),
             nsmt::SvcServerInfo => cur_message.data = nsmdt::SvcServerInfo(nt::SvcServerInfo::parse(reader, demo_data_mgr)),
             nsmt::SvcSetPause => cur_message.data = nsmdt::SvcSetPause(nt::SvcSetPause::parse(reader)),
             nsmt::SvcSetView => cur_message.data = nsmdt::SvcSetView(nt::SvcSetView::parse(reader)),
-            nsmt::SvcSounds => cur_message.data = nsmdt::SvcSounds(nt::SvcSounds::parse(reader)),
+            nsmt::SvcSounds => cur_message.data = nsmdt::SvcSounds(nt::SvcSounds::parse(reader, demo_data_mgr)),
             nsmt::SvcSplitScreen => cur_message.data = nsmdt::SvcSplitScreen(nt::SvcSplitScreen::parse(reader)),
             nsmt::SvcTempEntities => cur_message.data = nsmdt::SvcTempEntities(nt::SvcTempEntities::parse(reader, demo_data_mgr)),
-            nsmt::SvcUpdateStringTable => cur_message.data = nsmdt::SvcUpdateStringTable(nt::SvcUpdateStringTable::parse(reader)),
-            nsmt::SvcUserMessage => cur_message.data = nsmdt::SvcUserMessage(nt::SvcUserMessage::parse(reader, demo_data_mgr.user_message_list.clone())),
+            nsmt::SvcUpdateStringTable => cur_message.data = nsmdt::SvcUpdateStringTable(nt::SvcUpdateStringTable::parse(reader, demo_data_mgr)),
+            nsmt::SvcUserMessage => cur_message.data = nsmdt::SvcUserMessage(nt::SvcUserMessage::parse(reader, demo_data_mgr)),
             nsmt::SvcVoiceData => cur_message.data = nsmdt::SvcVoiceData(nt::SvcVoiceData::parse(reader)),
             nsmt::SvcVoiceInit => cur_message.data = nsmdt::SvcVoiceInit(nt::SvcVoiceInit::parse(reader)),
         };
 
+        // SvcServerInfo can change which game we think it is
+        if demo_data_mgr.game == Game::UNKNOWN {
+            return Err(ParseError::UnsupportedGame(demo_data_mgr.error_pos(msg_index)));
+        }
+        // the reader has the whole file so it only overflows at the end of it, reading past the packet is just as bad
+        if let Some(bit_offset) = reader.overflowed {
+            return Err(ParseError::Truncated(demo_data_mgr.error_pos(bit_offset)));
+        }
+        if reader.current > end_index {
+            return Err(ParseError::Overflow(demo_data_mgr.error_pos(end_index)));
+        }
+
         messages.push(cur_message);
     }
 
-    return messages;
+    return Ok(messages);
 }
 
-// this is why all the into's are needed
+// this is why all the try_from's are needed
 #[allow(unused)]
 pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, data_mgr: &DataManager) {
     for message in messages {
         match message.msg_type {
             nsmt::NetDisconnect => {
-                let msg_data: nt::NetDisconnect = message.data.into();
+                let Ok(msg_data) = nt::NetDisconnect::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: NetDisconnect".as_bytes());
                 file.write_fmt(format_args!("\n\t\tText: {}\n", msg_data.text));
             },
             nsmt::NetFile => {
-                let msg_data: nt::NetFile = message.data.into();
+                let Ok(msg_data) = nt::NetFile::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: NetFile".as_bytes());
                 file.write_fmt(format_args!("\n\t\tTransfer ID: {}", msg_data.transfer_id));
                 file.write_fmt(format_args!("\n\t\tFilename: {}", msg_data.filename));
                 file.write_fmt(format_args!("\n\t\tFileRequested: {}", msg_data.file_requested));
             },
             nsmt::NetTick => {
-                let msg_data: nt::NetTick = message.data.into();
+                let Ok(msg_data) = nt::NetTick::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: NetTick".as_bytes());
                 file.write_fmt(format_args!("\n\t\tTick: {}", msg_data.tick));
                 file.write_fmt(format_args!("\n\t\tHost Frame Time: {}", msg_data.host_frame_time as f32 / 1e5));
@@ -532,12 +635,12 @@ pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, dat
             },
             nsmt::NetNop => { file.write_all("\n\tMessage: NetNop".as_bytes()); },
             nsmt::NetStringCmd => {
-                let msg_data: nt::NetStringCmd = message.data.into();
+                let Ok(msg_data) = nt::NetStringCmd::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: NetStringCmd".as_bytes());
                 file.write_fmt(format_args!("\n\t\tCommand: {}", msg_data.command));
             },
             nsmt::NetSetConVar => {
-                let msg_data: nt::NetSetConVar = message.data.into();
+                let Ok(msg_data) = nt::NetSetConVar::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: NetSetConVar".as_bytes());
                 file.write_fmt(format_args!("\n\t\tLength: {}", msg_data.length));
                 file.write_fmt(format_args!("\n\t\t{} convars:", msg_data.convars.len()));
@@ -546,19 +649,27 @@ pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, dat
                 }
             },
             nsmt::NetSignonState => {
-                let msg_data: nt::NetSignonState = message.data.into();
+                let Ok(msg_data) = nt::NetSignonState::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: NetSignonState".as_bytes());
                 file.write_fmt(format_args!("\n\t\tSignon State: {}", msg_data.signon_state));
                 file.write_fmt(format_args!("\n\t\tSpawn Count: {}", msg_data.spawn_count));
+                if let Some(num_server_players) = msg_data.num_server_players {
+                    file.write_fmt(format_args!("\n\t\tNum Server Players: {}", num_server_players));
+                    file.write_fmt(format_args!("\n\t\tPlayer Network IDs Length (bytes): {}", msg_data.player_network_ids.map(|ids| ids.len()).unwrap_or(0)));
+                    file.write_fmt(format_args!("\n\t\tMap Name: {}", msg_data.map_name.unwrap_or_default()));
+                }
             }
             nsmt::SvcServerInfo => {
-                let msg_data: nt::SvcServerInfo = message.data.into();
+                let Ok(msg_data) = nt::SvcServerInfo::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcServerInfo".as_bytes());
                 file.write_fmt(format_args!("\n\t\tProtocol: {}", msg_data.protocol));
                 file.write_fmt(format_args!("\n\t\tServerCount: {}", msg_data.server_count));
                 file.write_fmt(format_args!("\n\t\tIs HLTV: {}", msg_data.is_hltv));
                 file.write_fmt(format_args!("\n\t\tIs Dedicated: {}", msg_data.is_dedicated));
                 file.write_fmt(format_args!("\n\t\tClient CRC: {}", msg_data.client_crc));
+                if let Some(string_table_crc) = msg_data.string_table_crc {
+                    file.write_fmt(format_args!("\n\t\tString Table CRC: {}", string_table_crc));
+                }
                 file.write_fmt(format_args!("\n\t\tMax Classes: {}", msg_data.max_classes));
                 if data_mgr.network_protocol == 24 {
                     let bytes = msg_data.map_md5.unwrap();
@@ -579,19 +690,19 @@ pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, dat
                 file.write_fmt(format_args!("\n\t\tHost Name: {}", msg_data.host_name));
             },
             nsmt::SvcSendTable => {
-                let msg_data: nt::SvcSendTable = message.data.into();
+                let Ok(msg_data) = nt::SvcSendTable::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcSendTable".as_bytes());
                 file.write_fmt(format_args!("\n\t\tNeeds Decoder: {}", msg_data.needs_decoder));
                 file.write_fmt(format_args!("\n\t\tLength (bits): {}", msg_data.length));
                 file.write_fmt(format_args!("\n\t\tProps: {}", msg_data.props));
             },
             nsmt::NetSplitScreenUser => {
-                let msg_data: nt::NetSplitScreenUser = message.data.into();
+                let Ok(msg_data) = nt::NetSplitScreenUser::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: NetSplitScreenUser".as_bytes());
                 file.write_fmt(format_args!("\n\t\tUnknown: {}", msg_data.unknown));
             },
             nsmt::SvcBspDecal => {
-                let msg_data: nt::SvcBspDecal = message.data.into();
+                let Ok(msg_data) = nt::SvcBspDecal::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcBspDecal".as_bytes());
                 file.write_fmt(format_args!("\n\t\tPos: {}, {}, {}",
                     msg_data.pos[0].map(|i| {i.to_string()}).unwrap_or_else(|| {"Null".to_string()}),
@@ -603,24 +714,24 @@ pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, dat
                 file.write_fmt(format_args!("\n\t\tLow Priority: {}", msg_data.low_priority));
             },
             nsmt::SvcClassInfo => {
-                let msg_data: nt::SvcClassInfo = message.data.into();
+                let Ok(msg_data) = nt::SvcClassInfo::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcClassInfo".as_bytes());
                 file.write_fmt(format_args!("\n\t\tCreate On Client: {}", msg_data.create_on_client));
                 file.write_fmt(format_args!("\n\t\t{} server classes", msg_data.length));
                 if !msg_data.create_on_client {
-                    file.write(":".as_bytes());
+                    file.write_all(":".as_bytes());
                     for server_class in msg_data.server_classes {
                         file.write_fmt(format_args!("\n\t\t\t[{}] {} ({})", server_class.datatable_id, server_class.class_name, server_class.class_name));
                     }
                 }
             },
             nsmt::SvcSetPause => {
-                let msg_data: nt::SvcSetPause = message.data.into();
+                let Ok(msg_data) = nt::SvcSetPause::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcSetPause".as_bytes());
                 file.write_fmt(format_args!("\n\t\tPaused: {}", msg_data.paused));
             },
             nsmt::SvcCreateStringTable => {
-                let msg_data: nt::SvcCreateStringTable = message.data.into();
+                let Ok(msg_data) = nt::SvcCreateStringTable::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcCreateStringTable".as_bytes());
                 file.write_fmt(format_args!("\n\t\tName: {}", msg_data.name));
                 file.write_fmt(format_args!("\n\t\tMax Entries: {}", msg_data.max_entries));
@@ -632,25 +743,25 @@ pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, dat
                 if data_mgr.network_protocol >= 15 {
                     file.write_fmt(format_args!("\n\t\tFlags: {}", bitflags_to_string(msg_data.flags.iter_names())));
                 }
-                file.write_all("\n\t\tNO MORE DATA AVAILABLE (yet)".as_bytes());
+                write_stringtable_updates_to_file(file, &msg_data.entries);
             },
             nsmt::SvcUpdateStringTable => {
-                let msg_data: nt::SvcUpdateStringTable = message.data.into();
+                let Ok(msg_data) = nt::SvcUpdateStringTable::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcUpdateStringTable".as_bytes());
                 file.write_fmt(format_args!("\n\t\tTable ID: {}", msg_data.table_id));
                 file.write_fmt(format_args!("\n\t\tNum Changed Entries: {}", msg_data.num_changed_entries));
                 file.write_fmt(format_args!("\n\t\tLength (bits): {}", msg_data.length));
-                file.write_all("\n\t\tNO MORE DATA AVAILABLE (yet)".as_bytes());
+                write_stringtable_updates_to_file(file, &msg_data.entries);
             },
             nsmt::SvcVoiceInit => {
-                let msg_data: nt::SvcVoiceInit = message.data.into();
+                let Ok(msg_data) = nt::SvcVoiceInit::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcVoiceInit".as_bytes());
                 file.write_fmt(format_args!("\n\t\tCodec: {}", msg_data.codec));
                 file.write_fmt(format_args!("\n\t\tQuality: {}", msg_data.quality));
                 file.write_fmt(format_args!("\n\t\tCodec: {}", msg_data.sample_rate.map(|i| {i.to_string()}).unwrap_or_else(|| {"Null".to_string()})));
             },
             nsmt::SvcVoiceData => {
-                let msg_data: nt::SvcVoiceData = message.data.into();
+                let Ok(msg_data) = nt::SvcVoiceData::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcVoiceInit".as_bytes());
                 file.write_fmt(format_args!("\n\t\tClient: {}", msg_data.client));
                 file.write_fmt(format_args!("\n\t\tProximity: {}", msg_data.proximity));
@@ -659,36 +770,50 @@ pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, dat
                 file.write_all("\n\t\tNO MORE DATA AVAILABLE (yet)".as_bytes());
             },
             nsmt::SvcPrint => {
-                let msg_data: nt::SvcPrint = message.data.into();
+                let Ok(msg_data) = nt::SvcPrint::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcPrint".as_bytes());
                 file.write_fmt(format_args!("\n\t\tMessage: {}", msg_data.message));
             },
             nsmt::SvcSounds => {
-                let msg_data: nt::SvcSounds = message.data.into();
+                let Ok(msg_data) = nt::SvcSounds::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcSounds".as_bytes());
                 file.write_fmt(format_args!("\n\t\tReliable Sound: {}", msg_data.reliable_sound));
                 file.write_fmt(format_args!("\n\t\tNum Sounds: {}", msg_data.num_sounds));
                 file.write_fmt(format_args!("\n\t\tLength (bits): {}", msg_data.length));
-                file.write_all("\n\t\tNO MORE DATA AVAILABLE (yet)".as_bytes());
+                for sound in msg_data.data {
+                    file.write_fmt(format_args!("\n\t\t\t{} ({})", sound.sound_name.unwrap_or_else(|| "Unknown".to_string()), sound.sound_num));
+                    file.write_fmt(format_args!("\n\t\t\t\tEntity Index: {}", sound.entity_index));
+                    file.write_fmt(format_args!("\n\t\t\t\tFlags: {}", bitflags_to_string(sound.flags.iter_names())));
+                    file.write_fmt(format_args!("\n\t\t\t\tChannel: {:?}", sound.channel));
+                    file.write_fmt(format_args!("\n\t\t\t\tIs Ambient: {}", sound.is_ambient));
+                    file.write_fmt(format_args!("\n\t\t\t\tIs Sentence: {}", sound.is_sentence));
+                    file.write_fmt(format_args!("\n\t\t\t\tSequence Number: {}", sound.sequence_number));
+                    file.write_fmt(format_args!("\n\t\t\t\tVolume: {}", sound.volume));
+                    file.write_fmt(format_args!("\n\t\t\t\tSound Level: {}", sound.sound_level));
+                    file.write_fmt(format_args!("\n\t\t\t\tPitch: {}", sound.pitch));
+                    file.write_fmt(format_args!("\n\t\t\t\tDelay: {}", sound.delay));
+                    file.write_fmt(format_args!("\n\t\t\t\tOrigin: {}", sound.origin));
+                    file.write_fmt(format_args!("\n\t\t\t\tSpeaker Entity: {}", sound.speaker_entity));
+                }
             },
             nsmt::SvcSetView => {
-                let msg_data: nt::SvcSetView = message.data.into();
+                let Ok(msg_data) = nt::SvcSetView::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcSetView".as_bytes());
                 file.write_fmt(format_args!("\n\t\tEntity Index: {}", msg_data.entity_index));
             },
             nsmt::SvcFixAngle => {
-                let msg_data: nt::SvcFixAngle = message.data.into();
+                let Ok(msg_data) = nt::SvcFixAngle::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcFixAngle".as_bytes());
                 file.write_fmt(format_args!("\n\t\tRelative: {}", msg_data.relative));
                 file.write_fmt(format_args!("\n\t\tAngle: {:.3} {:.3} {:.3}", msg_data.angle[0], msg_data.angle[1], msg_data.angle[2]));
             },
             nsmt::SvcCrosshairAngle => {
-                let msg_data: nt::SvcCrosshairAngle = message.data.into();
+                let Ok(msg_data) = nt::SvcCrosshairAngle::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcCrosshairAngle".as_bytes());
                 file.write_fmt(format_args!("\n\t\tAngle: {} {} {}", msg_data.angle[0], msg_data.angle[1], msg_data.angle[2]));
             },
             nsmt::SvcUserMessage => {
-                let msg_data: nt::SvcUserMessage = message.data.into();
+                let Ok(msg_data) = nt::SvcUserMessage::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcUserMessage".as_bytes());
                 file.write_fmt(format_args!("\n\t\tMessage Type: {:?}", msg_data.data.msg_type));
                 file.write_fmt(format_args!("\n\t\tLength (bits): {}", msg_data.length));
@@ -696,25 +821,41 @@ pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, dat
                 write_usermsg_data_to_file(msg_data.data, file);
             },
             nsmt::SvcEntityMessage => {
-                let msg_data: nt::SvcEntityMessage = message.data.into();
+                let Ok(msg_data) = nt::SvcEntityMessage::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcEntityMessage".as_bytes());
                 file.write_fmt(format_args!("\n\t\tEntity Index: {}", msg_data.entity_index));
-                file.write_fmt(format_args!("\n\t\tClass ID: {}", msg_data.class_id));
+                // the classes might have come in after this message was parsed
+                let class_name = msg_data.data.class_name
+                    .or_else(|| data_mgr.server_class_info.get(msg_data.class_id as usize).map(|c| c.class_name.clone()))
+                    .unwrap_or_else(|| "Unknown".to_string());
+                file.write_fmt(format_args!("\n\t\tClass: {} ({})", class_name, msg_data.class_id));
                 file.write_fmt(format_args!("\n\t\tLength (bits): {}", msg_data.length));
+                match msg_data.data.data {
+                    EntityMessageDataType::Strider(strider) => {
+                        file.write_fmt(format_args!("\n\t\tStrider Message: {:?}", strider.message_type));
+                        if let Some(position) = strider.position {
+                            file.write_fmt(format_args!("\n\t\tPosition: {}", position));
+                        }
+                    },
+                    EntityMessageDataType::Raw(bytes) => {
+                        let data_str: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                        file.write_fmt(format_args!("\n\t\tData: {}", data_str.join(" ")));
+                    },
+                }
             },
             nsmt::SvcGameEvent => {
-                let msg_data: nt::SvcGameEvent = message.data.into();
+                let Ok(msg_data) = nt::SvcGameEvent::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcGameEvent".as_bytes());
                 file.write_fmt(format_args!("\n\t\tLength: {}", msg_data.length));
 
                 let event = msg_data.data;
                 file.write_fmt(format_args!("\n\t\t{} ({})", event.descriptor.name, event.descriptor.event_id));
                 for key in event.keys {
-                    file.write_fmt(format_args!("\n\t\t\t{}: {}", key.0, key.1.to_string()));
+                    file.write_fmt(format_args!("\n\t\t\t{}: {}", key.0, key.1));
                 }
             },
             nsmt::SvcPacketEntities => {
-                let msg_data: nt::SvcPacketEntities = message.data.into();
+                let Ok(msg_data) = nt::SvcPacketEntities::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcPacketEntities".as_bytes());
                 file.write_fmt(format_args!("\n\t\tMax Entries: {}", msg_data.max_entries));
                 file.write_fmt(format_args!("\n\t\tIs Delta: {}", msg_data.is_delta));
@@ -723,23 +864,29 @@ pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, dat
                 file.write_fmt(format_args!("\n\t\tUpdated Entries: {}", msg_data.updated_entries));
                 file.write_fmt(format_args!("\n\t\tLength (bits): {}", msg_data.length));
                 file.write_fmt(format_args!("\n\t\tUpdata Baseline: {}", msg_data.update_baseline));
-                file.write_all("\n\t\tNO MORE DATA AVAILABLE (yet)".as_bytes());
+                match msg_data.data {
+                    Some(data) => write_entity_data_to_file(file, &data, data_mgr),
+                    None => { file.write_all("\n\t\tNO MORE DATA AVAILABLE (no datatables)".as_bytes()); },
+                }
             },
             nsmt::SvcTempEntities => {
-                let msg_data: nt::SvcTempEntities = message.data.into();
+                let Ok(msg_data) = nt::SvcTempEntities::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcTempEntities".as_bytes());
                 file.write_fmt(format_args!("\n\t\tNum Entries: {}", msg_data.num_entries));
                 file.write_fmt(format_args!("\n\t\tLength (bits): {}", msg_data.length));
-                file.write_all("\n\t\tNO MORE DATA AVAILABLE (yet)".as_bytes());
+                match msg_data.data {
+                    Some(data) => write_temp_entity_data_to_file(file, &data, data_mgr),
+                    None => { file.write_all("\n\t\tNO MORE DATA AVAILABLE (no datatables)".as_bytes()); },
+                }
             },
             nsmt::SvcPrefetch => {
-                let msg_data: nt::SvcPrefetch = message.data.into();
+                let Ok(msg_data) = nt::SvcPrefetch::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcPrefetch".as_bytes());
                 file.write_fmt(format_args!("\n\t\tSound Index: {}", msg_data.sound_index));
                 file.write_fmt(format_args!("\n\t\tSound Name: {}", msg_data.sound_name));
             },
             nsmt::SvcMenu => {
-                let msg_data: nt::SvcMenu = message.data.into();
+                let Ok(msg_data) = nt::SvcMenu::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcMenu".as_bytes());
                 file.write_fmt(format_args!("\n\t\tMenu Type: {}", msg_data.length));
                 file.write_fmt(format_args!("\n\t\tLength (bits): {}", msg_data.length));
@@ -768,29 +915,29 @@ pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, dat
                         }, name).to_string());
                         keys_str.push_str(", ");
                     }
-                    keys_str = if keys_str != "".to_string() { keys_str[..keys_str.len()-2].to_string() } else { "".to_string() };
+                    keys_str = if !keys_str.is_empty() { keys_str[..keys_str.len()-2].to_string() } else { "".to_string() };
                     file.write_fmt(format_args!("{}]", keys_str));
                 }
             },
             nsmt::SvcGetCvarValue => {
-                let msg_data: nt::SvcGetCvarValue = message.data.into();
+                let Ok(msg_data) = nt::SvcGetCvarValue::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcGetCvarValue".as_bytes());
                 file.write_fmt(format_args!("\n\t\tCookie: {}", msg_data.cookie));
                 file.write_fmt(format_args!("\n\t\tCvar Name: {}", msg_data.cvar_name));
             },
             nsmt::SvcCmdKeyValues => {
-                let msg_data: nt::SvcCmdKeyValues = message.data.into(); 
+                let Ok(msg_data) = nt::SvcCmdKeyValues::try_from(message.data) else { continue; }; 
                 file.write_fmt(format_args!("\n\t\tLength (bytes): {}", msg_data.length));
                 file.write_all("\n\t\tNO MORE DATA AVAILABLE (yet)".as_bytes());
             },
             nsmt::SvcPaintmapData => {
-                let msg_data: nt::SvcPaintmapData = message.data.into();
+                let Ok(msg_data) = nt::SvcPaintmapData::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcPaintmapData".as_bytes());
                 file.write_fmt(format_args!("\n\t\tLength (bits): {}", msg_data.length));
                 file.write_all("\n\t\tNO MORE DATA AVAILABLE (yet)".as_bytes());
             },
             nsmt::SvcSplitScreen => {
-                let msg_data: nt::SvcSplitScreen = message.data.into();
+                let Ok(msg_data) = nt::SvcSplitScreen::try_from(message.data) else { continue; };
                 file.write_all("\n\tMessage: SvcSplitScreen".as_bytes());
                 file.write_fmt(format_args!("\n\tType: {}", msg_data.s_type));
                 file.write_fmt(format_args!("\n\t\tLength (bytes): {}", msg_data.length));
@@ -800,4 +947,56 @@ pub fn write_msg_data_to_file(file: &mut File, messages: Vec<NetSvcMessage>, dat
         }
     }
     file.write_all("\n".as_bytes());
-}
\ No newline at end of file
+}
+
+// json export, see json.rs
+json_debug_string!(NetSvcMessageTypes);
+
+impl ToJson for NetSvcMessageDataTypes {
+    fn to_json(&self) -> JsonValue {
+        return match self {
+            NetSvcMessageDataTypes::Unknown => JsonValue::Null,
+            NetSvcMessageDataTypes::NetNop(data) => data.to_json(),
+            NetSvcMessageDataTypes::NetDisconnect(data) => data.to_json(),
+            NetSvcMessageDataTypes::NetFile(data) => data.to_json(),
+            NetSvcMessageDataTypes::NetSplitScreenUser(data) => data.to_json(),
+            NetSvcMessageDataTypes::NetTick(data) => data.to_json(),
+            NetSvcMessageDataTypes::NetStringCmd(data) => data.to_json(),
+            NetSvcMessageDataTypes::NetSetConVar(data) => data.to_json(),
+            NetSvcMessageDataTypes::NetSignonState(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcPrint(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcServerInfo(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcSendTable(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcClassInfo(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcSetPause(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcCreateStringTable(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcUpdateStringTable(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcVoiceInit(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcVoiceData(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcSounds(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcSetView(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcFixAngle(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcCrosshairAngle(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcBspDecal(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcSplitScreen(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcUserMessage(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcEntityMessage(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcGameEvent(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcPacketEntities(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcTempEntities(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcPrefetch(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcMenu(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcGameEventList(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcGetCvarValue(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcCmdKeyValues(data) => data.to_json(),
+            NetSvcMessageDataTypes::SvcPaintmapData(data) => data.to_json(),
+        };
+    }
+}
+
+// the type is written next to the data because the data alone doesnt say what message it is
+impl ToJson for NetSvcMessage {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::object(vec![("type", self.msg_type.to_json()), ("data", self.data.to_json())]);
+    }
+}
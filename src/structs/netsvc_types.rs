@@ -1,9 +1,12 @@
 use std::collections::HashMap;
 use crate::bitreader::BitReader;
 use crate::structs::utils;
-use crate::structs::data_manager::DataManager;
-use crate::structs::{user_message::{UserMessageType, UserMessage}, data_manager::Game};
-use crate::structs::utils::log2_of_x_plus_one;
+use crate::structs::data_manager::{DataManager, TickIntervalSource};
+use crate::structs::{user_message::UserMessage, data_manager::Game};
+use crate::structs::utils::{log2_of_x_plus_one, decompress_lzss};
+use crate::structs::stringtable::{StringTable, StringTableUpdate};
+use crate::structs::entities::{PacketEntitiesData, TempEntitiesData};
+use crate::json::{json_object, json_flags};
 
 /*
 this has all of the parsing for net/svc message types
@@ -114,15 +117,29 @@ impl NetSetConVar {
 pub struct NetSignonState {
     pub signon_state: i32,
     pub spawn_count: i32,
-    // theres more for demoprotocol 4 but i wont do it yet
+    // the rest only exists in demo protocol 4
+    pub num_server_players: Option<i32>,
+    pub player_network_ids: Option<Vec<u8>>,
+    pub map_name: Option<String>,
 }
 
 impl NetSignonState {
-    pub fn parse(reader: &mut BitReader) ->  Self {
-        Self {
-            signon_state: reader.read_int(8),
-            spawn_count: reader.read_int(32),
+    pub fn parse(reader: &mut BitReader, data_mgr: &DataManager) ->  Self {
+        let signon_state = reader.read_int(8);
+        let spawn_count = reader.read_int(32);
+
+        let mut num_server_players: Option<i32> = None;
+        let mut player_network_ids: Option<Vec<u8>> = None;
+        let mut map_name: Option<String> = None;
+        if data_mgr.demo_protocol == 4 {
+            num_server_players = Some(reader.read_int(32));
+            let ids_length = reader.read_int(32); // in bytes
+            player_network_ids = Some(reader.read_bytes(ids_length));
+            let map_name_length = reader.read_int(32); // also in bytes
+            map_name = Some(reader.read_ascii_string(map_name_length.saturating_mul(8)));
         }
+
+        Self { signon_state: signon_state, spawn_count: spawn_count, num_server_players: num_server_players, player_network_ids: player_network_ids, map_name: map_name }
     }
 }
 
@@ -134,6 +151,7 @@ pub struct SvcServerInfo {
     pub is_hltv: bool,
     pub is_dedicated: bool,
     pub client_crc: i32,
+    pub string_table_crc: Option<i32>, // only in demo protocol 4
     pub max_classes: i32,
     pub tick_interval: f32,
     pub map_crc: Option<i32>, // its either one or the other so theyre both an option
@@ -149,12 +167,16 @@ pub struct SvcServerInfo {
 }
 
 impl SvcServerInfo {
-    pub fn parse(reader: &mut BitReader, data_mgr: &DataManager) -> Self {
+    pub fn parse(reader: &mut BitReader, data_mgr: &mut DataManager) -> Self {
         let protocol = reader.read_int(16);
         let server_count = reader.read_int(32);
         let is_hltv = reader.read_bool();
         let is_dedicated = reader.read_bool();
         let client_crc = reader.read_int(32);
+        let mut string_table_crc: Option<i32> = None;
+        if data_mgr.demo_protocol == 4 {
+            string_table_crc = Some(reader.read_int(32));
+        }
         let max_classes =  reader.read_int(16);
         let mut map_crc: Option<i32> = None;
         let mut map_md5: Option<Vec<u8>> = None;
@@ -166,7 +188,8 @@ impl SvcServerInfo {
         let player_slot = reader.read_int(8);
         let max_clients = reader.read_int(8);
         let tick_interval = reader.read_float(32);
-        let platform = reader.read_ascii_string(8).chars().next().unwrap();
+        data_mgr.set_tick_interval(tick_interval, TickIntervalSource::ServerInfo);
+        let platform = reader.read_ascii_string(8).chars().next().unwrap_or('?'); // empty if the byte is 0 or the demo ends here
         let game_dir = reader.read_ascii_string_nulled();
         let map_name = reader.read_ascii_string_nulled();
         let sky_name = reader.read_ascii_string_nulled();
@@ -177,7 +200,11 @@ impl SvcServerInfo {
             has_replay = Some(reader.read_bool());
         }
 
-        Self { protocol: protocol, server_count: server_count, is_hltv: is_hltv, is_dedicated: is_dedicated, client_crc: client_crc,
+        // the header can be edited, this is where the server actually ran the game
+        data_mgr.game_characteristics.server_game_directory = Some(game_dir.clone());
+        data_mgr.detect_game();
+
+        Self { protocol: protocol, server_count: server_count, is_hltv: is_hltv, is_dedicated: is_dedicated, client_crc: client_crc, string_table_crc: string_table_crc,
             max_classes: max_classes, tick_interval: tick_interval, map_crc: map_crc, map_md5: map_md5, player_slot: player_slot,
             max_clients: max_clients, platform: platform, game_dir: game_dir, map_name: map_name, sky_name: sky_name, host_name: host_name, has_replay: has_replay,
         }
@@ -265,11 +292,11 @@ pub struct SvcCreateStringTable {
     pub user_data_size: Option<i32>,
     pub user_data_size_bits: Option<i32>,
     pub flags: StringTableFlags, // doesnt exist on 3420 so this is an Option
-    pub string_data: utils::StringTable, // placeholder for now
+    pub entries: Vec<StringTableUpdate>,
 }
 
 impl SvcCreateStringTable {
-    pub fn parse(reader: &mut BitReader, data_mgr: &DataManager) -> Self {
+    pub fn parse(reader: &mut BitReader, data_mgr: &mut DataManager) -> Self {
         let name = reader.read_ascii_string_nulled();
         let max_entries = reader.read_int(16);
         let num_entries = reader.read_int(log2_of_x_plus_one(max_entries));
@@ -289,8 +316,38 @@ impl SvcCreateStringTable {
             flags = StringTableFlags::from_bits_truncate(reader.read_int(if data_mgr.demo_protocol == 4 { 2 } else { 1 }));
         }
 
-        let string_data: utils::StringTable = utils::StringTable::new(); // placeholder
-        reader.skip(length as i32); // skip bits for now
+        let data_index = reader.current;
+        let mut table = StringTable::new(name.clone(), max_entries, user_data_fixed_size, user_data_size.unwrap_or(0), user_data_size_bits.unwrap_or(0));
+
+        let entries = if flags.contains(StringTableFlags::DataCompressed) {
+            let _uncompressed_size = reader.read_int(32);
+            let compressed_size = reader.read_int(32);
+            let compressed_data = reader.read_bytes(compressed_size);
+            match decompress_lzss(&compressed_data) {
+                Some(mut data) => {
+                    // the bitreader needs at least 8 bytes to work with
+                    while data.len() < 8 {
+                        data.push(0);
+                    }
+                    table.parse_update(&mut BitReader::new(data), num_entries, data_mgr.demo_protocol)
+                },
+                None => Vec::new(),
+            }
+        } else {
+            table.parse_update(reader, num_entries, data_mgr.demo_protocol)
+        };
+
+        reader.current = data_index + length as usize;
+        reader.fetch();
+
+        // the table id that SvcUpdateStringTable uses is just the order the tables were created in
+        match data_mgr.stringtables.iter().position(|t| t.name == name) {
+            Some(index) => data_mgr.stringtables[index] = table,
+            None => data_mgr.stringtables.push(table),
+        }
+        if name == "instancebaseline" {
+            data_mgr.update_instance_baselines();
+        }
 
         Self {
             name: name,
@@ -301,7 +358,7 @@ impl SvcCreateStringTable {
             user_data_size: user_data_size,
             user_data_size_bits: user_data_size_bits,
             flags: flags,
-            string_data: string_data,
+            entries: entries,
         }        
     }
 }
@@ -320,22 +377,37 @@ pub struct SvcUpdateStringTable {
     pub table_id: i32,
     pub num_changed_entries: i32,
     pub length: i32,
-    pub data: utils::StringTable, // also just a placeholder
+    pub entries: Vec<StringTableUpdate>,
 }
 
 impl SvcUpdateStringTable {
-    pub fn parse(reader: &mut BitReader) -> Self {
+    pub fn parse(reader: &mut BitReader, data_mgr: &mut DataManager) -> Self {
         let table_id = reader.read_int(5);
-        let num_changed_entries = reader.read_int_if_exists(16).unwrap_or_else(|| {1});
+        let num_changed_entries = reader.read_int_if_exists(16).unwrap_or(1);
 
         let length = reader.read_int(20);
-        let data = utils::StringTable::new(); // nothin
-        reader.skip(length as i32);
+        let data_index = reader.current;
+        let demo_protocol = data_mgr.demo_protocol;
+
+        let mut entries: Vec<StringTableUpdate> = Vec::new();
+        let mut table_name = String::new();
+        if let Some(table) = data_mgr.stringtables.get_mut(table_id as usize) {
+            entries = table.parse_update(reader, num_changed_entries, demo_protocol);
+            table_name = table.name.clone();
+        }
+
+        reader.current = data_index + length as usize;
+        reader.fetch();
+
+        if table_name == "instancebaseline" {
+            data_mgr.update_instance_baselines();
+        }
+
         Self {
             table_id: table_id,
             num_changed_entries: num_changed_entries,
             length: length,
-            data: data,
+            entries: entries,
         }
     }
 }
@@ -384,7 +456,7 @@ impl SvcVoiceData {
         let data: utils::VoiceData = utils::VoiceData::new();
 
         // skip bits
-        reader.skip(length as i32);
+        reader.skip(length);
 
         Self {
             client: client,
@@ -418,7 +490,7 @@ pub struct SvcSounds {
 }
 
 impl SvcSounds {
-    pub fn parse(reader: &mut BitReader) -> Self {
+    pub fn parse(reader: &mut BitReader, data_mgr: &DataManager) -> Self {
         let reliable_sounds = reader.read_bool();
 
         let num_sounds: i32;
@@ -432,8 +504,17 @@ impl SvcSounds {
             length = reader.read_int(16);
         }
 
-        let data: Vec<utils::SoundInfo> = Vec::new(); // placeholder
-        reader.skip(length as i32); // skip
+        let data_index = reader.current;
+        let mut data: Vec<utils::SoundInfo> = Vec::new();
+        let mut delta = utils::SoundInfo::new();
+        for _ in 0..num_sounds {
+            let sound = utils::SoundInfo::parse(reader, data_mgr, &delta);
+            data.push(sound.clone());
+            delta = sound;
+        }
+
+        reader.current = data_index + length as usize;
+        reader.fetch();
 
         Self { reliable_sound: reliable_sounds, num_sounds: num_sounds, length: length, data: data }
     }
@@ -529,7 +610,7 @@ impl SvcSplitScreen {
         let length = reader.read_int(11);
         
         let data: utils::SplitScreenData = utils::SplitScreenData::new(); // placeholder
-        reader.skip(length as i32); // skip
+        reader.skip(length); // skip
 
         Self { s_type: s_type, length: length, data: data }
     }
@@ -544,14 +625,16 @@ pub struct SvcUserMessage {
 }
 
 impl SvcUserMessage {
-    pub fn parse(reader: &mut BitReader, user_message_event_list: Vec<UserMessageType>) -> Self {
+    pub fn parse(reader: &mut BitReader, data_mgr: &mut DataManager) -> Self {
         let msg_type = reader.read_int(8);
-        let length = reader.read_int(11);
-        if msg_type >= user_message_event_list.len() as i32 {
-            reader.skip(length);
-            Self { length: length, data: UserMessage::new() }
-        } else {
-            Self { length: length, data: UserMessage::parse(reader, user_message_event_list[msg_type as usize], length)}
+        let length = reader.read_int(if data_mgr.demo_protocol == 4 { 12 } else { 11 });
+        data_mgr.game_characteristics.add_user_message(msg_type);
+        match data_mgr.user_message_list.get(msg_type as usize) {
+            Some(user_message_type) => Self { length: length, data: UserMessage::parse(reader, *user_message_type, length) },
+            None => {
+                reader.skip(length);
+                Self { length: length, data: UserMessage::new() }
+            },
         }
     }
 }
@@ -568,13 +651,12 @@ pub struct SvcEntityMessage {
 }
 
 impl SvcEntityMessage {
-    pub fn parse(reader: &mut BitReader) -> Self {
+    pub fn parse(reader: &mut BitReader, data_mgr: &DataManager) -> Self {
         let entity_index = reader.read_int(11);
         let class_id = reader.read_int(9);
         let length = reader.read_int(11);
 
-        let data: utils::EntityMessageData = utils::EntityMessageData::new(); // placeholder
-        reader.skip(length as i32); // skip
+        let data = utils::EntityMessageData::parse(reader, data_mgr, class_id, length);
 
         Self { entity_index: entity_index, class_id: class_id, length: length, data: data }
     }
@@ -629,11 +711,12 @@ pub struct SvcPacketEntities {
     pub updated_entries: i32,
     pub length: i32,
     pub update_baseline: bool,
-    pub data: utils::PacketEntitiesData,
+    pub data_index: usize, // where the entity data starts in the file, used if we have to come back to it later
+    pub data: Option<PacketEntitiesData>, // None if we couldnt read it (yet)
 }
 
 impl SvcPacketEntities {
-    pub fn parse(reader: &mut BitReader) -> Self {
+    pub fn parse(reader: &mut BitReader, data_mgr: &mut DataManager) -> Self {
         let max_entries = reader.read_int(11);
         let is_delta = reader.read_bool();
         let mut delta_from: Option<i32> = None;
@@ -644,9 +727,21 @@ impl SvcPacketEntities {
         let updated_entries = reader.read_int(11);
         let length = reader.read_int(20);
         let update_baseline = reader.read_bool();
-        let data:utils::PacketEntitiesData = utils::PacketEntitiesData::new(); // placeholder
-        reader.skip(length as i32); // skip
-        Self {
+        let data_index = reader.current;
+
+        // we can only read entities if we have the datatables
+        // the first few of these come in signon packets before the datatables packet so those get read in parser.rs once we have them
+        let mut data: Option<PacketEntitiesData> = None;
+        if !data_mgr.dt_mgr.prop_lookup.is_empty() {
+            data = Some(PacketEntitiesData::parse(reader, data_mgr, updated_entries, is_delta));
+            // always end up right after the entity data even if we didnt manage to read all of it
+            reader.current = data_index + length as usize;
+            reader.fetch();
+        } else {
+            reader.skip(length);
+        }
+
+        let msg = Self {
             max_entries: max_entries,
             is_delta: is_delta,
             delta_from: delta_from,
@@ -654,9 +749,13 @@ impl SvcPacketEntities {
             updated_entries: updated_entries,
             length: length,
             update_baseline: update_baseline,
+            data_index: data_index,
             data: data,
-        }
+        };
+
+        data_mgr.apply_packet_entities(&msg);
 
+        return msg;
     }
 }
 
@@ -664,15 +763,24 @@ impl SvcPacketEntities {
 pub struct SvcTempEntities {
     pub num_entries: i32,
     pub length: i32,
-    pub data: utils::TempEntitiesData,
+    pub data: Option<TempEntitiesData>, // None if we dont have the datatables
 }
 
 impl SvcTempEntities {
     pub fn parse(reader: &mut BitReader, data_mgr: &DataManager) -> Self {
         let num_entires = reader.read_int(8);
         let length = if data_mgr.game == Game::PORTAL_1_1910503 { reader.read_var_int32() } else { reader.read_int(17) };
-        let data: utils::TempEntitiesData = utils::TempEntitiesData::new();
-        reader.skip(length as i32);
+        let data_index = reader.current;
+
+        let mut data: Option<TempEntitiesData> = None;
+        if !data_mgr.dt_mgr.prop_lookup.is_empty() {
+            data = Some(TempEntitiesData::parse(reader, data_mgr, num_entires));
+            reader.current = data_index + length as usize;
+            reader.fetch();
+        } else {
+            reader.skip(length);
+        }
+
         Self { num_entries: num_entires, length: length, data: data }
     }
 }
@@ -689,12 +797,8 @@ impl SvcPrefetch {
         
         let mut sound_name: String = "None".to_string();
 
-        // todo: make this better cause this is just awful lmao
-        for i in &data_mgr.stringtables {
-            if i.name == "soundprecache".to_string() {
-                sound_name = i.table_entries[sound_index as usize].clone().name;
-                break;
-            }
+        if let Some(entry) = data_mgr.get_stringtable("soundprecache").and_then(|t| t.table_entries.get(sound_index as usize)) {
+            sound_name = entry.name.clone();
         }
 
         Self { sound_index: sound_index, sound_name: sound_name }
@@ -767,7 +871,7 @@ impl SvcCmdKeyValues {
     pub fn parse(reader: &mut BitReader) -> Self {
         let length = reader.read_int(32);
         let data: utils::CmdKeyValuesData = utils::CmdKeyValuesData::new();
-        reader.skip((length*8) as i32);
+        reader.skip(length * 8);
 
         Self { length: length, data: data }
     }
@@ -783,8 +887,45 @@ impl SvcPaintmapData {
     pub fn parse(reader: &mut BitReader) -> Self {
         let length = reader.read_int(32);
         let data: utils::PaintmapData = utils::PaintmapData::new();
-        reader.skip(length as i32);
+        reader.skip(length);
 
         Self { length: length, data: data }
     }
-}
\ No newline at end of file
+}
+
+// json export, see json.rs
+json_object!(NetNop {});
+json_object!(NetDisconnect { text });
+json_object!(NetFile { transfer_id, filename, file_requested });
+json_object!(NetSplitScreenUser { unknown });
+json_object!(NetTick { tick, host_frame_time, host_frame_time_standard_deviation });
+json_object!(NetStringCmd { command });
+json_object!(NetSetConVar { length, convars });
+json_object!(NetSignonState { signon_state, spawn_count, num_server_players, player_network_ids, map_name });
+json_object!(SvcServerInfo { protocol, server_count, is_hltv, is_dedicated, client_crc, string_table_crc, max_classes, tick_interval, map_crc, map_md5, player_slot, max_clients, platform, game_dir, map_name, sky_name, host_name, has_replay });
+json_object!(SvcSendTable { needs_decoder, length, props });
+json_object!(SvcClassInfo { length, create_on_client, server_classes });
+json_object!(SvcSetPause { paused });
+json_object!(SvcCreateStringTable { name, max_entries, num_entries, length, user_data_fixed_size, user_data_size, user_data_size_bits, flags, entries });
+json_object!(SvcUpdateStringTable { table_id, num_changed_entries, length, entries });
+json_object!(SvcVoiceInit { codec, quality, sample_rate });
+json_object!(SvcVoiceData { client, proximity, length, audible, data });
+json_object!(SvcPrint { message });
+json_object!(SvcSounds { reliable_sound, num_sounds, length, data });
+json_object!(SvcSetView { entity_index });
+json_object!(SvcFixAngle { relative, angle });
+json_object!(SvcCrosshairAngle { angle });
+json_object!(SvcBspDecal { pos, decal_texture_index, entity_index, model_index, low_priority });
+json_object!(SvcSplitScreen { s_type, length, data });
+json_object!(SvcUserMessage { length, data });
+json_object!(SvcEntityMessage { entity_index, class_id, length, data });
+json_object!(SvcGameEvent { length, data });
+json_object!(SvcPacketEntities { max_entries, is_delta, delta_from, base_line, updated_entries, length, update_baseline, data });
+json_object!(SvcTempEntities { num_entries, length, data });
+json_object!(SvcPrefetch { sound_index, sound_name });
+json_object!(SvcMenu { menu_type, length, data });
+json_object!(SvcGameEventList { events, length });
+json_object!(SvcGetCvarValue { cookie, cvar_name });
+json_object!(SvcCmdKeyValues { length, data });
+json_object!(SvcPaintmapData { length, data });
+json_flags!(StringTableFlags);
@@ -1,4 +1,5 @@
 use crate::structs::packet_data_types as pdt;
+use crate::json::{JsonValue, ToJson, json_debug_string};
 
 // packets are how all of the demos data (except the header) is stored
 // for every packet there is its type, its tick, its slot (only on demo protocol 4), and its data (see packet_data_types.rs)
@@ -14,10 +15,19 @@ pub enum PacketType {
     DataTables = 6,
     Stop = 7,
     StringTables = 8,
+    CustomData = 9, // only in demo protocol 4, where its 8 and stringtables is 9
 }
 
 impl PacketType {
-    pub fn from_int(value: i32) -> Self {
+    pub fn from_int(value: i32, demo_protocol: i32) -> Self {
+        if demo_protocol == 4 {
+            match value {
+                8 => return PacketType::CustomData,
+                9 => return PacketType::StringTables,
+                _ => (),
+            }
+        }
+
         match value {
             1 => PacketType::SignOn,
             2 => PacketType::Packet,
@@ -42,51 +52,74 @@ pub enum PacketDataType {
     DataTables(pdt::DataTables),
     Stop(pdt::Stop),
     StringTables(pdt::StringTables),
+    CustomData(pdt::CustomData),
 }
 
-// all the into<> are required to later extrapolate the data from the enum
-impl Into<pdt::PP> for PacketDataType {
-    fn into(self) -> pdt::PP {
-        match self {
-            PacketDataType::Packet(value) => value,
-            _ => panic!("what the hell are you trying to do???"),
+// all the try_from<> are required to later extrapolate the data from the enum
+// if you ask for the wrong type you get the original value back instead of a panic
+impl TryFrom<PacketDataType> for pdt::PP {
+    type Error = PacketDataType;
+
+    fn try_from(value: PacketDataType) -> Result<Self, Self::Error> {
+        match value {
+            PacketDataType::Packet(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<pdt::ConsoleCmd> for PacketDataType {
-    fn into(self) -> pdt::ConsoleCmd {
-        match self {
-            PacketDataType::ConsoleCmd(value) => value,
-            _ => panic!("what the hell are you trying to do???"),
+impl TryFrom<PacketDataType> for pdt::ConsoleCmd {
+    type Error = PacketDataType;
+
+    fn try_from(value: PacketDataType) -> Result<Self, Self::Error> {
+        match value {
+            PacketDataType::ConsoleCmd(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<pdt::UserCmd> for PacketDataType {
-    fn into(self) -> pdt::UserCmd {
-        match self {
-            PacketDataType::UserCmd(value) => value,
-            _ => panic!("what the hell are you trying to do???"),
+impl TryFrom<PacketDataType> for pdt::UserCmd {
+    type Error = PacketDataType;
+
+    fn try_from(value: PacketDataType) -> Result<Self, Self::Error> {
+        match value {
+            PacketDataType::UserCmd(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<pdt::DataTables> for PacketDataType {
-    fn into(self) -> pdt::DataTables {
-        match self {
-            PacketDataType::DataTables(value) => value,
-            _ => panic!("what the hell are you trying to do???"),
-        }        
+impl TryFrom<PacketDataType> for pdt::DataTables {
+    type Error = PacketDataType;
+
+    fn try_from(value: PacketDataType) -> Result<Self, Self::Error> {
+        match value {
+            PacketDataType::DataTables(value) => Ok(value),
+            other => Err(other),
+        }
     }
 }
 
-impl Into<pdt::StringTables> for PacketDataType {
-    fn into(self) -> pdt::StringTables {
-        match self {
-            PacketDataType::StringTables(value) => value,
-            _ => panic!("what the hell are you trying to do???"),
-        }        
+impl TryFrom<PacketDataType> for pdt::StringTables {
+    type Error = PacketDataType;
+
+    fn try_from(value: PacketDataType) -> Result<Self, Self::Error> {
+        match value {
+            PacketDataType::StringTables(value) => Ok(value),
+            other => Err(other),
+        }
+    }
+}
+
+impl TryFrom<PacketDataType> for pdt::CustomData {
+    type Error = PacketDataType;
+
+    fn try_from(value: PacketDataType) -> Result<Self, Self::Error> {
+        match value {
+            PacketDataType::CustomData(value) => Ok(value),
+            other => Err(other),
+        }
     }
 }
 
@@ -103,3 +136,33 @@ impl Packet {
         Self { packet_type: PacketType::Unknown, tick: 0, slot: None, data: PacketDataType::Unknown }
     }
 }
+
+// json export, see json.rs
+json_debug_string!(PacketType);
+
+impl ToJson for PacketDataType {
+    fn to_json(&self) -> JsonValue {
+        return match self {
+            PacketDataType::Unknown => JsonValue::Null,
+            PacketDataType::Packet(data) => data.to_json(),
+            PacketDataType::SyncTick(data) => data.to_json(),
+            PacketDataType::ConsoleCmd(data) => data.to_json(),
+            PacketDataType::UserCmd(data) => data.to_json(),
+            PacketDataType::DataTables(data) => data.to_json(),
+            PacketDataType::Stop(data) => data.to_json(),
+            PacketDataType::StringTables(data) => data.to_json(),
+            PacketDataType::CustomData(data) => data.to_json(),
+        };
+    }
+}
+
+impl ToJson for Packet {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::object(vec![
+            ("type", self.packet_type.to_json()),
+            ("tick", self.tick.to_json()),
+            ("slot", self.slot.to_json()),
+            ("data", self.data.to_json()),
+        ]);
+    }
+}
@@ -4,6 +4,7 @@ use crate::structs::net_svc_message::NetSvcMessage;
 use crate::structs::stringtable::StringTable;
 use crate::structs::send_table::SendTable;
 use crate::structs::utils::ServerClass;
+use crate::json::{json_object};
 
 // just definitions for all the packet types, should move parsing here too
 
@@ -16,6 +17,7 @@ use crate::structs::utils::ServerClass;
 #[derive(Debug)]
 pub struct PP {
     pub cmd_info: CmdInfo,
+    pub split_screen_cmd_info: Option<CmdInfo>, // the second player in split screen, only in demo protocol 4
     pub in_sequence: i32,
     pub out_sequence: i32,
     pub size: i32,
@@ -24,7 +26,7 @@ pub struct PP {
 
 impl PP {
     pub fn new() -> Self {
-        Self { cmd_info: CmdInfo::new(), in_sequence: 0, out_sequence: 0, size: 0, messages: Vec::new() }
+        Self { cmd_info: CmdInfo::new(), split_screen_cmd_info: None, in_sequence: 0, out_sequence: 0, size: 0, messages: Vec::new() }
     }
 }
 
@@ -96,3 +98,28 @@ impl StringTables {
         Self { size: 0, table_count: 0, tables: Vec::new() }
     }
 }
+
+// only in demo protocol 4, data for a callback the game registered on the client
+// in portal 2 its only ever the radial menu (pinging and gestures in coop)
+#[derive(Debug)]
+pub struct CustomData {
+    pub callback_index: i32,
+    pub size: i32,
+    pub data: Vec<u8>,
+}
+
+impl CustomData {
+    pub fn new() -> Self {
+        Self { callback_index: 0, size: 0, data: Vec::new() }
+    }
+}
+
+// json export, see json.rs
+json_object!(PP { cmd_info, split_screen_cmd_info, in_sequence, out_sequence, size, messages });
+json_object!(SyncTick {});
+json_object!(ConsoleCmd { size, data });
+json_object!(UserCmd { cmd, size, data });
+json_object!(DataTables { size, send_table_count, send_tables, class_count, server_classes });
+json_object!(Stop {});
+json_object!(StringTables { size, table_count, tables });
+json_object!(CustomData { callback_index, size, data });
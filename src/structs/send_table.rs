@@ -4,6 +4,7 @@ use crate::structs::data_manager::DataManager;
 use std::fs::File;
 use std::io::Write;
 use crate::structs::utils::bitflags_to_string;
+use crate::json::{json_object, json_debug_string, json_flags};
 
 #[derive(Debug, Clone)]
 pub struct SendTable {
@@ -43,14 +44,21 @@ pub struct SendTableProp {
     pub high_value: Option<f32>,
     pub num_bits: Option<i32>,
     pub num_elements: Option<i32>,
+    pub priority: Option<i32>, // only demo protocol 4 has it
     pub float_parse_type: FloatParseType
 }
 
 impl SendTableProp {
     pub fn parse(reader: &mut BitReader, data_mgr: &DataManager) -> Self {
-        let send_prop_type: SendPropType = data_mgr.send_prop_type_list[reader.read_int(5) as usize].clone();
+        // only out of range with the wrong game profile, which gets found out once the server classes are read
+        let send_prop_type: SendPropType = data_mgr.send_prop_type_list.get(reader.read_int(5) as usize).cloned().unwrap_or(SendPropType::DataTable);
         let name = reader.read_ascii_string_nulled();
-        let flags = PropFlag::from_bits_truncate(reader.read_int(16)); // hardcoded bc im not doing anything other than p1 for now
+        let (flags, priority) = if data_mgr.demo_protocol == 4 {
+            // the priority decides where the prop ends up in the flattened props, see DataTablesManager::sort_props
+            (PropFlag::from_new_engine_bits(reader.read_int(19)), Some(reader.read_int(8)))
+        } else {
+            (PropFlag::from_bits_truncate(reader.read_int(16)), None)
+        };
         
         let mut exclude_dt_name: Option<String> = None;
         let mut low_value: Option<f32> = None;
@@ -70,10 +78,17 @@ impl SendTableProp {
                 SendPropType::Array => {
                     num_elements = Some(reader.read_int(10));
                 },
-                _ => panic!("lmao")
+                _ => {}, // nothing else to read
             }
         }
 
+        // figured out once here so the entity parsing code doesnt have to look at the flags every time
+        let float_parse_type = if send_prop_type == SendPropType::Float || send_prop_type == SendPropType::Vector3 || send_prop_type == SendPropType::Vector2 {
+            FloatParseType::from_flags(&flags)
+        } else {
+            FloatParseType::None
+        };
+
         Self {
             send_prop_type: send_prop_type,
             name: name,
@@ -83,7 +98,8 @@ impl SendTableProp {
             high_value: high_value,
             num_bits: num_bits,
             num_elements: num_elements,
-            float_parse_type: FloatParseType::None,
+            priority: priority,
+            float_parse_type: float_parse_type,
         }
     }
 }
@@ -101,16 +117,16 @@ enum_from_primitive! {
     }
 }
 
-impl Into<&str> for SendPropType {
-    fn into(self) -> &'static str {
-        match self {
-            Self::Int => "Int",
-            Self::Float => "Float",
-            Self::Vector2 => "Vector2",
-            Self::Vector3 => "Vector3",
-            Self::String => "String",
-            Self::Array => "Array",
-            Self::DataTable => "DataTable",
+impl From<SendPropType> for &str {
+    fn from(value: SendPropType) -> Self {
+        match value {
+            SendPropType::Int => "Int",
+            SendPropType::Float => "Float",
+            SendPropType::Vector2 => "Vector2",
+            SendPropType::Vector3 => "Vector3",
+            SendPropType::String => "String",
+            SendPropType::Array => "Array",
+            SendPropType::DataTable => "DataTable",
         }
     }
 }
@@ -134,6 +150,34 @@ bitflags::bitflags! {
         const CoordMp = 1 << 13;
         const CoordMpLp = 1 << 14; // low precision
         const CoordMpInt = 1 << 15;
+        const CellCoord = 1 << 16; // the cell coord ones only exist in demo protocol 4
+        const CellCoordLp = 1 << 17;
+        const CellCoordInt = 1 << 18;
+    }
+}
+
+impl PropFlag {
+    // demo protocol 4 moved ChangesOften to the end to make room for the cell coord flags
+    // this puts everything back where the old engine has it so the rest of the code only deals with one layout
+    pub fn from_new_engine_bits(bits: i32) -> Self {
+        let mut flags = PropFlag::from_bits_truncate(bits & 0x3FF); // the first 10 are the same
+        let moved = [
+            (10, PropFlag::IsVectorElem),
+            (11, PropFlag::Collapsible),
+            (12, PropFlag::CoordMp),
+            (13, PropFlag::CoordMpLp),
+            (14, PropFlag::CoordMpInt),
+            (15, PropFlag::CellCoord),
+            (16, PropFlag::CellCoordLp),
+            (17, PropFlag::CellCoordInt),
+            (18, PropFlag::ChangesOften),
+        ];
+        for (bit, flag) in moved {
+            if bits & (1 << bit) != 0 {
+                flags |= flag;
+            }
+        }
+        return flags;
     }
 }
 
@@ -152,6 +196,33 @@ pub enum FloatParseType {
     BitCellChordInt,
 }
 
+impl FloatParseType {
+    // the order matters here, its the same order source checks the flags in
+    pub fn from_flags(flags: &PropFlag) -> Self {
+        if flags.contains(PropFlag::Coord) {
+            FloatParseType::Coord
+        } else if flags.contains(PropFlag::CoordMp) {
+            FloatParseType::BitCoordMp
+        } else if flags.contains(PropFlag::CoordMpLp) {
+            FloatParseType::BitCoordMpLp
+        } else if flags.contains(PropFlag::CoordMpInt) {
+            FloatParseType::BitCoordMpInt
+        } else if flags.contains(PropFlag::NoScale) {
+            FloatParseType::NoScale
+        } else if flags.contains(PropFlag::Normal) {
+            FloatParseType::Normal
+        } else if flags.contains(PropFlag::CellCoord) {
+            FloatParseType::BitCellChord
+        } else if flags.contains(PropFlag::CellCoordLp) {
+            FloatParseType::BitCellChordLp
+        } else if flags.contains(PropFlag::CellCoordInt) {
+            FloatParseType::BitCellChordInt
+        } else {
+            FloatParseType::Standard
+        }
+    }
+}
+
 // this function will make you wonder why i chose programming instead of something else as a hobby
 #[allow(unused)]
 pub fn write_send_table_data_to_file(file: &mut File, table: SendTable) {
@@ -200,4 +271,10 @@ pub fn write_send_table_data_to_file(file: &mut File, table: SendTable) {
 
         file.write_fmt(format_args!("\n\t\t\t{}", base_str));
     }
-}
\ No newline at end of file
+}
+
+// json export, see json.rs
+json_object!(SendTable { needs_decoder, name, prop_count, prop_list });
+json_object!(SendTableProp { send_prop_type, name, flags, exclude_dt_name, low_value, high_value, num_bits, num_elements, priority, float_parse_type });
+json_debug_string!(SendPropType, FloatParseType);
+json_flags!(PropFlag);
@@ -3,7 +3,9 @@ use std::io::Write;
 use bitflags::bitflags;
 use crate::bitreader::BitReader;
 use crate::structs::packet_data_types::StringTables;
-use crate::structs::utils::bitflags_to_string;
+use crate::structs::utils::{bitflags_to_string, log2_of_x_plus_one};
+use crate::structs::data_manager::DataManager;
+use crate::json::{JsonValue, ToJson, json_object, json_flags};
 
 // all of this information is from UntitledParser
 
@@ -18,9 +20,105 @@ pub struct StringTable {
     pub class_count: i32,
     pub table_entries: Vec<StringTableEntry>,
     pub classes: Vec<StringTableClass>,
+    // these only come from SvcCreateStringTable and are needed to read updates to the table
+    pub max_entries: i32,
+    pub user_data_fixed_size: bool,
+    pub user_data_size: i32,
+    pub user_data_size_bits: i32,
 }
 
 impl StringTable {
+    pub fn new(name: String, max_entries: i32, user_data_fixed_size: bool, user_data_size: i32, user_data_size_bits: i32) -> Self {
+        Self {
+            name: name,
+            entry_count: 0,
+            class_count: 0,
+            table_entries: Vec::new(),
+            classes: Vec::new(),
+            max_entries: max_entries,
+            user_data_fixed_size: user_data_fixed_size,
+            user_data_size: user_data_size,
+            user_data_size_bits: user_data_size_bits,
+        }
+    }
+
+    // reads the entries from SvcCreateStringTable and SvcUpdateStringTable and applies them to the table
+    // returns the entries that changed
+    // https://github.com/UncraftedName/UntitledParser/blob/master/DemoParser/src/Parser/Components/Packets/StringTableUpdate.cs
+    pub fn parse_update(&mut self, reader: &mut BitReader, num_entries: i32, demo_protocol: i32) -> Vec<StringTableUpdate> {
+        let mut updates: Vec<StringTableUpdate> = Vec::new();
+        let entry_bits = log2_of_x_plus_one(self.max_entries) - 1;
+        let mut history: Vec<String> = Vec::new(); // the last 32 entry names, used to compress new names
+        let mut last_index: i32 = -1;
+
+        // doesnt seem to ever be used in demos
+        if demo_protocol == 4 && reader.read_bool() {
+            return updates;
+        }
+
+        for _ in 0..num_entries {
+            let index = if reader.read_bool() { last_index + 1 } else { reader.read_int(entry_bits) };
+            last_index = index;
+
+            if index < 0 || index >= self.max_entries {
+                break;
+            }
+
+            let mut name: Option<String> = None;
+            if reader.read_bool() {
+                if reader.read_bool() {
+                    // the start of this name is the same as the start of an earlier one
+                    let history_index = reader.read_int(5) as usize;
+                    let bytes_to_copy = reader.read_int(5) as usize;
+                    let mut new_name: String = history.get(history_index).map(|n| n.chars().take(bytes_to_copy).collect()).unwrap_or_default();
+                    new_name.push_str(&reader.read_ascii_string_nulled());
+                    name = Some(new_name);
+                } else {
+                    name = Some(reader.read_ascii_string_nulled());
+                }
+            }
+
+            let mut entry_data = StringTableEntryDataTypes::None;
+            if reader.read_bool() {
+                let (length, bits) = if self.user_data_fixed_size {
+                    (self.user_data_size, self.user_data_size_bits)
+                } else {
+                    let length = reader.read_int(14);
+                    (length, length * 8)
+                };
+                let data_start = reader.current;
+                entry_data = StringTableEntry::new().parse_entry_data(reader, self.name.clone(), length);
+                reader.current = data_start + bits as usize;
+                reader.fetch();
+            }
+
+            // source puts the name the entry ends up with into the history, for existing entries thats their current name
+            let history_name = if (index as usize) < self.table_entries.len() {
+                // existing entries only get their data changed
+                let entry = &mut self.table_entries[index as usize];
+                if entry_data != StringTableEntryDataTypes::None {
+                    entry.entry_data = entry_data;
+                }
+                updates.push(StringTableUpdate { index: index, entry: entry.clone() });
+                entry.name.clone()
+            } else {
+                let entry = StringTableEntry { name: name.unwrap_or_default(), entry_data: entry_data };
+                self.table_entries.push(entry.clone());
+                let history_name = entry.name.clone();
+                updates.push(StringTableUpdate { index: index, entry: entry });
+                history_name
+            };
+
+            if history.len() > 31 {
+                history.remove(0);
+            }
+            history.push(history_name);
+        }
+
+        self.entry_count = self.table_entries.len() as i32;
+        return updates;
+    }
+
     pub fn parse(reader: &mut BitReader) -> Self {
         let name = reader.read_ascii_string_nulled();
         let entry_count = reader.read_int(16);
@@ -43,10 +141,27 @@ impl StringTable {
             }
         }
 
-        Self { name: name, entry_count: entry_count, class_count: class_count, table_entries: table_entries, classes: classes }
+        Self {
+            name: name,
+            entry_count: entry_count,
+            class_count: class_count,
+            table_entries: table_entries,
+            classes: classes,
+            max_entries: 0,
+            user_data_fixed_size: false,
+            user_data_size: 0,
+            user_data_size_bits: 0,
+        }
     }
 }
 
+// an entry that got added or changed by SvcCreateStringTable or SvcUpdateStringTable
+#[derive(Debug, Clone)]
+pub struct StringTableUpdate {
+    pub index: i32,
+    pub entry: StringTableEntry,
+}
+
 #[derive(Debug, Clone)]
 pub struct StringTableEntry {
     pub name: String,
@@ -77,6 +192,7 @@ impl StringTableEntry {
             "soundprecache" => StringTableEntryDataTypes::PrecacheData(PrecacheData::parse(reader)),
             "genericprecache" => StringTableEntryDataTypes::PrecacheData(PrecacheData::parse(reader)),
             "decalprecache" => StringTableEntryDataTypes::PrecacheData(PrecacheData::parse(reader)),
+            "instancebaseline" => StringTableEntryDataTypes::InstanceBaseline(InstanceBaseline::parse(reader, length)),
             _ => { reader.skip(length * 8); StringTableEntryDataTypes::Unknown }
         }
     }
@@ -92,6 +208,7 @@ pub enum StringTableEntryDataTypes {
     StringEntryData(StringEntryData),
     LightStyle(LightStyle),
     PrecacheData(PrecacheData),
+    InstanceBaseline(InstanceBaseline),
 }
 
 #[derive(Debug, Clone)]
@@ -174,7 +291,19 @@ impl QueryPort {
 }
 
 // has some data about entities
-pub struct InstanceBaseline; // not until i do datatable stuff
+// the entry name is a class id and the data is a list of props in the same format as in SvcPacketEntities
+// those are the values every entity of that class starts out with
+// we need the flattened classes to read them so only the bytes are stored here, see DataManager::update_instance_baselines
+#[derive(Debug, PartialEq, Clone)]
+pub struct InstanceBaseline {
+    pub data: Vec<u8>,
+}
+
+impl InstanceBaseline {
+    pub fn parse(reader: &mut BitReader, length: i32) -> Self {
+        Self { data: reader.read_bytes(length) }
+    }
+}
 
 // just strings
 #[derive(Debug, PartialEq, Clone)]
@@ -226,56 +355,66 @@ bitflags! {
     }
 }
 
-// impl into<type> for stringtableentrydatatypes is needed for dumping
+// impl try_from<stringtableentrydatatypes> for every type is needed for dumping
+
+impl TryFrom<StringTableEntryDataTypes> for PlayerInfo {
+    type Error = StringTableEntryDataTypes;
 
-impl Into<PlayerInfo> for StringTableEntryDataTypes {
-    fn into(self) -> PlayerInfo {
-        match self {
-            StringTableEntryDataTypes::PlayerInfo(value) => value,
-            _ => panic!("how are you even seeing this?"),
+    fn try_from(value: StringTableEntryDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            StringTableEntryDataTypes::PlayerInfo(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<LightStyle> for StringTableEntryDataTypes {
-    fn into(self) -> LightStyle {
-        match self {
-            StringTableEntryDataTypes::LightStyle(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<StringTableEntryDataTypes> for LightStyle {
+    type Error = StringTableEntryDataTypes;
+
+    fn try_from(value: StringTableEntryDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            StringTableEntryDataTypes::LightStyle(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<PrecacheData> for StringTableEntryDataTypes {
-    fn into(self) -> PrecacheData {
-        match self {
-            StringTableEntryDataTypes::PrecacheData(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<StringTableEntryDataTypes> for PrecacheData {
+    type Error = StringTableEntryDataTypes;
+
+    fn try_from(value: StringTableEntryDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            StringTableEntryDataTypes::PrecacheData(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<StringEntryData> for StringTableEntryDataTypes {
-    fn into(self) -> StringEntryData {
-        match self {
-            StringTableEntryDataTypes::StringEntryData(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<StringTableEntryDataTypes> for StringEntryData {
+    type Error = StringTableEntryDataTypes;
+
+    fn try_from(value: StringTableEntryDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            StringTableEntryDataTypes::StringEntryData(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<QueryPort> for StringTableEntryDataTypes {
-    fn into(self) -> QueryPort {
-        match self {
-            StringTableEntryDataTypes::QueryPort(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<StringTableEntryDataTypes> for QueryPort {
+    type Error = StringTableEntryDataTypes;
+
+    fn try_from(value: StringTableEntryDataTypes) -> Result<Self, Self::Error> {
+        match value {
+            StringTableEntryDataTypes::QueryPort(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
 
 #[allow(unused)]
-pub fn write_stringtables_data_to_file(file: &mut File, data: StringTables) {
+pub fn write_stringtables_data_to_file(file: &mut File, data: StringTables, data_mgr: &DataManager) {
     file.write_fmt(format_args!("\tData Size (bytes): {}", data.size));
     file.write_fmt(format_args!("\n\tTable Count: {}", data.table_count));
     for table in data.tables {
@@ -285,16 +424,16 @@ pub fn write_stringtables_data_to_file(file: &mut File, data: StringTables) {
             if entry.entry_data != StringTableEntryDataTypes::None {
                 if table.name.contains("precache") {
                     let mut base_str = "                                                                                          ".to_string();
-                    let entry_data: PrecacheData = entry.entry_data.into();
+                    let Ok(entry_data) = PrecacheData::try_from(entry.entry_data) else { continue; };
                     base_str.replace_range(0..entry.name.len(), &entry.name);
                     base_str.push_str("Flags: ");
                     base_str.push_str(&bitflags_to_string(entry_data.flags.iter_names()));
                     file.write_fmt(format_args!("\n\t\t\t{}", base_str));
                 } else if table.name == "GameRulesCreation" || table.name == "InfoPanel" {
-                    let entry_data: StringEntryData = entry.entry_data.into();
+                    let Ok(entry_data) = StringEntryData::try_from(entry.entry_data) else { continue; };
                     file.write_fmt(format_args!("\n\t\t\t{}: {}", entry.name, entry_data.str));
                 } else if table.name == "userinfo" {
-                    let entry_data: PlayerInfo = entry.entry_data.into();
+                    let Ok(entry_data) = PlayerInfo::try_from(entry.entry_data) else { continue; };
                     file.write_fmt(format_args!("\n\t\t\tEntry Name: {}", entry.name));
                     file.write_fmt(format_args!("\n\t\t\t\tName: {}", entry_data.name));
                     file.write_fmt(format_args!("\n\t\t\t\tUser ID: {}", entry_data.user_id));
@@ -307,11 +446,24 @@ pub fn write_stringtables_data_to_file(file: &mut File, data: StringTables) {
                                 entry_data.custom_files[0], entry_data.custom_files[1], entry_data.custom_files[2], entry_data.custom_files[3]));
                     file.write_fmt(format_args!("\n\t\t\t\tFiles Downloaded: {}", entry_data.files_downloaded));
                 } else if table.name == "server_query_info" {
-                    let entry_data: QueryPort = entry.entry_data.into();
+                    let Ok(entry_data) = QueryPort::try_from(entry.entry_data) else { continue; };
                     file.write_fmt(format_args!("\n\t\t\tEntry Name: {}", entry.name));
                     file.write_fmt(format_args!("\n\t\t\t\tPort: {}", entry_data.port));
+                } else if table.name == "instancebaseline" {
+                    let class_id = entry.name.parse::<i32>().unwrap_or(-1);
+                    let class_name = data_mgr.server_class_info.get(class_id as usize).map(|c| c.class_name.clone()).unwrap_or_else(|| "Unknown".to_string());
+                    file.write_fmt(format_args!("\n\t\t\t{} ({}):", entry.name, class_name));
+                    match data_mgr.get_instance_baseline(class_id) {
+                        Some(props) => {
+                            let f_props = &data_mgr.dt_mgr.prop_lookup[class_id as usize].1;
+                            for prop in props {
+                                file.write_fmt(format_args!("\n\t\t\t\t{}: {}", f_props[prop.index].name, prop.value));
+                            }
+                        },
+                        None => { file.write_all(" couldnt read baseline".as_bytes()); },
+                    }
                 } else if table.name == "lightstyles" {
-                    let entry_data: LightStyle = entry.entry_data.into();
+                    let Ok(entry_data) = LightStyle::try_from(entry.entry_data) else { continue; };
                     file.write_fmt(format_args!("\n\t\t\t{}: ", entry.name));
                     file.write_fmt(format_args!("{} frames: [", entry_data.values.len()));
                     let mut frame_str: String = "".to_string();
@@ -319,7 +471,7 @@ pub fn write_stringtables_data_to_file(file: &mut File, data: StringTables) {
                         frame_str.push_str(&frame.to_string());
                         frame_str.push_str(", ");
                     }
-                    file.write_fmt(format_args!("{}]", if frame_str != "" { frame_str[..frame_str.len()-2].to_string() } else { "".to_string() }));
+                    file.write_fmt(format_args!("{}]", if !frame_str.is_empty() { frame_str[..frame_str.len()-2].to_string() } else { "".to_string() }));
                 } else {
                     file.write_fmt(format_args!("\n\t\t\t{}", entry.name));
                 }
@@ -334,4 +486,40 @@ pub fn write_stringtables_data_to_file(file: &mut File, data: StringTables) {
         }
     }
     file.write_all("\n".as_bytes());
-}
\ No newline at end of file
+}
+
+#[allow(unused)]
+pub fn write_stringtable_updates_to_file(file: &mut File, entries: &[StringTableUpdate]) {
+    file.write_fmt(format_args!("\n\t\t{} entries:", entries.len()));
+    for update in entries {
+        file.write_fmt(format_args!("\n\t\t\t[{}] {}", update.index, update.entry.name));
+    }
+}
+
+// json export, see json.rs
+json_object!(StringTable { name, entry_count, class_count, table_entries, classes, max_entries, user_data_fixed_size, user_data_size, user_data_size_bits });
+json_object!(StringTableUpdate { index, entry });
+json_object!(StringTableEntry { name, entry_data });
+json_object!(StringTableClass { name, data });
+json_object!(PlayerInfo { name, user_id, guid, friends_id, friends_name, fake_player, is_hltv, custom_files, files_downloaded });
+json_object!(QueryPort { port });
+json_object!(InstanceBaseline { data });
+json_object!(StringEntryData { str });
+json_object!(LightStyle { values });
+json_object!(PrecacheData { flags });
+json_flags!(PrecacheFlags);
+
+impl ToJson for StringTableEntryDataTypes {
+    fn to_json(&self) -> JsonValue {
+        return match self {
+            StringTableEntryDataTypes::None => JsonValue::Null,
+            StringTableEntryDataTypes::Unknown => JsonValue::Null,
+            StringTableEntryDataTypes::PlayerInfo(data) => data.to_json(),
+            StringTableEntryDataTypes::QueryPort(data) => data.to_json(),
+            StringTableEntryDataTypes::StringEntryData(data) => data.to_json(),
+            StringTableEntryDataTypes::LightStyle(data) => data.to_json(),
+            StringTableEntryDataTypes::PrecacheData(data) => data.to_json(),
+            StringTableEntryDataTypes::InstanceBaseline(data) => data.to_json(),
+        };
+    }
+}
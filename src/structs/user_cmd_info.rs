@@ -1,11 +1,13 @@
 use bitflags::bitflags;
 
 use crate::bitreader::BitReader;
+use crate::json::{json_object, json_flags};
 
 // has info about the players view angles, how much they moved their mouse
 // what buttons they pressed etc.
 // dont know what the difference is between view angles here and in cmdinfo
 // all of the values in UserCmdInfo are Optional, meaning that the value exists only if the bit before its supposed value is 1; 
+// the ones that arent there didnt change since the last usercmd, analysis/inputs.rs fills them in
 
 #[derive(Debug)]
 pub struct UserCmdInfo {
@@ -17,7 +19,7 @@ pub struct UserCmdInfo {
     pub forward_move: Option<f32>,
     pub side_move: Option<f32>,
     pub up_move: Option<f32>,
-    pub buttons: Buttons,
+    pub buttons: Option<Buttons>,
     pub impulse: Option<i32>,
     pub weapon_select: Option<i32>,
     pub weapon_subtype: Option<i32>,
@@ -36,7 +38,7 @@ impl UserCmdInfo {
             forward_move: None,
             side_move: None,
             up_move: None,
-            buttons: Buttons::None,
+            buttons: None,
             impulse: None,
             weapon_select: None,
             weapon_subtype: None,
@@ -55,12 +57,12 @@ impl UserCmdInfo {
         let forward_move: Option<f32> = reader.read_float_if_exists(32);
         let side_move: Option<f32> = reader.read_float_if_exists(32);
         let up_move: Option<f32> = reader.read_float_if_exists(32);
-        let buttons: Buttons = Buttons::from_bits_truncate(reader.read_int_if_exists(32).unwrap_or(0));
+        let buttons: Option<Buttons> = reader.read_int_if_exists(32).map(Buttons::from_bits_truncate);
         let impulse: Option<i32> = reader.read_int_if_exists(8);
         let weapon_select: Option<i32> = reader.read_int_if_exists(11);
         let mut weapon_subtype: Option<i32> = None;
 
-        if weapon_select != None { weapon_subtype = Some(reader.read_int(6)) }
+        if weapon_select.is_some() { weapon_subtype = Some(reader.read_int(6)) }
 
         let mouse_dx: Option<i32> = reader.read_signed_int_if_exists(16);
         let mouse_dy: Option<i32> = reader.read_signed_int_if_exists(16);
@@ -86,7 +88,7 @@ impl UserCmdInfo {
 
 // thanks untitledparser :)
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy, PartialEq)]
     pub struct Buttons : i32 {
         const None            = 0;
 		const Attack          = 1;
@@ -122,4 +124,8 @@ bitflags! {
 		const Ability3        = 1 << 30;
 		const Ability4        = 1 << 31;
     }
-}
\ No newline at end of file
+}
+
+// json export, see json.rs
+json_object!(UserCmdInfo { command_number, tick_count, view_angles_x, view_angles_y, view_angles_z, forward_move, side_move, up_move, buttons, impulse, weapon_select, weapon_subtype, mouse_dx, mouse_dy });
+json_flags!(Buttons);
@@ -5,8 +5,8 @@ use std::io::Write;
 use crate::bitreader::BitReader;
 use crate::structs::utils::{Vec3, EHandle, bitflags_to_string};
 use crate::enum_primitive::enum_from_primitive;
-use crate::enum_primitive::FromPrimitive;
-use crate::bitflags::bitflags;
+use bitflags::bitflags;
+use crate::json::{JsonValue, ToJson, json_object, json_debug_string, json_flags};
 
 // these are for the SvcUserMessage net/svc message
 // theyre used to store various data, kind of functioning like a smaller Packet packet
@@ -14,40 +14,42 @@ use crate::bitflags::bitflags;
 // used in data_manager.rs
 // this is all the possible usermessage types (except l4d and l4d2) which i borrowed from untitledparser
 // only like 20% of these are actually implemented but ill have them all here cause why not
-#[allow(unused)]
-#[derive(Debug, Copy, Clone)]
-pub enum UserMessageType {
-    // book keeping
-    Unknown,
-    // 3420 types
-    Geiger,Train, HudText, SayText, SayText2, TextMsg,
-    HudMsg, ResetHUD, GameTitle, ItemPickup, ShowMenu,
-    Shake, Fade, VguiMenu, Rumble, Battery, Damage,
-    VoiceMask, RequestState, CloseCaption, HintText,
-    KeyHintText, SquadMemberDied, AmmoDenied, CreditsMsg,
-    CreditsPortalMsg, LogoTimeMsg, AchievementEvent,
-    EntityPortalled, KillCam,
-    // additional types from unpack
-    SPHapWeaponEvent, HapDmg,
-    HapPunch, HapSetDrag,
-    HapSetConstForce, HapMeleeContact,
-    // additional types from portal 2
-    Tilt, CloseCaptionDirect, UpdateJalopyRadar,
-    CurrentTimescale, DesiredTimescale, InventoryFlash,
-    IndicatorFlash, ControlHelperAnimate, TakePhoto,
-    Flash, HudPingIndicator, OpenRadialMenu,
-    AddLocator, MpMapCompleted, MpMapIncomplete,
-    MpMapCompletedData, MpTauntEarned, MpTauntUnlocked,
-    MpTauntLocked, MpAllTauntsLocked, PortalFXSurface,
-    PaintWorld, PaintEntity, ChangePaintColor, PaintBombExplode,
-    RemoveAllPaint, PaintAllSurfaces, RemovePaint,
-    StartSurvey, ApplyHitBoxDamageEffect, SetMixLayerTriggerFactor,
-    TransitionFade, ScoreboardTempUpdate, ChallengeModCheatSession,
-    ChallengeModCloseAllUI, 
-    // additional types from portal 1 steampipe
-    CallVoteFailed, VoteStart,
-    VotePass, VoteFailed,
-    VoteSetup,
+enum_from_primitive! {
+    #[allow(unused)]
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub enum UserMessageType {
+        // book keeping
+        Unknown,
+        // 3420 types
+        Geiger,Train, HudText, SayText, SayText2, TextMsg,
+        HudMsg, ResetHUD, GameTitle, ItemPickup, ShowMenu,
+        Shake, Fade, VguiMenu, Rumble, Battery, Damage,
+        VoiceMask, RequestState, CloseCaption, HintText,
+        KeyHintText, SquadMemberDied, AmmoDenied, CreditsMsg,
+        CreditsPortalMsg, LogoTimeMsg, AchievementEvent,
+        EntityPortalled, KillCam,
+        // additional types from unpack
+        SPHapWeaponEvent, HapDmg,
+        HapPunch, HapSetDrag,
+        HapSetConstForce, HapMeleeContact,
+        // additional types from portal 2
+        Tilt, CloseCaptionDirect, UpdateJalopyRadar,
+        CurrentTimescale, DesiredTimescale, InventoryFlash,
+        IndicatorFlash, ControlHelperAnimate, TakePhoto,
+        Flash, HudPingIndicator, OpenRadialMenu,
+        AddLocator, MpMapCompleted, MpMapIncomplete,
+        MpMapCompletedData, MpTauntEarned, MpTauntUnlocked,
+        MpTauntLocked, MpAllTauntsLocked, PortalFXSurface,
+        PaintWorld, PaintEntity, ChangePaintColor, PaintBombExplode,
+        RemoveAllPaint, PaintAllSurfaces, RemovePaint,
+        StartSurvey, ApplyHitBoxDamageEffect, SetMixLayerTriggerFactor,
+        TransitionFade, ScoreboardTempUpdate, ChallengeModCheatSession,
+        ChallengeModCloseAllUI, 
+        // additional types from portal 1 steampipe
+        CallVoteFailed, VoteStart,
+        VotePass, VoteFailed,
+        VoteSetup,
+    }
 }
 
 // all of the currently parseable types
@@ -187,10 +189,10 @@ impl Damage {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct EntityPortalled {
-    portal: EHandle,
-    portalled: EHandle,
-    new_position: Vec3,
-    new_angles: Vec3,
+    pub portal: EHandle, // the portal it went into
+    pub portalled: EHandle,
+    pub new_position: Vec3,
+    pub new_angles: Vec3,
 }
 
 impl EntityPortalled {
@@ -257,7 +259,7 @@ impl HudMsgInfo {
             x: reader.read_float(32), y: reader.read_float(32),
             r1: reader.read_int(8), g1: reader.read_int(8), b1: reader.read_int(8), a1: reader.read_int(8),
             r2: reader.read_int(8), g2: reader.read_int(8), b2: reader.read_int(8), a2: reader.read_int(8),
-            effect: HudMsgEffect::from_i32(reader.read_int(8)).unwrap(),
+            effect: HudMsgEffect::from_i32(reader.read_int(8)),
             fade_in: reader.read_float(32), fade_out: reader.read_float(32),
             hold_time: reader.read_float(32), fx_time: reader.read_float(32),
             message: reader.read_ascii_string_nulled(),
@@ -273,9 +275,9 @@ pub struct HudMsg {
 
 impl HudMsg {
     pub fn parse(reader: &mut BitReader) -> Self {
-        let channel = HudChannel::from_i32(reader.read_int(8) % 6).unwrap();
+        let channel = HudChannel::from_i32(reader.read_int(8) % 6);
         let mut msg_info: Option<HudMsgInfo> = None;
-        if reader.bit_size - reader.current as usize >= 148 {
+        if reader.bit_size - reader.current >= 148 {
             msg_info = Some(HudMsgInfo::parse(reader));
         }
 
@@ -317,7 +319,7 @@ pub struct KillCam {
 impl KillCam {
     pub fn parse(reader: &mut BitReader) -> Self {
         Self {
-            spec_mode: SpectatorMode::from_i32(reader.read_int(8)).unwrap(),
+            spec_mode: SpectatorMode::from_i32(reader.read_int(8)),
             target1: reader.read_int(8),
             target2: reader.read_int(8),
             unknown: reader.read_int(8),
@@ -384,7 +386,7 @@ impl PaintEntity {
     pub fn parse(reader: &mut BitReader) -> Self {
         Self {
             ent: reader.read_ehandle(),
-            paint_type: PaintType::from_i32(reader.read_int(8)).unwrap(),
+            paint_type: PaintType::from_i32(reader.read_int(8)),
             pos: reader.read_vec3(),
         }
     }
@@ -403,7 +405,7 @@ pub struct PaintWorld {
 
 impl PaintWorld {
     pub fn parse(reader: &mut BitReader) -> Self {
-        let paint_type = PaintType::from_i32(reader.read_int(8)).unwrap();
+        let paint_type = PaintType::from_i32(reader.read_int(8));
         let ehandle = EHandle { val: reader.read_int(32) };
         let unkhf1 = reader.read_float(32);
         let unkhf2 = reader.read_float(32);
@@ -428,13 +430,13 @@ impl PaintWorld {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct PortalFXSurface {
-    portal_ent: i32,
-    owner_ent: i32,
-    team: i32,
-    portal_num: i32,
-    effect: PortalFizzleType,
-    origin: Vec<Option<f32>>,
-    angles: Vec<Option<f32>>,
+    pub portal_ent: i32,
+    pub owner_ent: i32,
+    pub team: i32,
+    pub portal_num: i32,
+    pub effect: PortalFizzleType,
+    pub origin: Vec<Option<f32>>,
+    pub angles: Vec<Option<f32>>,
 }
 
 impl PortalFXSurface {
@@ -444,7 +446,7 @@ impl PortalFXSurface {
             owner_ent: reader.read_int(16),
             team: reader.read_int(8),
             portal_num: reader.read_int(8),
-            effect: PortalFizzleType::from_i32(reader.read_int(8)).unwrap(),
+            effect: PortalFizzleType::from_i32(reader.read_int(8)),
             origin: reader.read_vector_coords(),
             angles: reader.read_vector_coords(),
         }
@@ -472,7 +474,7 @@ pub struct Rumble {
 impl Rumble {
     pub fn parse(reader: &mut BitReader) -> Self {
         Self {
-            rumble_type: RumbleLookup::from_i32(reader.read_int(8)).unwrap(),
+            rumble_type: RumbleLookup::from_i32(reader.read_int(8)),
             scale: reader.read_int(8) as f32 / 100.0,
             rumble_flags: RumbleFlags::from_bits_truncate(reader.read_int(8)),
         }
@@ -542,7 +544,7 @@ pub struct Shake {
 impl Shake {
     pub fn parse(reader: &mut BitReader) -> Self {
         Self {
-            command: ShakeCommand::from_i32(reader.read_int(8)).unwrap(),
+            command: ShakeCommand::from_i32(reader.read_int(8)),
             amplitude: reader.read_float(32),
             frequency: reader.read_float(32),
             duration: reader.read_float(32),
@@ -558,7 +560,7 @@ pub struct TextMsg {
 
 impl TextMsg {
     pub fn parse(reader: &mut BitReader) -> Self {
-        let destination = TextMsgDestination::from_i32(reader.read_int(8)).unwrap();
+        let destination = TextMsgDestination::from_i32(reader.read_int(8));
         let mut messages: Vec<String> = Vec::new();
     
         for _ in 0..5 {
@@ -759,7 +761,28 @@ bitflags! {
 	}
 }
 
-enum_from_primitive! {
+// like enum_from_primitive but a value that isnt one of the variants becomes Unknown instead of None,
+// so a garbage byte in a message doesnt take the whole parser down with it
+macro_rules! enum_with_unknown {
+    ($(#[$attr:meta])* pub enum $name:ident { $($variant:ident = $value:expr,)* }) => {
+        $(#[$attr])*
+        pub enum $name {
+            $($variant,)*
+            Unknown(i32),
+        }
+
+        impl $name {
+            pub fn from_i32(value: i32) -> Self {
+                match value {
+                    $(v if v == $value => $name::$variant,)*
+                    _ => $name::Unknown(value),
+                }
+            }
+        }
+    };
+}
+
+enum_with_unknown! {
     #[derive(Debug, PartialEq, Clone)]
     pub enum HudMsgEffect {
         Fade = 0,
@@ -768,60 +791,60 @@ enum_from_primitive! {
     }
 }
 
-enum_from_primitive! {
-#[derive(Debug, PartialEq, Clone)]
+enum_with_unknown! {
+    #[derive(Debug, PartialEq, Clone)]
     pub enum HudChannel {
         NetMessage1 = 0,
-        NetMessage2,
-        NetMessage3,
-        NetMessage4,
-        NetMessage5,
-        NetMessage6
+        NetMessage2 = 1,
+        NetMessage3 = 2,
+        NetMessage4 = 3,
+        NetMessage5 = 4,
+        NetMessage6 = 5,
     }
 }
 
-enum_from_primitive! {
+enum_with_unknown! {
     #[derive(Debug, PartialEq, Clone)]
     pub enum SpectatorMode {
-        None,      // not in spectator mode
-        DeathCam,  // special mode for death cam animation
-        FreezeCam, // zooms to a target, and freeze-frames on them
-        Fixed,     // view from a fixed camera position
-        InEye,     // follow a player in first person view
-        Chase,     // follow a player in third person view
-        Roaming    // free roaming
+        None = 0,      // not in spectator mode
+        DeathCam = 1,  // special mode for death cam animation
+        FreezeCam = 2, // zooms to a target, and freeze-frames on them
+        Fixed = 3,     // view from a fixed camera position
+        InEye = 4,     // follow a player in first person view
+        Chase = 5,     // follow a player in third person view
+        Roaming = 6,   // free roaming
     }
 }
 
-enum_from_primitive! {
+enum_with_unknown! {
     #[derive(Debug, PartialEq, Clone)]
     pub enum PortalFizzleType {
         PortalFizzleSuccess = 0, // Placed fine (no fizzle)
-        PortalFizzleCantFit,
-        PortalFizzleOverlappedLinked,
-        PortalFizzleBadVolume,
-        PortalFizzleBadSurface,
-        PortalFizzleKilled,
-        PortalFizzleCleanser,
-        PortalFizzleClose,
-        PortalFizzleNearBlue,
-        PortalFizzleNearRed,
-        PortalFizzleNone,
+        PortalFizzleCantFit = 1,
+        PortalFizzleOverlappedLinked = 2,
+        PortalFizzleBadVolume = 3,
+        PortalFizzleBadSurface = 4,
+        PortalFizzleKilled = 5,
+        PortalFizzleCleanser = 6,
+        PortalFizzleClose = 7,
+        PortalFizzleNearBlue = 8,
+        PortalFizzleNearRed = 9,
+        PortalFizzleNone = 10,
     }
 }
 
-enum_from_primitive! {
+enum_with_unknown! {
     #[derive(Debug, PartialEq, Clone)]
     pub enum PaintType {
-        JumpPaint,
-        SpeedPaintOther,
-        SpeedPaint,
-        PortalPaint,
-        ClearPaint,
+        JumpPaint = 0,
+        SpeedPaintOther = 1,
+        SpeedPaint = 2,
+        PortalPaint = 3,
+        ClearPaint = 4,
     }
 }
 
-enum_from_primitive! {
+enum_with_unknown! {
     #[derive(Debug, PartialEq, Clone)]
     #[allow(non_camel_case_types)]
     pub enum RumbleLookup {
@@ -829,343 +852,409 @@ enum_from_primitive! {
         RumbleStopAll = 0, // cease all current rumbling effects.
 
         // Weapons
-        Pistol, Weap_357, Smg1, Ar2, ShotgunSingle, ShotgunDouble, Ar2AltFire,
-        RpgMissile, CrowbarSwing,
+        Pistol = 1, Weap_357 = 2, Smg1 = 3, Ar2 = 4, ShotgunSingle = 5, ShotgunDouble = 6, Ar2AltFire = 7,
+        RpgMissile = 8, CrowbarSwing = 9,
 
         // Vehicles
-        AirboatGun, JeepEngineLoop,
+        AirboatGun = 10, JeepEngineLoop = 11,
 
-        FlatLeft, FlatRight, FlatBoth,
+        FlatLeft = 12, FlatRight = 13, FlatBoth = 14,
 
         // Damage
-        DmgLow, DmgMed, DmgHigh,
+        DmgLow = 15, DmgMed = 16, DmgHigh = 17,
 
         // Fall damage
-        FallLong, FallShort,
+        FallLong = 18, FallShort = 19,
 
-        PhyscannonOpen, PhyscannonPunt, PhyscannonLow, PhyscannonMedium, PhyscannonHigh,
+        PhyscannonOpen = 20, PhyscannonPunt = 21, PhyscannonLow = 22, PhyscannonMedium = 23, PhyscannonHigh = 24,
 
-        PortalgunLeft, PortalgunRight, PortalPlacementFailure, NumRumbleEffects
+        PortalgunLeft = 25, PortalgunRight = 26, PortalPlacementFailure = 27, NumRumbleEffects = 28,
     }
 }
 
-enum_from_primitive! {
+enum_with_unknown! {
     #[derive(Debug, PartialEq, Clone)]
     pub enum ShakeCommand {
-        Start = 0,  // Starts the screen shake for all players within the radius.
-        Stop,       // Stops the screen shake for all players within the radius.
-        Amplitude,  // Modifies the amplitude of an active screen shake for all players within the radius.
-        Frequency,  // Modifies the frequency of an active screen shake for all players within the radius.
-        RumbleOnly, // Starts a shake effect that only rumbles the controller, no screen effect.
-        NoRumble    // Starts a shake that does NOT rumble the controller.
+        Start = 0,      // Starts the screen shake for all players within the radius.
+        Stop = 1,       // Stops the screen shake for all players within the radius.
+        Amplitude = 2,  // Modifies the amplitude of an active screen shake for all players within the radius.
+        Frequency = 3,  // Modifies the frequency of an active screen shake for all players within the radius.
+        RumbleOnly = 4, // Starts a shake effect that only rumbles the controller, no screen effect.
+        NoRumble = 5,   // Starts a shake that does NOT rumble the controller.
     }
 }
 
-enum_from_primitive! {
+enum_with_unknown! {
     #[derive(Debug, PartialEq, Clone)]
     pub enum TextMsgDestination {
-		PrintNotify = 1,
-		PrintConsole,
-		PrintTalk,
-		PrintCenter
-	}
+        PrintNotify = 1,
+        PrintConsole = 2,
+        PrintTalk = 3,
+        PrintCenter = 4,
+    }
 }
 
-// implementing Into<type> for every type in the UserMessageDataType enum (needed for dumping)
+// implementing TryFrom<UserMessageDataType> for every type in the enum (needed for dumping)
 
-impl Into<AchievementEvent> for UserMessageDataType {
-    fn into(self) -> AchievementEvent {
-        match self {
-            UserMessageDataType::AchievementEvent(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for AchievementEvent {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::AchievementEvent(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<Battery> for UserMessageDataType {
-    fn into(self) -> Battery {
-        match self {
-            UserMessageDataType::Battery(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for Battery {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::Battery(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<CloseCaption> for UserMessageDataType {
-    fn into(self) -> CloseCaption {
-        match self {
-            UserMessageDataType::CloseCaption(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for CloseCaption {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::CloseCaption(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<Damage> for UserMessageDataType {
-    fn into(self) -> Damage {
-        match self {
-            UserMessageDataType::Damage(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for Damage {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::Damage(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<EntityPortalled> for UserMessageDataType {
-    fn into(self) -> EntityPortalled {
-        match self {
-            UserMessageDataType::EntityPortalled(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for EntityPortalled {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::EntityPortalled(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<Fade> for UserMessageDataType {
-    fn into(self) -> Fade {
-        match self {
-            UserMessageDataType::Fade(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for Fade {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::Fade(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<Geiger> for UserMessageDataType {
-    fn into(self) -> Geiger {
-        match self {
-            UserMessageDataType::Geiger(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for Geiger {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::Geiger(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<HudMsg> for UserMessageDataType {
-    fn into(self) -> HudMsg {
-        match self {
-            UserMessageDataType::HudMsg(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for HudMsg {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::HudMsg(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<HudText> for UserMessageDataType {
-    fn into(self) -> HudText {
-        match self {
-            UserMessageDataType::HudText(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for HudText {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::HudText(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<KeyHintText> for UserMessageDataType {
-    fn into(self) -> KeyHintText {
-        match self {
-            UserMessageDataType::KeyHintText(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for KeyHintText {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::KeyHintText(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<KillCam> for UserMessageDataType {
-    fn into(self) -> KillCam {
-        match self {
-            UserMessageDataType::KillCam(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for KillCam {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::KillCam(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<LogoTimeMsg> for UserMessageDataType {
-    fn into(self) -> LogoTimeMsg {
-        match self {
-            UserMessageDataType::LogoTimeMsg(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for LogoTimeMsg {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::LogoTimeMsg(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<MpMapCompleted> for UserMessageDataType {
-    fn into(self) -> MpMapCompleted {
-        match self {
-            UserMessageDataType::MpMapCompleted(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for MpMapCompleted {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::MpMapCompleted(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<MpTauntEarned> for UserMessageDataType {
-    fn into(self) -> MpTauntEarned {
-        match self {
-            UserMessageDataType::MpTauntEarned(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for MpTauntEarned {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::MpTauntEarned(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<MpTauntLocked> for UserMessageDataType {
-    fn into(self) -> MpTauntLocked {
-        match self {
-            UserMessageDataType::MpTauntLocked(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for MpTauntLocked {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::MpTauntLocked(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<PaintEntity> for UserMessageDataType {
-    fn into(self) -> PaintEntity {
-        match self {
-            UserMessageDataType::PaintEntity(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for PaintEntity {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::PaintEntity(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<PaintWorld> for UserMessageDataType {
-    fn into(self) -> PaintWorld {
-        match self {
-            UserMessageDataType::PaintWorld(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for PaintWorld {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::PaintWorld(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<PortalFXSurface> for UserMessageDataType {
-    fn into(self) -> PortalFXSurface {
-        match self {
-            UserMessageDataType::PortalFXSurface(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for PortalFXSurface {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::PortalFXSurface(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<ResetHUD> for UserMessageDataType {
-    fn into(self) -> ResetHUD {
-        match self {
-            UserMessageDataType::ResetHUD(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for ResetHUD {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::ResetHUD(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<Rumble> for UserMessageDataType {
-    fn into(self) -> Rumble {
-        match self {
-            UserMessageDataType::Rumble(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for Rumble {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::Rumble(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SayText> for UserMessageDataType {
-    fn into(self) -> SayText {
-        match self {
-            UserMessageDataType::SayText(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for SayText {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::SayText(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SayText2> for UserMessageDataType {
-    fn into(self) -> SayText2 {
-        match self {
-            UserMessageDataType::SayText2(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for SayText2 {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::SayText2(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<ScoreboardTempUpdate> for UserMessageDataType {
-    fn into(self) -> ScoreboardTempUpdate {
-        match self {
-            UserMessageDataType::ScoreboardTempUpdate(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for ScoreboardTempUpdate {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::ScoreboardTempUpdate(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<Shake> for UserMessageDataType {
-    fn into(self) -> Shake {
-        match self {
-            UserMessageDataType::Shake(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for Shake {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::Shake(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<TextMsg> for UserMessageDataType {
-    fn into(self) -> TextMsg {
-        match self {
-            UserMessageDataType::TextMsg(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for TextMsg {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::TextMsg(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<Train> for UserMessageDataType {
-    fn into(self) -> Train {
-        match self {
-            UserMessageDataType::Train(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for Train {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::Train(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<TransitionFade> for UserMessageDataType {
-    fn into(self) -> TransitionFade {
-        match self {
-            UserMessageDataType::TransitionFade(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for TransitionFade {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::TransitionFade(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<VguiMenu> for UserMessageDataType {
-    fn into(self) -> VguiMenu {
-        match self {
-            UserMessageDataType::VguiMenu(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for VguiMenu {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::VguiMenu(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<VoiceMask> for UserMessageDataType {
-    fn into(self) -> VoiceMask {
-        match self {
-            UserMessageDataType::VoiceMask(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for VoiceMask {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::VoiceMask(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<HapPunch> for UserMessageDataType {
-    fn into(self) -> HapPunch {
-        match self {
-            UserMessageDataType::HapPunch(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for HapPunch {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::HapPunch(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<HapSetConstForce> for UserMessageDataType {
-    fn into(self) -> HapSetConstForce {
-        match self {
-            UserMessageDataType::HapSetConstForce(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for HapSetConstForce {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::HapSetConstForce(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<HapSetDrag> for UserMessageDataType {
-    fn into(self) -> HapSetDrag {
-        match self {
-            UserMessageDataType::HapSetDrag(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for HapSetDrag {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::HapSetDrag(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
 
-impl Into<SpHapWeaponEvent> for UserMessageDataType {
-    fn into(self) -> SpHapWeaponEvent {
-        match self {
-            UserMessageDataType::SpHapWeaponEvent(value) => value,
-            _ => panic!("how are you even seeing this?"),
+impl TryFrom<UserMessageDataType> for SpHapWeaponEvent {
+    type Error = UserMessageDataType;
+
+    fn try_from(value: UserMessageDataType) -> Result<Self, Self::Error> {
+        match value {
+            UserMessageDataType::SpHapWeaponEvent(value) => Ok(value),
+            other => Err(other),
         }
     }
 }
@@ -1175,48 +1264,49 @@ impl Into<SpHapWeaponEvent> for UserMessageDataType {
 pub fn write_usermsg_data_to_file(msg: UserMessage, file: &mut File) {
     match msg.msg_type {
         UserMessageType::AchievementEvent => {
-            let data: AchievementEvent = msg.data.into();
+            let Ok(data) = AchievementEvent::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tAchievement ID: {}", data.achievement_id));
         },
         UserMessageType::Battery => {
-            let data: Battery = msg.data.into();
+            let Ok(data) = Battery::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tBattery Value: {}", data.battery_val));
         },
         UserMessageType::CloseCaption => {
-            let data: CloseCaption = msg.data.into();
+            let Ok(data) = CloseCaption::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tToken Name: {}", data.token_name));
             file.write_fmt(format_args!("\n\t\t\tDuration: {}", data.duration));
             file.write_fmt(format_args!("\n\t\t\tFlags: {}", bitflags_to_string(data.flags.iter_names())));
         },
         UserMessageType::Damage => {
-            let data: Damage = msg.data.into();
+            let Ok(data) = Damage::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tArmor: {}", data.armor));
             file.write_fmt(format_args!("\n\t\t\tDamage Taken: {}", data.damage_taken));
             file.write_fmt(format_args!("\n\t\t\tVisible Bits Damage: {}", bitflags_to_string(data.visible_bits_damage.iter_names())));
             file.write_fmt(format_args!("\n\t\t\tVec From: {}", data.vec_from));
         },
         UserMessageType::EntityPortalled => {
-            let data: EntityPortalled = msg.data.into();
+            let Ok(data) = EntityPortalled::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tPortal: {}", data.portal));
             file.write_fmt(format_args!("\n\t\t\tPortalled: {}", data.portalled));
             file.write_fmt(format_args!("\n\t\t\tNew Position: {}", data.new_position));
             file.write_fmt(format_args!("\n\t\t\tNew Angles: {}", data.new_angles));
         },
         UserMessageType::Fade => {
-            let data: Fade = msg.data.into();
+            let Ok(data) = Fade::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tDuration: {}", data.duration));
             file.write_fmt(format_args!("\n\t\t\tHold Time: {}", data.hold_time));
             file.write_fmt(format_args!("\n\t\t\tFlags: {}", bitflags_to_string(data.flags.iter_names())));
             file.write_fmt(format_args!("\n\t\t\tRGBA: {}, {}, {}, {}", data.r, data.g, data.b, data.a));
         },
         UserMessageType::Geiger => {
-            let data: Geiger = msg.data.into();
+            let Ok(data) = Geiger::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tGeiger Range: {}", data.geiger_range));
         },
         UserMessageType::HudMsg => {
-            let data: HudMsg = msg.data.into();
+            let Ok(data) = HudMsg::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tChannel: {:?}", data.channel));
-            let msg_info = data.msg_info.unwrap();
+            // short messages only have the channel
+            let Some(msg_info) = data.msg_info else { return; };
             file.write_fmt(format_args!("\n\t\t\tMessage Info:", ));
             file.write_fmt(format_args!("\n\t\t\t\tX, Y: {}, {}", msg_info.x, msg_info.y));
             file.write_fmt(format_args!("\n\t\t\t\tRGBA 1: {}, {}, {}, {}", msg_info.r1, msg_info.g1, msg_info.b1, msg_info.a1));
@@ -1227,47 +1317,47 @@ pub fn write_usermsg_data_to_file(msg: UserMessage, file: &mut File) {
             file.write_fmt(format_args!("\n\t\t\t\tMessage: {}", msg_info.message));
         },
         UserMessageType::HudText => {
-            let data: HudText = msg.data.into();
+            let Ok(data) = HudText::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tString: {}", data.string));
         },
         UserMessageType::KeyHintText => {
-            let data: KeyHintText = msg.data.into();
+            let Ok(data) = KeyHintText::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tCount: {}", data.count));
             file.write_fmt(format_args!("\n\t\t\tKey String: {}", data.key_string));
         },
         UserMessageType::KillCam => {
-            let data: KillCam = msg.data.into();
+            let Ok(data) = KillCam::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tSpectator Mode: {:?}", data.spec_mode));
             file.write_fmt(format_args!("\n\t\t\tTarget 1: {}", data.target1));
             file.write_fmt(format_args!("\n\t\t\tTarget 2: {}", data.target2));
             file.write_fmt(format_args!("\n\t\t\tUnknown Byte: {}", data.unknown));
         },
         UserMessageType::LogoTimeMsg => {
-            let data: LogoTimeMsg = msg.data.into();
+            let Ok(data) = LogoTimeMsg::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tTime: {}", data.time));
         },
         UserMessageType::MpMapCompleted => {
-            let data: MpMapCompleted = msg.data.into();
+            let Ok(data) = MpMapCompleted::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tBranch: {}", data.branch));
             file.write_fmt(format_args!("\n\t\t\tLevel: {}", data.level));
         },
         UserMessageType::MpTauntEarned => {
-            let data: MpTauntEarned = msg.data.into();
+            let Ok(data) = MpTauntEarned::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tTaunt Name: {}", data.taunt_name));
             file.write_fmt(format_args!("\n\t\t\tAward Silently: {}", data.award_silently));
         },
         UserMessageType::MpTauntLocked => {
-            let data: MpTauntLocked = msg.data.into();
+            let Ok(data) = MpTauntLocked::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tTaunt Name: {}", data.taunt_name));
         },
         UserMessageType::PaintEntity => {
-            let data: PaintEntity = msg.data.into();
+            let Ok(data) = PaintEntity::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tEntity: {}", data.ent));
             file.write_fmt(format_args!("\n\t\t\tPaint Type: {:?}", data.paint_type));
             file.write_fmt(format_args!("\n\t\t\tPos: {}", data.pos));
         },
         UserMessageType::PaintWorld => {
-            let data: PaintWorld = msg.data.into();
+            let Ok(data) = PaintWorld::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tPaint Type: {:?}", data.paint_type));
             file.write_fmt(format_args!("\n\t\t\tEntity: {}", data.ehandle));
             file.write_fmt(format_args!("\n\t\t\tUnknown HF1: {}", data.unkhf1));
@@ -1280,10 +1370,10 @@ pub fn write_usermsg_data_to_file(msg: UserMessage, file: &mut File) {
                 pos_str.push_str(&data.positions[i as usize].to_string());
                 pos_str.push_str(", ");
             }
-            file.write_fmt(format_args!("{}]", pos_str[..pos_str.len()-2].to_string()));
+            file.write_fmt(format_args!("{}]", &pos_str[..pos_str.len()-2]));
         },
         UserMessageType::PortalFXSurface => {
-            let data: PortalFXSurface = msg.data.into();
+            let Ok(data) = PortalFXSurface::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tPortal Ent: {}", data.portal_ent));
             file.write_fmt(format_args!("\n\t\t\tOwner Ent: {}", data.owner_ent));
             file.write_fmt(format_args!("\n\t\t\tTeam: {}", data.team));
@@ -1302,23 +1392,23 @@ pub fn write_usermsg_data_to_file(msg: UserMessage, file: &mut File) {
             ));
         }
         UserMessageType::ResetHUD => {
-            let data: ResetHUD = msg.data.into();
+            let Ok(data) = ResetHUD::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tUnknown Byte: {}", data.unknown));
         },
         UserMessageType::Rumble => {
-            let data: Rumble = msg.data.into();
+            let Ok(data) = Rumble::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tRumble Type: {:?}", data.rumble_type));
             file.write_fmt(format_args!("\n\t\t\tScale: {}", data.scale));
             file.write_fmt(format_args!("\n\t\t\tRumble Flags: {}", bitflags_to_string(data.rumble_flags.iter_names())));
         },
         UserMessageType::SayText => {
-            let data: SayText = msg.data.into();
+            let Ok(data) = SayText::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tClient ID: {}", data.client_id));
             file.write_fmt(format_args!("\n\t\t\tText: {}", data.text));
             file.write_fmt(format_args!("\n\t\t\tWants To Chat: {}", data.wants_to_chat));
         },
         UserMessageType::SayText2 => {
-            let data: SayText2 = msg.data.into();
+            let Ok(data) = SayText2::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tClient: {}", data.client));
             file.write_fmt(format_args!("\n\t\t\tWants To Chat: {}", data.wants_to_chat));
             file.write_fmt(format_args!("\n\t\t\tMessage Name: {}", data.msg_name));
@@ -1328,19 +1418,19 @@ pub fn write_usermsg_data_to_file(msg: UserMessage, file: &mut File) {
             }
         },
         UserMessageType::ScoreboardTempUpdate => {
-            let data: ScoreboardTempUpdate = msg.data.into();
+            let Ok(data) = ScoreboardTempUpdate::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tNum Portals: {}", data.num_portals));
             file.write_fmt(format_args!("\n\t\t\tTime Taken: {}", data.time_taken as f32 / 100.0));
         },
         UserMessageType::Shake => {
-            let data: Shake = msg.data.into();
+            let Ok(data) = Shake::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tCommand: {:?}", data.command));
             file.write_fmt(format_args!("\n\t\t\tAmplitude: {}", data.amplitude));
             file.write_fmt(format_args!("\n\t\t\tFrequency: {}", data.frequency));
             file.write_fmt(format_args!("\n\t\t\tDuration: {}", data.duration));
         },
         UserMessageType::TextMsg => {
-            let data: TextMsg = msg.data.into();
+            let Ok(data) = TextMsg::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tDestination: {:?}", data.destination));
             file.write_all("\n\t\t\tMessages:".as_bytes());
             for i in 0..5 {
@@ -1348,15 +1438,15 @@ pub fn write_usermsg_data_to_file(msg: UserMessage, file: &mut File) {
             }
         },
         UserMessageType::Train => {
-            let data: Train = msg.data.into();
+            let Ok(data) = Train::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tPos: {}", data.pos));
         },
         UserMessageType::TransitionFade => {
-            let data: TransitionFade = msg.data.into();
+            let Ok(data) = TransitionFade::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tSeconds: {}", data.seconds));
         },
         UserMessageType::VguiMenu => {
-            let data: VguiMenu = msg.data.into();
+            let Ok(data) = VguiMenu::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tMessage: {}", data.message));
             file.write_fmt(format_args!("\n\t\t\tShow: {}", data.show));
             file.write_fmt(format_args!("\n\t\t\tCount: {}", data.count));
@@ -1366,7 +1456,7 @@ pub fn write_usermsg_data_to_file(msg: UserMessage, file: &mut File) {
             }
         },
         UserMessageType::VoiceMask => {
-            let data: VoiceMask = msg.data.into();
+            let Ok(data) = VoiceMask::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tVoice Max Players: {}", data.voice_max_players));
             file.write_all("\n\t\t\tPlayer Masks:".as_bytes());
             for i in 0..data.voice_max_players {
@@ -1375,23 +1465,110 @@ pub fn write_usermsg_data_to_file(msg: UserMessage, file: &mut File) {
             file.write_fmt(format_args!("\n\t\t\tPlayer Mod Enable: {}", data.player_mod_enable));
         },
         UserMessageType::HapPunch => {
-            let data: HapPunch = msg.data.into();
+            let Ok(data) = HapPunch::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tF1, F2, F3: {}, {}, {}", data.f1, data.f2, data.f3));
         },
         UserMessageType::HapSetDrag => {
-            let data: HapSetDrag = msg.data.into();
+            let Ok(data) = HapSetDrag::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tUnknown Float: {}", data.unknown));
         },
         UserMessageType::SPHapWeaponEvent => {
-            let data: SpHapWeaponEvent = msg.data.into();
+            let Ok(data) = SpHapWeaponEvent::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tUnknown Int: {}", data.unk));
         },
         UserMessageType::HapSetConstForce => {
-            let data: HapSetConstForce = msg.data.into();
+            let Ok(data) = HapSetConstForce::try_from(msg.data) else { return; };
             file.write_fmt(format_args!("\n\t\t\tS1, S2, S3: {}, {}, {}", data.s1, data.s2, data.s3));
         },
         _ => {
             file.write_all("\n\t\t\tDATA UNKNOWN OR NOT IMPLEMENTED".as_bytes());
         },
     }
-}
\ No newline at end of file
+}
+
+// json export, see json.rs
+json_object!(AchievementEvent { achievement_id });
+json_object!(Battery { battery_val });
+json_object!(CloseCaption { token_name, duration, flags });
+json_object!(Damage { armor, damage_taken, visible_bits_damage, vec_from });
+json_object!(EntityPortalled { portal, portalled, new_position, new_angles });
+json_object!(Fade { duration, hold_time, flags, r, g, b, a });
+json_object!(Geiger { geiger_range });
+json_object!(HudMsgInfo { x, r1, r2, effect, fade_in, message });
+json_object!(HudMsg { channel, msg_info });
+json_object!(HudText { string });
+json_object!(KeyHintText { count, key_string });
+json_object!(KillCam { spec_mode, target1, target2, unknown });
+json_object!(LogoTimeMsg { time });
+json_object!(MpMapCompleted { branch, level });
+json_object!(MpMapCompletedData {});
+json_object!(MpTauntEarned { taunt_name, award_silently });
+json_object!(MpTauntLocked { taunt_name });
+json_object!(PaintEntity { ent, paint_type, pos });
+json_object!(PaintWorld { paint_type, ehandle, unkhf1, unkhf2, length, center, positions });
+json_object!(PortalFXSurface { portal_ent, owner_ent, team, portal_num, effect, origin, angles });
+json_object!(ResetHUD { unknown });
+json_object!(Rumble { rumble_type, scale, rumble_flags });
+json_object!(SayText { client_id, text, wants_to_chat });
+json_object!(SayText2 { client, wants_to_chat, msg_name, msgs });
+json_object!(ScoreboardTempUpdate { num_portals, time_taken });
+json_object!(Shake { command, amplitude, frequency, duration });
+json_object!(TextMsg { destination, messages });
+json_object!(Train { pos });
+json_object!(TransitionFade { seconds });
+json_object!(VguiMenu { message, show, count, key_values });
+json_object!(PlayerMask { game_rules_mask, ban_mask });
+json_object!(VoiceMask { voice_max_players, player_masks, player_mod_enable });
+json_object!(HapPunch { f1 });
+json_object!(HapSetConstForce { s1 });
+json_object!(HapSetDrag { unknown });
+json_object!(SpHapWeaponEvent { unk });
+json_debug_string!(UserMessageType, HudMsgEffect, HudChannel, SpectatorMode, PortalFizzleType, PaintType, RumbleLookup, ShakeCommand, TextMsgDestination);
+json_flags!(DamageType, RumbleFlags, FadeFlags, CloseCaptionFlags);
+
+impl ToJson for UserMessageDataType {
+    fn to_json(&self) -> JsonValue {
+        return match self {
+            UserMessageDataType::Unknown => JsonValue::Null,
+            UserMessageDataType::AchievementEvent(data) => data.to_json(),
+            UserMessageDataType::Battery(data) => data.to_json(),
+            UserMessageDataType::CloseCaption(data) => data.to_json(),
+            UserMessageDataType::Damage(data) => data.to_json(),
+            UserMessageDataType::EntityPortalled(data) => data.to_json(),
+            UserMessageDataType::Fade(data) => data.to_json(),
+            UserMessageDataType::Geiger(data) => data.to_json(),
+            UserMessageDataType::HudMsg(data) => data.to_json(),
+            UserMessageDataType::HudText(data) => data.to_json(),
+            UserMessageDataType::KeyHintText(data) => data.to_json(),
+            UserMessageDataType::KillCam(data) => data.to_json(),
+            UserMessageDataType::LogoTimeMsg(data) => data.to_json(),
+            UserMessageDataType::MpMapCompleted(data) => data.to_json(),
+            UserMessageDataType::MpTauntEarned(data) => data.to_json(),
+            UserMessageDataType::MpTauntLocked(data) => data.to_json(),
+            UserMessageDataType::PaintEntity(data) => data.to_json(),
+            UserMessageDataType::PaintWorld(data) => data.to_json(),
+            UserMessageDataType::PortalFXSurface(data) => data.to_json(),
+            UserMessageDataType::ResetHUD(data) => data.to_json(),
+            UserMessageDataType::Rumble(data) => data.to_json(),
+            UserMessageDataType::SayText(data) => data.to_json(),
+            UserMessageDataType::SayText2(data) => data.to_json(),
+            UserMessageDataType::ScoreboardTempUpdate(data) => data.to_json(),
+            UserMessageDataType::Shake(data) => data.to_json(),
+            UserMessageDataType::TextMsg(data) => data.to_json(),
+            UserMessageDataType::Train(data) => data.to_json(),
+            UserMessageDataType::TransitionFade(data) => data.to_json(),
+            UserMessageDataType::VguiMenu(data) => data.to_json(),
+            UserMessageDataType::VoiceMask(data) => data.to_json(),
+            UserMessageDataType::HapPunch(data) => data.to_json(),
+            UserMessageDataType::HapSetConstForce(data) => data.to_json(),
+            UserMessageDataType::HapSetDrag(data) => data.to_json(),
+            UserMessageDataType::SpHapWeaponEvent(data) => data.to_json(),
+        };
+    }
+}
+
+impl ToJson for UserMessage {
+    fn to_json(&self) -> JsonValue {
+        return JsonValue::object(vec![("type", self.msg_type.to_json()), ("data", self.data.to_json())]);
+    }
+}
@@ -1,10 +1,14 @@
 use std::collections::HashMap;
 use core::fmt;
 use crate::bitreader::BitReader;
-use crate::structs::net_svc_message::{NetSvcMessage, NetSvcMessageTypes};
+use crate::structs::net_svc_message::{NetSvcMessage, NetSvcMessageDataTypes};
 use crate::structs::data_manager::DataManager;
+use crate::structs::entity_manager::{EntityManager, Entity};
+
+use crate::enum_primitive::enum_from_primitive;
+use crate::enum_primitive::FromPrimitive;
+use crate::json::{JsonValue, ToJson, json_object, json_debug_string, json_flags};
 
-use super::netsvc_types::SvcSetPause;
 
 // used a bunch in usermessages
 // dont know what these really are
@@ -21,6 +25,11 @@ impl EHandle {
     pub fn serial(&self) -> i32 {
         return self.val >> 11
     }
+
+    // the entity this handle points to, None if it doesnt exist anymore
+    pub fn resolve<'a>(&self, ent_mgr: &'a EntityManager) -> Option<&'a Entity> {
+        return ent_mgr.resolve_ehandle(self);
+    }
 }
 
 impl fmt::Display for EHandle {
@@ -58,15 +67,6 @@ impl ServerClass {
 }
 
 // placeholders for now
-#[derive(Debug, Clone)]
-pub struct StringTable;
-
-impl StringTable {
-    pub fn new() -> Self {
-        Self
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct VoiceData;
 
@@ -76,9 +76,6 @@ impl VoiceData {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct SoundInfo;
-
 #[derive(Debug, Clone)]
 pub struct SplitScreenData;
 
@@ -88,30 +85,80 @@ impl SplitScreenData {
     }
 }
 
+// entity messages are sent from a specific entity on the server to the same entity on the client
+// the format of the data depends on the class that sent it (the class id in the message) and isnt networked anywhere,
+// so every class gets its own decoder and everything else is kept as bytes.
+// portal 1 doesnt send any that we know of, prop_portal and the prop classes dont override ReceiveMessage on the client,
+// so the strider from hl2 is the only decoder and anything portal sends ends up in Raw
+#[derive(Debug, Clone)]
+pub struct EntityMessageData {
+    pub class_name: Option<String>, // from server_class_info, None if we dont have the classes
+    pub data: EntityMessageDataType,
+}
+
 #[derive(Debug, Clone)]
-pub struct EntityMessageData;
+pub enum EntityMessageDataType {
+    Strider(StriderMessage),
+    Raw(Vec<u8>), // fallback for every other class, if the length isnt a multiple of 8 the last byte only has the remaining bits
+}
 
 impl EntityMessageData {
-    pub fn new() -> Self {
-        Self
+    pub fn parse(reader: &mut BitReader, data_mgr: &DataManager, class_id: i32, length: i32) -> Self {
+        let class_name = data_mgr.server_class_info.get(class_id as usize).map(|c| c.class_name.clone());
+        let start = reader.current;
+
+        let decoded = match class_name.as_deref() {
+            Some("CNPC_Strider") => StriderMessage::parse(reader).map(EntityMessageDataType::Strider),
+            _ => None,
+        };
+
+        // the decoders dont have to read everything, and if one of them didnt like the data the bytes are still there
+        let data = match decoded {
+            Some(data) if reader.current <= start + length as usize => data,
+            _ => {
+                reader.current = start;
+                reader.fetch();
+                let mut bytes = reader.read_bytes(length / 8);
+                if length % 8 != 0 {
+                    bytes.push(reader.read_int(length % 8) as u8);
+                }
+                EntityMessageDataType::Raw(bytes)
+            },
+        };
+        reader.current = start + length as usize;
+        reader.fetch();
+
+        Self { class_name: class_name, data: data }
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct PacketEntitiesData;
-
-impl PacketEntitiesData {
-    pub fn new() -> Self {
-        Self
+enum_from_primitive! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum StriderMessageType {
+        BigShot = 1, // the cannon fires
+        Streaks = 2, // the cannon starts charging
+        Dead = 3,
     }
 }
 
+// half-life 2 and the episodes, C_Strider::ReceiveMessage
 #[derive(Debug, Clone)]
-pub struct TempEntitiesData;
-
-impl TempEntitiesData {
-    pub fn new() -> Self {
-        Self
+pub struct StriderMessage {
+    pub message_type: StriderMessageType,
+    pub position: Option<Vec3>, // where the cannon is aimed at, not sent for Dead
+}
+
+impl StriderMessage {
+    pub fn parse(reader: &mut BitReader) -> Option<Self> {
+        let message_type = StriderMessageType::from_i32(reader.read_int(8))?;
+        let position = if message_type == StriderMessageType::Dead {
+            None
+        } else {
+            let coords = reader.read_vector_coords();
+            Some(Vec3 { x: coords[0].unwrap_or(0.0), y: coords[1].unwrap_or(0.0), z: coords[2].unwrap_or(0.0) })
+        };
+
+        return Some(Self { message_type: message_type, position: position });
     }
 }
 
@@ -142,6 +189,159 @@ impl PaintmapData {
     }
 }
 
+// sounds played by the server
+// every sound in SvcSounds is delta encoded from the one before it, the first one from the default values
+// https://github.com/UncraftedName/UntitledParser/blob/master/DemoParser/src/Parser/Components/Messages/SvcSounds.cs
+#[derive(Debug, Clone)]
+pub struct SoundInfo {
+    pub entity_index: i32,
+    pub sound_num: i32,
+    pub sound_name: Option<String>, // from the soundprecache stringtable
+    pub flags: SoundFlags,
+    pub channel: SoundChannel,
+    pub is_ambient: bool,
+    pub is_sentence: bool,
+    pub sequence_number: i32,
+    pub volume: f32,
+    pub sound_level: i32,
+    pub pitch: i32,
+    pub delay: f32, // in seconds
+    pub origin: Vec3,
+    pub speaker_entity: i32,
+}
+
+impl SoundInfo {
+    pub fn new() -> Self {
+        Self {
+            entity_index: 0,
+            sound_num: 0,
+            sound_name: None,
+            flags: SoundFlags::None,
+            channel: SoundChannel::Static,
+            is_ambient: false,
+            is_sentence: false,
+            sequence_number: 0,
+            volume: 1f32,
+            sound_level: 75,
+            pitch: 100,
+            delay: 0f32,
+            origin: Vec3::new(),
+            speaker_entity: -1,
+        }
+    }
+
+    pub fn parse(reader: &mut BitReader, data_mgr: &DataManager, delta: &SoundInfo) -> Self {
+        let mut info = delta.clone();
+
+        if reader.read_bool() {
+            let bits = if reader.read_bool() { 5 } else { 11 };
+            info.entity_index = reader.read_int(bits);
+        }
+
+        if let Some(sound_num) = reader.read_int_if_exists(data_mgr.sound_index_bits) {
+            info.sound_num = sound_num;
+        }
+        if let Some(flags) = reader.read_int_if_exists(data_mgr.sound_flag_bits) {
+            info.flags = SoundFlags::from_bits_truncate(flags);
+        }
+        if let Some(channel) = reader.read_int_if_exists(3) {
+            info.channel = SoundChannel::from_i32(channel).unwrap(); // 3 bits so its always valid
+        }
+        info.is_ambient = reader.read_bool();
+        info.is_sentence = reader.read_bool();
+
+        if info.flags != SoundFlags::Stop {
+            if reader.read_bool() {
+                // same sequence number
+            } else if reader.read_bool() {
+                info.sequence_number += 1;
+            } else {
+                info.sequence_number = reader.read_int(10);
+            }
+
+            if let Some(volume) = reader.read_int_if_exists(7) {
+                info.volume = volume as f32 / 127f32;
+            }
+            if let Some(sound_level) = reader.read_int_if_exists(9) {
+                info.sound_level = sound_level;
+            }
+            if let Some(pitch) = reader.read_int_if_exists(8) {
+                info.pitch = pitch;
+            }
+
+            if reader.read_bool() {
+                let mut delay = reader.read_signed_int(13) as f32 / 1000f32;
+                if delay < 0f32 {
+                    delay *= 10f32;
+                }
+                info.delay = delay - 0.1; // source biases it for whatever reason
+            }
+
+            if reader.read_bool() {
+                info.origin.x = reader.read_vector_coord();
+            }
+            if reader.read_bool() {
+                info.origin.y = reader.read_vector_coord();
+            }
+            if reader.read_bool() {
+                info.origin.z = reader.read_vector_coord();
+            }
+
+            if reader.read_bool() {
+                info.speaker_entity = reader.read_signed_int(12);
+            }
+        } else {
+            // stopped sounds dont have any of that
+            info.sequence_number = 0;
+            info.volume = 0f32;
+            info.sound_level = 0;
+            info.pitch = 100;
+            info.delay = 0f32;
+            info.speaker_entity = -1;
+        }
+
+        info.sound_name = if info.is_sentence {
+            None // sentences are in a different table
+        } else {
+            data_mgr.get_stringtable("soundprecache").and_then(|t| t.table_entries.get(info.sound_num as usize)).map(|e| e.name.clone())
+        };
+
+        return info;
+    }
+}
+
+bitflags::bitflags! {
+    #[derive(Debug, PartialEq, Clone)]
+    pub struct SoundFlags : i32 {
+        const None = 0;
+        const ChangeVolume = 1;
+        const ChangePitch = 1 << 1;
+        const Stop = 1 << 2;
+        const Spawning = 1 << 3; // we're spawning, used in some cases for ambients
+        const Delay = 1 << 4;
+        const StopLooping = 1 << 5;
+        const Speaker = 1 << 6; // being played again by a microphone through a speaker
+        const ShouldPause = 1 << 7; // this sound should be paused if the game is paused
+        const IgnorePhonemes = 1 << 8;
+        const IgnoreName = 1 << 9;
+        const DoNotOverwriteExistingOnChannel = 1 << 10; // steampipe only
+    }
+}
+
+enum_from_primitive! {
+    #[derive(Debug, PartialEq, Clone)]
+    pub enum SoundChannel {
+        Auto = 0,
+        Weapon,
+        Voice,
+        Item,
+        Body,
+        Stream, // allocate stream channel from the static or dynamic area
+        Static, // allocate channel from the static area
+        VoiceBase, // allocate channel for network voice data
+    }
+}
+
 // GameEvent stuff
 /*
 first big thing i implemented for this parser
@@ -168,17 +368,17 @@ pub enum GameEventKeyTypes {
     UInt64(u64),
 }
 
-impl GameEventKeyTypes {
-    pub fn to_string(self) -> String {
+impl fmt::Display for GameEventKeyTypes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self  {
-            Self::Boolean(value) => value.to_string(),
-            Self::Float(value) => value.to_string(),
-            Self::Int16(value) => value.to_string(),
-            Self::Int32(value) => value.to_string(),
-            Self::Int8(value) => value.to_string(),
-            Self::String(value) => value,
-            Self::UInt64(value) => value.to_string(),
-            _ => "you arent supposed to be seeing this".to_string(),
+            Self::Boolean(value) => write!(f, "{}", value),
+            Self::Float(value) => write!(f, "{}", value),
+            Self::Int16(value) => write!(f, "{}", value),
+            Self::Int32(value) => write!(f, "{}", value),
+            Self::Int8(value) => write!(f, "{}", value),
+            Self::String(value) => write!(f, "{}", value),
+            Self::UInt64(value) => write!(f, "{}", value),
+            _ => write!(f, "you arent supposed to be seeing this"),
         }
     }
 }
@@ -301,9 +501,8 @@ impl fmt::Display for Vec2 {
 
 // theres a better way to go about this whole pause checking thing probably
 // too bad
-pub fn check_for_pause(messages: &Vec<NetSvcMessage>, data_mgr: &mut DataManager) -> bool {
-    let data: SvcSetPause = messages.iter().find(|m| {m.msg_type == NetSvcMessageTypes::SvcSetPause}).unwrap().data.clone().into();
-    return data.paused;
+pub fn check_for_pause(messages: &[NetSvcMessage], _data_mgr: &mut DataManager) -> bool {
+    return messages.iter().find_map(|m| match &m.data { NetSvcMessageDataTypes::SvcSetPause(data) => Some(data.paused), _ => None }).unwrap_or(false);
 }
 
 // log2(x) + 1; used a bunch in important places
@@ -317,6 +516,53 @@ pub fn log2_of_x_plus_one(x: i32) -> i32 {
     return j + 1;
 }
 
+// source's lzss decompression, used for compressed stringtable data
+// the data starts with "LZSS" and the decompressed size
+// https://github.com/ValveSoftware/source-sdk-2013/blob/master/sp/src/tier1/lzss.cpp
+pub fn decompress_lzss(data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() < 8 || &data[0..4] != b"LZSS" {
+        return None;
+    }
+    let actual_size = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+    // the size comes from the file, a corrupt one shouldnt make us allocate gigabytes. lzss cant grow data more than about 8x
+    let mut output: Vec<u8> = Vec::with_capacity(actual_size.min(data.len() * 8));
+    let mut index = 8;
+    let mut cmd_byte = 0u8;
+    let mut get_cmd_byte = 0;
+
+    loop {
+        if get_cmd_byte == 0 {
+            cmd_byte = *data.get(index)?;
+            index += 1;
+        }
+        get_cmd_byte = (get_cmd_byte + 1) & 0x07;
+
+        if cmd_byte & 1 != 0 {
+            // copy count bytes from position bytes back in the output
+            let position = ((*data.get(index)? as usize) << 4) | ((*data.get(index + 1)? as usize) >> 4);
+            let count = (data[index + 1] & 0x0F) as usize + 1;
+            index += 2;
+            if count == 1 {
+                break;
+            }
+            let source = output.len().checked_sub(position + 1)?;
+            for i in 0..count {
+                output.push(output[source + i]);
+            }
+        } else {
+            output.push(*data.get(index)?);
+            index += 1;
+        }
+        cmd_byte >>= 1;
+    }
+
+    if output.len() != actual_size {
+        return None;
+    }
+
+    return Some(output);
+}
+
 // below is something i shouldve done a long time ago
 
 pub fn bitflags_to_string<B: bitflags::Flags>(names: bitflags::iter::IterNames<B>) -> String {
@@ -325,11 +571,55 @@ pub fn bitflags_to_string<B: bitflags::Flags>(names: bitflags::iter::IterNames<B
         flag_str.push_str(name.0);
         flag_str.push_str(" | ");
     }
-    if flag_str == "" {
+    if flag_str.is_empty() {
         flag_str = "None".to_string();
     } else {
         flag_str = flag_str[..flag_str.len() - 3].to_string();
     }
     
     return flag_str;
-}
\ No newline at end of file
+}
+
+// json export, see json.rs
+json_object!(EHandle { val });
+json_object!(ConVar { convar_name, convar_value });
+json_object!(ServerClass { datatable_id, class_name, data_table_name });
+json_object!(VoiceData {});
+json_object!(SplitScreenData {});
+json_object!(EntityMessageData { class_name, data });
+json_object!(StriderMessage { message_type, position });
+json_object!(MenuData {});
+json_object!(CmdKeyValuesData {});
+json_object!(PaintmapData {});
+json_object!(SoundInfo { entity_index, sound_num, sound_name, flags, channel, is_ambient, is_sentence, sequence_number, volume, sound_level, pitch, delay, origin, speaker_entity });
+json_object!(GameEvent { descriptor, keys });
+json_object!(GameEventDescriptor { event_id, name, keys });
+json_object!(GameEventList { events, length, data });
+json_object!(Vec3 { x, y, z });
+json_object!(Vec2 { x, y });
+json_debug_string!(SoundChannel, StriderMessageType);
+json_flags!(SoundFlags);
+
+impl ToJson for EntityMessageDataType {
+    fn to_json(&self) -> JsonValue {
+        return match self {
+            EntityMessageDataType::Strider(data) => data.to_json(),
+            EntityMessageDataType::Raw(bytes) => bytes.to_json(),
+        };
+    }
+}
+
+impl ToJson for GameEventKeyTypes {
+    fn to_json(&self) -> JsonValue {
+        return match self {
+            GameEventKeyTypes::None => JsonValue::Null,
+            GameEventKeyTypes::String(value) => value.to_json(),
+            GameEventKeyTypes::Float(value) => value.to_json(),
+            GameEventKeyTypes::Int32(value) => value.to_json(),
+            GameEventKeyTypes::Int16(value) => value.to_json(),
+            GameEventKeyTypes::Int8(value) => value.to_json(),
+            GameEventKeyTypes::Boolean(value) => value.to_json(),
+            GameEventKeyTypes::UInt64(value) => value.to_json(),
+        };
+    }
+}